@@ -0,0 +1,176 @@
+//! In-memory wrapped-stats aggregation over a fetched `Vec<ScrobbleRecord>`.
+//!
+//! `wrapped::calculate_wrapped_stats` computes a similar summary, but from Postgres
+//! materialized views that only exist once scrobbles have been imported. CLI tools like
+//! `test_fetch` just have a `Vec<ScrobbleRecord>` in hand with nothing stored yet, so this
+//! computes the equivalent summary directly from that list.
+
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::atproto::ScrobbleRecord;
+
+const TOP_N: usize = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TopArtist {
+    pub name: String,
+    pub plays: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TopTrack {
+    pub track_name: String,
+    pub artist_name: String,
+    pub plays: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TopRelease {
+    pub release_name: String,
+    pub plays: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WrappedStats {
+    pub total_plays: u32,
+    /// `None` when none of the records carried a `duration`, rather than reporting zero.
+    pub total_listening_minutes: Option<f64>,
+    pub top_artists: Vec<TopArtist>,
+    pub top_tracks: Vec<TopTrack>,
+    pub top_releases: Vec<TopRelease>,
+    /// Plays per calendar month, indexed 0 (January) through 11 (December).
+    pub plays_by_month: [u32; 12],
+    /// Plays per UTC hour-of-day, indexed 0 through 23.
+    pub plays_by_hour: [u32; 24],
+    pub longest_daily_streak: u32,
+}
+
+fn normalize(name: &str) -> String {
+    name.to_lowercase().trim().to_string()
+}
+
+/// The (identity, display name) of a record's primary artist, identified by MBID when
+/// present so MusicBrainz-enriched and bare records for the same artist merge into one
+/// bucket, falling back to the normalized name otherwise.
+fn primary_artist_identity(record: &ScrobbleRecord) -> Option<(String, String)> {
+    let name = record.artists.first()?.clone();
+    let identity = record
+        .artist_mb_ids
+        .as_ref()
+        .and_then(|ids| ids.first())
+        .cloned()
+        .unwrap_or_else(|| normalize(&name));
+    Some((identity, name))
+}
+
+/// Aggregate a fetched scrobble history into wrapped-style summary stats.
+pub fn aggregate(records: &[ScrobbleRecord]) -> WrappedStats {
+    let total_plays = records.len() as u32;
+
+    let mut total_duration_ms: i64 = 0;
+    let mut has_duration = false;
+
+    // artist identity -> (display name, play count)
+    let mut artist_counts: HashMap<String, (String, u32)> = HashMap::new();
+    // (normalized track name, artist identity) -> (track name, artist name, play count)
+    let mut track_counts: HashMap<(String, String), (String, String, u32)> = HashMap::new();
+    // normalized release name -> (display name, play count)
+    let mut release_counts: HashMap<String, (String, u32)> = HashMap::new();
+
+    let mut plays_by_month = [0u32; 12];
+    let mut plays_by_hour = [0u32; 24];
+    let mut play_dates: Vec<NaiveDate> = Vec::new();
+
+    for record in records {
+        if let Some(duration) = record.duration {
+            total_duration_ms += duration;
+            has_duration = true;
+        }
+
+        if let Some((identity, name)) = primary_artist_identity(record) {
+            artist_counts.entry(identity.clone()).or_insert_with(|| (name.clone(), 0)).1 += 1;
+
+            let track_key = (normalize(&record.track_name), identity);
+            track_counts
+                .entry(track_key)
+                .or_insert_with(|| (record.track_name.clone(), name, 0))
+                .2 += 1;
+        }
+
+        if let Some(release_name) = &record.release_name {
+            release_counts
+                .entry(normalize(release_name))
+                .or_insert_with(|| (release_name.clone(), 0))
+                .1 += 1;
+        }
+
+        if let Some(played_time) = &record.played_time {
+            if let Ok(played_at) = DateTime::parse_from_rfc3339(played_time) {
+                let played_at = played_at.with_timezone(&Utc);
+                plays_by_month[played_at.month0() as usize] += 1;
+                plays_by_hour[played_at.hour() as usize] += 1;
+                play_dates.push(played_at.date_naive());
+            }
+        }
+    }
+
+    let mut top_artists: Vec<TopArtist> = artist_counts
+        .into_values()
+        .map(|(name, plays)| TopArtist { name, plays })
+        .collect();
+    top_artists.sort_by(|a, b| b.plays.cmp(&a.plays));
+    top_artists.truncate(TOP_N);
+
+    let mut top_tracks: Vec<TopTrack> = track_counts
+        .into_values()
+        .map(|(track_name, artist_name, plays)| TopTrack {
+            track_name,
+            artist_name,
+            plays,
+        })
+        .collect();
+    top_tracks.sort_by(|a, b| b.plays.cmp(&a.plays));
+    top_tracks.truncate(TOP_N);
+
+    let mut top_releases: Vec<TopRelease> = release_counts
+        .into_values()
+        .map(|(release_name, plays)| TopRelease { release_name, plays })
+        .collect();
+    top_releases.sort_by(|a, b| b.plays.cmp(&a.plays));
+    top_releases.truncate(TOP_N);
+
+    WrappedStats {
+        total_plays,
+        total_listening_minutes: has_duration.then(|| total_duration_ms as f64 / (1000.0 * 60.0)),
+        top_artists,
+        top_tracks,
+        top_releases,
+        plays_by_month,
+        plays_by_hour,
+        longest_daily_streak: longest_streak(&play_dates),
+    }
+}
+
+fn longest_streak(play_dates: &[NaiveDate]) -> u32 {
+    let mut dates = play_dates.to_vec();
+    dates.sort();
+    dates.dedup();
+
+    let mut longest = 0u32;
+    let mut current = 0u32;
+    let mut prev: Option<NaiveDate> = None;
+
+    for date in dates {
+        let is_consecutive = prev
+            .and_then(|p| p.succ_opt())
+            .map(|next| next == date)
+            .unwrap_or(false);
+        current = if is_consecutive { current + 1 } else { 1 };
+        longest = longest.max(current);
+        prev = Some(date);
+    }
+
+    longest
+}
@@ -1,10 +1,40 @@
 use anyhow::{Context, Result};
+use async_stream::try_stream;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
 use lexicon::fm_teal::alpha::feed::play::Play;
-use repo_stream::{DiskBuilder, Driver, DriverBuilder};
 use serde::{Deserialize, Serialize};
-use std::io::Cursor;
+use std::future::Future;
+use std::pin::Pin;
 
 const PLAY_COLLECTION: &str = "fm.teal.alpha.feed.play";
+/// Page size for `com.atproto.repo.listRecords` - comfortably under the PDS's max.
+const LIST_RECORDS_PAGE_SIZE: u32 = 100;
+
+/// A backend that can produce a unified scrobble history for some identifier (a DID, a
+/// Last.fm username, ...) and a year. Letting callers depend on this instead of
+/// `fetch_scrobbles` directly is what lets a user pass either a DID or a Last.fm username
+/// and get a single, uniform `Vec<ScrobbleRecord>` back.
+pub trait ScrobbleSource {
+    fn fetch_scrobbles<'a>(
+        &'a self,
+        identifier: &'a str,
+        year: u32,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<ScrobbleRecord>>> + Send + 'a>>;
+}
+
+/// The original AT Protocol backend, backed by `fetch_scrobbles` below.
+pub struct AtprotoSource;
+
+impl ScrobbleSource for AtprotoSource {
+    fn fetch_scrobbles<'a>(
+        &'a self,
+        identifier: &'a str,
+        year: u32,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<ScrobbleRecord>>> + Send + 'a>> {
+        Box::pin(fetch_scrobbles(identifier, year))
+    }
+}
 
 #[derive(Debug, Deserialize)]
 struct BlobRef {
@@ -40,14 +70,27 @@ pub async fn fetch_profile_picture(did: &str) -> Result<Option<String>> {
         pds, did
     );
 
-    let response = reqwest::get(&url).await?;
+    let response =
+        crate::http_retry::send_with_retry("atproto_get_record", || reqwest::get(url.as_str()))
+            .await?;
+    let status = response.status();
 
-    if !response.status().is_success() {
+    if !status.is_success() {
         tracing::debug!("no profile record found for {}", did);
+        let body = response.text().await.unwrap_or_default();
+        crate::diagnostics::report_failure("atproto_get_record", &url, Some(status), &body).await;
         return Ok(None);
     }
 
-    let record: GetRecordResponse = response.json().await?;
+    let body = response.text().await?;
+    let record: GetRecordResponse = match serde_json::from_str(&body) {
+        Ok(record) => record,
+        Err(e) => {
+            crate::diagnostics::report_failure("atproto_get_record", &url, Some(status), &body)
+                .await;
+            return Err(e).context("failed to parse profile record");
+        }
+    };
 
     // If there's an avatar, construct the blob URL
     if let Some(avatar) = record.value.avatar {
@@ -92,11 +135,64 @@ fn extract_artists_from_play(play: &Play) -> (Vec<String>, Option<Vec<String>>)
     (names, mbids)
 }
 
+#[derive(Debug, Deserialize)]
+struct GetSessionResponse {
+    did: String,
+}
+
+/// Prove that whoever is presenting `access_token` actually controls `did`, by asking that
+/// DID's own PDS whose session the token belongs to (`com.atproto.server.getSession`) rather
+/// than trusting a caller-supplied `did` query parameter. Used to gate anything that binds
+/// external state (stored Spotify tokens, playlist mutations) to a DID.
+pub async fn verify_session_owns_did(did: &str, access_token: &str) -> Result<bool> {
+    let pds = resolve_pds(did).await?;
+    let url = format!("{}/xrpc/com.atproto.server.getSession", pds);
+
+    let response = reqwest::Client::new()
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", access_token))
+        .send()
+        .await
+        .context("failed to reach PDS getSession endpoint")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Ok(false);
+    }
+
+    let body = response
+        .text()
+        .await
+        .context("failed to read getSession response")?;
+    let session: GetSessionResponse = match serde_json::from_str(&body) {
+        Ok(session) => session,
+        Err(e) => {
+            crate::diagnostics::report_failure("atproto_get_session", &url, Some(status), &body)
+                .await;
+            return Err(e).context("failed to parse getSession response");
+        }
+    };
+
+    Ok(session.did == did)
+}
+
 /// Resolve DID to find the user's PDS endpoint
 async fn resolve_pds(did: &str) -> Result<String> {
     let plc_url = format!("https://plc.directory/{}", did);
-    let response = reqwest::get(&plc_url).await?;
-    let doc: serde_json::Value = response.json().await?;
+    let response = crate::http_retry::send_with_retry("atproto_plc_directory", || {
+        reqwest::get(plc_url.as_str())
+    })
+    .await?;
+    let status = response.status();
+    let body = response.text().await?;
+    let doc: serde_json::Value = match serde_json::from_str(&body) {
+        Ok(doc) => doc,
+        Err(e) => {
+            crate::diagnostics::report_failure("atproto_plc_directory", &plc_url, Some(status), &body)
+                .await;
+            return Err(e).context("failed to parse DID document");
+        }
+    };
 
     let service = doc
         .get("service")
@@ -109,127 +205,185 @@ async fn resolve_pds(did: &str) -> Result<String> {
     Ok(service.to_string())
 }
 
-/// Download and parse a CAR file from a user's AT Protocol repo
-pub async fn fetch_scrobbles(did: &str, _year: u32) -> Result<Vec<ScrobbleRecord>> {
-    // Resolve DID to PDS endpoint
-    let pds = resolve_pds(did).await?;
-    tracing::info!("resolved PDS: {}", pds);
+/// An inclusive-start, exclusive-end UTC window used to bound a scrobble fetch.
+#[derive(Debug, Clone, Copy)]
+pub struct DateRange {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
 
-    // Download CAR file from PDS
-    let pds_url = format!("{}/xrpc/com.atproto.sync.getRepo?did={}", pds, did);
+impl DateRange {
+    /// The `[Jan 1, Jan 1 of next year)` window for a calendar year.
+    pub fn year(year: u32) -> Self {
+        let bound = |y: u32| {
+            chrono::NaiveDate::from_ymd_opt(y as i32, 1, 1)
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|dt| dt.and_utc())
+                .unwrap_or_else(Utc::now)
+        };
+        DateRange {
+            start: bound(year),
+            end: bound(year + 1),
+        }
+    }
 
-    tracing::info!("fetching repo for {}", did);
-    let response = reqwest::get(&pds_url)
-        .await
-        .context("failed to fetch repo")?;
+    /// An unbounded window covering a DID's entire history, used for the first sync of a
+    /// user (no watermark yet) or a forced full resync.
+    pub fn all_time() -> Self {
+        DateRange {
+            start: DateTime::<Utc>::MIN_UTC,
+            end: Utc::now(),
+        }
+    }
 
-    let status = response.status();
-    let car_bytes = response
-        .bytes()
-        .await
-        .context("failed to read response bytes")?;
+    /// Everything strictly after `since`, used for incremental imports once a watermark
+    /// exists for the DID. `start` is bumped a millisecond past `since` - the stream filter
+    /// below treats `start` as inclusive, and `since` is always a previous run's newest
+    /// `played_at`, so using it unmodified would refetch (and re-tally) the very play that
+    /// set the watermark on every subsequent run.
+    pub fn since(since: DateTime<Utc>) -> Self {
+        DateRange {
+            start: since + chrono::Duration::milliseconds(1),
+            end: Utc::now(),
+        }
+    }
+}
 
-    tracing::info!("downloaded {} bytes (status: {})", car_bytes.len(), status);
+#[derive(Debug, Deserialize)]
+struct ListRecordsResponse {
+    #[serde(default)]
+    records: Vec<ListRecord>,
+    cursor: Option<String>,
+}
 
-    if !status.is_success() {
-        let error_text = String::from_utf8_lossy(&car_bytes);
-        anyhow::bail!("failed to fetch repo: {} - {}", status, error_text);
-    }
+#[derive(Debug, Deserialize)]
+struct ListRecord {
+    uri: String,
+    #[serde(default)]
+    cid: String,
+    value: serde_json::Value,
+}
+
+/// Stream a user's play records from `com.atproto.repo.listRecords`, paging through
+/// `cursor` and yielding records lazily as each page arrives rather than buffering the
+/// whole repo in memory first. Records outside `range` are skipped rather than ending the
+/// stream early, since `listRecords` doesn't guarantee newest-first ordering across PDS
+/// implementations.
+pub fn fetch_scrobbles_stream(
+    did: &str,
+    range: DateRange,
+) -> impl Stream<Item = Result<ScrobbleRecord>> + '_ {
+    try_stream! {
+        let pds = resolve_pds(did).await?;
+        tracing::info!("resolved PDS: {}", pds);
+
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut url = format!(
+                "{}/xrpc/com.atproto.repo.listRecords?repo={}&collection={}&limit={}",
+                pds, did, PLAY_COLLECTION, LIST_RECORDS_PAGE_SIZE
+            );
+            if let Some(cursor) = &cursor {
+                url.push_str(&format!("&cursor={}", cursor));
+            }
 
-    // Create an async reader from the bytes
-    let reader = Cursor::new(car_bytes.to_vec());
-    let reader = tokio::io::BufReader::new(reader);
+            let response = reqwest::get(&url)
+                .await
+                .context("failed to list records")?;
+            let status = response.status();
 
-    // Load the CAR file with repo-stream
-    let mut scrobbles = Vec::new();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                crate::diagnostics::report_failure("atproto_list_records", &url, Some(status), &body)
+                    .await;
+                anyhow::bail!("listRecords failed: {}", status);
+            }
 
-    match DriverBuilder::new()
-        .with_mem_limit_mb(100)
-        .with_block_processor(|block| block.to_vec())
-        .load_car(reader)
-        .await?
-    {
-        Driver::Memory(_commit, mut driver) => {
-            // Process records in chunks
-            while let Some(chunk) = driver.next_chunk(256).await? {
-                for (rkey, block_data) in chunk {
-                    // Check if this is a play record by rkey prefix
-                    if rkey.starts_with(PLAY_COLLECTION) {
-                        // Deserialize the Play record
-                        if let Ok(play) = serde_ipld_dagcbor::from_slice::<Play>(&block_data) {
-                            let (artists, artist_mb_ids) = extract_artists_from_play(&play);
-                            let played_time = play.played_time.as_ref().map(|dt| dt.to_string());
-
-                            scrobbles.push(ScrobbleRecord {
-                                uri: format!("at://{}/{}", did, rkey),
-                                cid: String::new(), // CID not available from this API
-                                track_name: play.track_name.to_string(),
-                                artists,
-                                played_time,
-                                duration: play.duration,
-                                recording_mb_id: play
-                                    .recording_mb_id
-                                    .as_ref()
-                                    .map(|s| s.to_string()),
-                                track_mb_id: play.track_mb_id.as_ref().map(|s| s.to_string()),
-                                release_mb_id: play.release_mb_id.as_ref().map(|s| s.to_string()),
-                                release_name: play.release_name.as_ref().map(|s| s.to_string()),
-                                artist_mb_ids,
-                            });
-                        }
-                    }
+            let body = response
+                .text()
+                .await
+                .context("failed to read listRecords response body")?;
+            let page: ListRecordsResponse = match serde_json::from_str(&body) {
+                Ok(page) => page,
+                Err(e) => {
+                    crate::diagnostics::report_failure(
+                        "atproto_list_records",
+                        &url,
+                        Some(status),
+                        &body,
+                    )
+                    .await;
+                    Err(e).context("failed to parse listRecords response")?
                 }
+            };
+
+            if page.records.is_empty() {
+                break;
             }
-        }
-        Driver::Disk(paused) => {
-            tracing::info!("repo exceeds memory limit, using disk storage");
-
-            // Create temporary directory for disk storage
-            let temp_dir = std::env::temp_dir().join(format!("repo-{}", did.replace(':', "-")));
-            std::fs::create_dir_all(&temp_dir)?;
-
-            let disk_path = temp_dir.join("blocks.db");
-            let store = DiskBuilder::new().open(disk_path).await?;
-
-            let (_commit, mut driver) = paused.finish_loading(store).await?;
-
-            // Process records in chunks from disk
-            while let Some(chunk) = driver.next_chunk(256).await? {
-                for (rkey, block_data) in chunk {
-                    if rkey.starts_with(PLAY_COLLECTION) {
-                        if let Ok(play) = serde_ipld_dagcbor::from_slice::<Play>(&block_data) {
-                            let (artists, artist_mb_ids) = extract_artists_from_play(&play);
-                            let played_time = play.played_time.as_ref().map(|dt| dt.to_string());
-
-                            scrobbles.push(ScrobbleRecord {
-                                uri: format!("at://{}/{}", did, rkey),
-                                cid: String::new(),
-                                track_name: play.track_name.to_string(),
-                                artists,
-                                played_time,
-                                duration: play.duration,
-                                recording_mb_id: play
-                                    .recording_mb_id
-                                    .as_ref()
-                                    .map(|s| s.to_string()),
-                                track_mb_id: play.track_mb_id.as_ref().map(|s| s.to_string()),
-                                release_mb_id: play.release_mb_id.as_ref().map(|s| s.to_string()),
-                                release_name: play.release_name.as_ref().map(|s| s.to_string()),
-                                artist_mb_ids,
-                            });
-                        }
-                    }
+
+            for record in page.records {
+                let Ok(play) = serde_json::from_value::<Play>(record.value) else {
+                    continue;
+                };
+
+                let (artists, artist_mb_ids) = extract_artists_from_play(&play);
+                let played_time = play.played_time.as_ref().map(|dt| dt.to_string());
+
+                let in_range = played_time
+                    .as_deref()
+                    .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                    .map(|dt| {
+                        let dt = dt.with_timezone(&Utc);
+                        dt >= range.start && dt < range.end
+                    })
+                    .unwrap_or(true);
+                if !in_range {
+                    continue;
                 }
+
+                yield ScrobbleRecord {
+                    uri: record.uri,
+                    cid: record.cid,
+                    track_name: play.track_name.to_string(),
+                    artists,
+                    played_time,
+                    duration: play.duration,
+                    recording_mb_id: play.recording_mb_id.as_ref().map(|s| s.to_string()),
+                    track_mb_id: play.track_mb_id.as_ref().map(|s| s.to_string()),
+                    release_mb_id: play.release_mb_id.as_ref().map(|s| s.to_string()),
+                    release_name: play.release_name.as_ref().map(|s| s.to_string()),
+                    artist_mb_ids,
+                    spotify_track_url: None,
+                };
             }
 
-            // Clean up temporary directory
-            if let Err(e) = std::fs::remove_dir_all(&temp_dir) {
-                tracing::warn!("failed to clean up temp dir: {}", e);
+            cursor = page.cursor;
+            if cursor.is_none() {
+                break;
             }
         }
     }
+}
 
-    tracing::info!("found {} play records", scrobbles.len());
+/// Fetch a user's play records for a calendar year by collecting `fetch_scrobbles_stream`
+/// over the `[Jan 1, Jan 1 of next year)` window.
+pub async fn fetch_scrobbles(did: &str, year: u32) -> Result<Vec<ScrobbleRecord>> {
+    let range = DateRange::year(year);
+    let stream = fetch_scrobbles_stream(did, range);
+    futures::pin_mut!(stream);
+
+    let mut scrobbles = Vec::new();
+    while let Some(record) = stream.next().await {
+        scrobbles.push(record?);
+    }
+
+    tracing::info!(
+        "found {} play records for {} in {}",
+        scrobbles.len(),
+        did,
+        year
+    );
 
     Ok(scrobbles)
 }
@@ -247,6 +401,322 @@ pub struct ScrobbleRecord {
     pub release_mb_id: Option<String>,
     pub release_name: Option<String>,
     pub artist_mb_ids: Option<Vec<String>>,
+    /// `open.spotify.com/track/<id>` link, filled in by `spotify::SpotifyLinkResolver`.
+    pub spotify_track_url: Option<String>,
+}
+
+/// Minimum confidence (MusicBrainz's 0-100 search score) required before a match is
+/// trusted enough to fill in an MBID.
+const MUSICBRAINZ_MIN_SCORE: u64 = 80;
+
+#[derive(Debug, Clone)]
+struct MusicBrainzMatch {
+    recording_id: String,
+    release_title: Option<String>,
+    artist_ids: Vec<String>,
+    score: u64,
+}
+
+/// Backfill `recording_mb_id`/`artist_mb_ids`/`release_name` for scrobbles missing them -
+/// every record from `lastfm::LastfmSource` lacks MBIDs entirely, and AT Protocol records
+/// occasionally do too. Lookups go through the shared `musicbrainz` client, which already
+/// rate-limits and retries, and are deduplicated by normalized `(track, artist)` key within
+/// a run so repeated tracks don't re-query.
+pub async fn backfill_musicbrainz_ids(records: &mut [ScrobbleRecord]) -> Result<()> {
+    let mut cache: std::collections::HashMap<(String, String), Option<MusicBrainzMatch>> =
+        std::collections::HashMap::new();
+
+    for record in records.iter_mut() {
+        if record.recording_mb_id.is_some() {
+            continue;
+        }
+        let Some(first_artist) = record.artists.first().cloned() else {
+            continue;
+        };
+
+        let key = (
+            normalize_for_lookup(&record.track_name),
+            normalize_for_lookup(&first_artist),
+        );
+
+        let found = if let Some(cached) = cache.get(&key) {
+            cached.clone()
+        } else {
+            let result = query_musicbrainz_recording(&record.track_name, &first_artist).await;
+
+            let found = match result {
+                Ok(found) => found,
+                Err(e) => {
+                    tracing::warn!(
+                        "musicbrainz lookup failed for '{}' by '{}': {}",
+                        record.track_name,
+                        first_artist,
+                        e
+                    );
+                    None
+                }
+            };
+            cache.insert(key, found.clone());
+            found
+        };
+
+        let Some(found) = found else { continue };
+        if found.score < MUSICBRAINZ_MIN_SCORE {
+            continue;
+        }
+
+        record.recording_mb_id = Some(found.recording_id);
+        if record.release_name.is_none() {
+            record.release_name = found.release_title;
+        }
+        if record.artist_mb_ids.is_none() && !found.artist_ids.is_empty() {
+            record.artist_mb_ids = Some(found.artist_ids);
+        }
+    }
+
+    Ok(())
+}
+
+fn normalize_for_lookup(name: &str) -> String {
+    name.to_lowercase().trim().to_string()
+}
+
+/// One batch's worth of progress from `backfill_missing_musicbrainz_ids`.
+#[derive(Debug, Clone, Copy)]
+pub struct MusicBrainzBackfillBatch {
+    pub scanned: usize,
+    pub resolved: usize,
+    pub last_id: i64,
+    /// `true` once a batch scans fewer than `batch_size` rows, meaning there's nothing left
+    /// with a null `recording_mb_id` past `last_id`.
+    pub done: bool,
+}
+
+/// Retroactively resolve `recording_mb_id` (and, where still missing, `artists[0].artistMbId`
+/// and `release_name`) for `user_plays` rows that have none, by querying MusicBrainz directly
+/// rather than only copying IDs that already exist on some other row (that's what the CLI's
+/// plain-SQL `BackfillMbIds` does). Walks rows in `id` order starting just after the
+/// `musicbrainz_backfill_progress` high-water mark, so a large table can be backfilled over
+/// many resumable runs instead of one long-running scan. Resolutions (including "not found")
+/// are cached in `musicbrainz_resolution_cache` keyed on the normalized `(track, artist)` pair,
+/// so a name that keeps showing up without an MBID is only ever looked up once.
+pub async fn backfill_missing_musicbrainz_ids(
+    pool: &sqlx::PgPool,
+    batch_size: i64,
+) -> Result<MusicBrainzBackfillBatch> {
+    use sqlx::Row;
+
+    let last_id: i64 =
+        sqlx::query_scalar("SELECT last_id FROM musicbrainz_backfill_progress WHERE id = 1")
+            .fetch_one(pool)
+            .await?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, track_name, (artists->0)->>'artistName' as artist_name
+        FROM user_plays
+        WHERE id > $1
+          AND recording_mb_id IS NULL
+          AND jsonb_array_length(artists) > 0
+        ORDER BY id
+        LIMIT $2
+        "#,
+    )
+    .bind(last_id)
+    .bind(batch_size)
+    .fetch_all(pool)
+    .await?;
+
+    let scanned = rows.len();
+    let mut resolved = 0usize;
+    let mut new_last_id = last_id;
+
+    for row in &rows {
+        let id: i64 = row.get("id");
+        let track_name: String = row.get("track_name");
+        let artist_name: Option<String> = row.get("artist_name");
+        new_last_id = id;
+
+        let Some(artist_name) = artist_name else {
+            continue;
+        };
+
+        let normalized_track = normalize_for_lookup(&track_name);
+        let normalized_artist = normalize_for_lookup(&artist_name);
+
+        let cached = sqlx::query(
+            r#"
+            SELECT recording_mb_id, artist_mb_id, release_name
+            FROM musicbrainz_resolution_cache
+            WHERE track_name = $1 AND artist_name = $2
+            "#,
+        )
+        .bind(&normalized_track)
+        .bind(&normalized_artist)
+        .fetch_optional(pool)
+        .await?;
+
+        let (recording_mb_id, artist_mb_id, release_name) = if let Some(cached) = cached {
+            (
+                cached.get::<Option<String>, _>("recording_mb_id"),
+                cached.get::<Option<String>, _>("artist_mb_id"),
+                cached.get::<Option<String>, _>("release_name"),
+            )
+        } else {
+            let found = match query_musicbrainz_recording(&track_name, &artist_name).await {
+                Ok(found) => found,
+                Err(e) => {
+                    tracing::warn!(
+                        "musicbrainz backfill lookup failed for '{}' by '{}': {}",
+                        track_name,
+                        artist_name,
+                        e
+                    );
+                    None
+                }
+            };
+
+            let (recording_mb_id, artist_mb_id, release_name) = match &found {
+                Some(m) if m.score >= MUSICBRAINZ_MIN_SCORE => (
+                    Some(m.recording_id.clone()),
+                    m.artist_ids.first().cloned(),
+                    m.release_title.clone(),
+                ),
+                _ => (None, None, None),
+            };
+
+            sqlx::query(
+                r#"
+                INSERT INTO musicbrainz_resolution_cache
+                    (track_name, artist_name, recording_mb_id, artist_mb_id, release_name)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (track_name, artist_name) DO UPDATE SET
+                    recording_mb_id = $3,
+                    artist_mb_id = $4,
+                    release_name = $5,
+                    resolved_at = NOW()
+                "#,
+            )
+            .bind(&normalized_track)
+            .bind(&normalized_artist)
+            .bind(&recording_mb_id)
+            .bind(&artist_mb_id)
+            .bind(&release_name)
+            .execute(pool)
+            .await?;
+
+            (recording_mb_id, artist_mb_id, release_name)
+        };
+
+        let Some(recording_mb_id) = recording_mb_id else {
+            continue;
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE user_plays
+            SET
+                recording_mb_id = $2,
+                release_name = COALESCE(release_name, $3),
+                artists = (
+                    SELECT jsonb_agg(
+                        CASE
+                            WHEN ord = 1 AND elem->>'artistMbId' IS NULL AND $4::text IS NOT NULL
+                                THEN elem || jsonb_build_object('artistMbId', $4::text)
+                            ELSE elem
+                        END
+                        ORDER BY ord
+                    )
+                    FROM jsonb_array_elements(artists) WITH ORDINALITY AS t(elem, ord)
+                )
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(&recording_mb_id)
+        .bind(&release_name)
+        .bind(&artist_mb_id)
+        .execute(pool)
+        .await?;
+
+        resolved += 1;
+    }
+
+    sqlx::query("UPDATE musicbrainz_backfill_progress SET last_id = $1 WHERE id = 1")
+        .bind(new_last_id)
+        .execute(pool)
+        .await?;
+
+    Ok(MusicBrainzBackfillBatch {
+        scanned,
+        resolved,
+        last_id: new_last_id,
+        done: (scanned as i64) < batch_size,
+    })
+}
+
+/// Query MusicBrainz's recording search for the top-scoring match of `track` by `artist`.
+async fn query_musicbrainz_recording(
+    track: &str,
+    artist: &str,
+) -> Result<Option<MusicBrainzMatch>> {
+    let query = format!(r#"recording:"{}" AND artist:"{}""#, track, artist);
+    let url = "https://musicbrainz.org/ws/2/recording";
+
+    let Some(data) =
+        crate::musicbrainz::get_json(url, &[("fmt", "json"), ("query", query.as_str())]).await?
+    else {
+        return Ok(None);
+    };
+
+    let Some(recordings) = data.get("recordings").and_then(|r| r.as_array()) else {
+        return Ok(None);
+    };
+
+    let Some(best) = recordings
+        .iter()
+        .max_by_key(|r| r.get("score").and_then(|s| s.as_u64()).unwrap_or(0))
+    else {
+        return Ok(None);
+    };
+
+    let Some(recording_id) = best.get("id").and_then(|id| id.as_str()) else {
+        return Ok(None);
+    };
+
+    let score = best.get("score").and_then(|s| s.as_u64()).unwrap_or(0);
+
+    let release_title = best
+        .get("releases")
+        .and_then(|r| r.as_array())
+        .and_then(|releases| releases.first())
+        .and_then(|release| release.get("title"))
+        .and_then(|title| title.as_str())
+        .map(|s| s.to_string());
+
+    let artist_ids = best
+        .get("artist-credit")
+        .and_then(|credits| credits.as_array())
+        .map(|credits| {
+            credits
+                .iter()
+                .filter_map(|credit| {
+                    credit
+                        .get("artist")
+                        .and_then(|artist| artist.get("id"))
+                        .and_then(|id| id.as_str())
+                        .map(|s| s.to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Some(MusicBrainzMatch {
+        recording_id: recording_id.to_string(),
+        release_title,
+        artist_ids,
+        score,
+    }))
 }
 
 #[derive(Debug, Deserialize)]
@@ -262,11 +732,23 @@ pub async fn resolve_handle_to_did(handle: &str) -> Result<String> {
     );
 
     let response = reqwest::get(&url).await?;
+    let status = response.status();
 
-    if !response.status().is_success() {
-        anyhow::bail!("failed to resolve handle: {}", response.status());
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        crate::diagnostics::report_failure("atproto_resolve_handle", &url, Some(status), &body)
+            .await;
+        anyhow::bail!("failed to resolve handle: {}", status);
     }
 
-    let doc: MiniDocResponse = response.json().await?;
+    let body = response.text().await?;
+    let doc: MiniDocResponse = match serde_json::from_str(&body) {
+        Ok(doc) => doc,
+        Err(e) => {
+            crate::diagnostics::report_failure("atproto_resolve_handle", &url, Some(status), &body)
+                .await;
+            return Err(e).context("failed to parse resolveMiniDoc response");
+        }
+    };
     Ok(doc.did)
 }
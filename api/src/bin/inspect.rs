@@ -2,7 +2,7 @@ mod import_scrobbles;
 
 use anyhow::Result;
 use sqlx::postgres::PgPool;
-use teal_wrapped_api::db;
+use teal_wrapped_api::{db, rekordbox};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -50,6 +50,15 @@ async fn main() -> Result<()> {
             db::refresh_user_stats(&pool).await?;
             println!("refresh complete!");
         }
+        "import-rekordbox" => {
+            if args.len() < 4 {
+                println!("usage: inspect import-rekordbox <did> <path>");
+                return Ok(());
+            }
+            let did = &args[2];
+            let path = &args[3];
+            import_rekordbox(&pool, did, path).await?;
+        }
         _ => {
             print_usage();
         }
@@ -67,6 +76,27 @@ fn print_usage() {
     println!("  inspect user <did> [year]      - show user's listening stats");
     println!("  inspect buddies <did> [year]   - show user's music buddies");
     println!("  inspect refresh                - refresh materialized views");
+    println!("  inspect import-rekordbox <did> <path> - import a Rekordbox export.pdb");
+}
+
+async fn import_rekordbox(pool: &PgPool, did: &str, path: &str) -> Result<()> {
+    println!("parsing rekordbox export at {}...", path);
+
+    let plays = rekordbox::parse_export_pdb(std::path::Path::new(path))?;
+
+    if plays.is_empty() {
+        println!("no history entries found in export");
+        return Ok(());
+    }
+
+    println!("found {} history entries, storing for {}...", plays.len(), did);
+
+    let scrobbles = rekordbox::plays_to_scrobbles(did, plays);
+    db::store_user_plays(pool, did, &scrobbles).await?;
+
+    println!("imported {} rekordbox plays for {}", scrobbles.len(), did);
+
+    Ok(())
 }
 
 async fn show_stats(pool: &PgPool) -> Result<()> {
@@ -190,14 +220,15 @@ async fn show_user_stats(pool: &PgPool, user_did: &str, year: u32) -> Result<()>
         println!("  {} - {} plays", artist, plays);
     }
 
-    // Show top 5 albums
+    // Show top 5 albums, breaking ties between equally-played albums by earliest month
+    // listened so a tie doesn't come down to arbitrary row order.
     let top_albums: Vec<(String, i64)> = sqlx::query_as(
         r#"
         SELECT release_name, COUNT(*) as plays
         FROM user_plays
         WHERE user_did = $1 AND EXTRACT(YEAR FROM played_at) = $2 AND release_name IS NOT NULL
         GROUP BY release_name
-        ORDER BY plays DESC
+        ORDER BY plays DESC, MIN(EXTRACT(MONTH FROM played_at)) ASC
         LIMIT 5
         "#,
     )
@@ -213,6 +244,80 @@ async fn show_user_stats(pool: &PgPool, user_did: &str, year: u32) -> Result<()>
         }
     }
 
+    show_monthly_timeline(pool, user_did, year).await?;
+
+    Ok(())
+}
+
+/// Month-by-month breakdown for the given year: plays per month plus each month's
+/// single top artist, giving a seasonal shape to the listening rather than just the
+/// annual aggregates above.
+async fn show_monthly_timeline(pool: &PgPool, user_did: &str, year: u32) -> Result<()> {
+    let monthly_plays: Vec<(f64, i64)> = sqlx::query_as(
+        r#"
+        SELECT
+            EXTRACT(MONTH FROM played_at) as month,
+            COUNT(*) as plays
+        FROM user_plays
+        WHERE user_did = $1 AND EXTRACT(YEAR FROM played_at) = $2
+        GROUP BY date_trunc('month', played_at), EXTRACT(MONTH FROM played_at)
+        ORDER BY month
+        "#,
+    )
+    .bind(user_did)
+    .bind(year as i32)
+    .fetch_all(pool)
+    .await?;
+
+    let monthly_top_artists: Vec<(f64, String, i64)> = sqlx::query_as(
+        r#"
+        SELECT month, artist_name, plays FROM (
+            SELECT
+                EXTRACT(MONTH FROM played_at) as month,
+                artist->>'artistName' as artist_name,
+                COUNT(*) as plays,
+                ROW_NUMBER() OVER (
+                    PARTITION BY EXTRACT(MONTH FROM played_at)
+                    ORDER BY COUNT(*) DESC
+                ) as rank
+            FROM user_plays, jsonb_array_elements(artists) as artist
+            WHERE user_did = $1 AND EXTRACT(YEAR FROM played_at) = $2
+            GROUP BY month, artist_name
+        ) ranked
+        WHERE rank = 1
+        "#,
+    )
+    .bind(user_did)
+    .bind(year as i32)
+    .fetch_all(pool)
+    .await?;
+
+    if monthly_plays.is_empty() {
+        return Ok(());
+    }
+
+    let top_artist_by_month: std::collections::HashMap<i32, (String, i64)> = monthly_top_artists
+        .into_iter()
+        .map(|(month, artist, plays)| (month as i32, (artist, plays)))
+        .collect();
+
+    const MONTH_NAMES: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    println!("\nmonthly timeline:");
+    for (month, plays) in monthly_plays {
+        let month = month as i32;
+        let name = MONTH_NAMES.get((month - 1) as usize).copied().unwrap_or("???");
+        match top_artist_by_month.get(&month) {
+            Some((artist, artist_plays)) => println!(
+                "  {} - {} plays (top: {}, {} plays)",
+                name, plays, artist, artist_plays
+            ),
+            None => println!("  {} - {} plays", name, plays),
+        }
+    }
+
     Ok(())
 }
 
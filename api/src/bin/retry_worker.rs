@@ -0,0 +1,14 @@
+use anyhow::Result;
+use teal_wrapped_api::{db, retry_worker};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_env_filter("retry_worker=info,teal_wrapped_api=info")
+        .init();
+
+    let pool = db::init_db().await?;
+
+    retry_worker::run(pool).await
+}
@@ -1,4 +1,4 @@
-use teal_wrapped_api::atproto;
+use teal_wrapped_api::{aggregate, atproto};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -39,5 +39,17 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    let stats = aggregate::aggregate(&scrobbles);
+    println!("\nwrapped stats:");
+    println!("  total plays: {}", stats.total_plays);
+    if let Some(minutes) = stats.total_listening_minutes {
+        println!("  total listening time: {:.1} minutes", minutes);
+    }
+    println!("  longest daily streak: {} days", stats.longest_daily_streak);
+    println!("  top artists:");
+    for artist in stats.top_artists.iter().take(5) {
+        println!("    {} ({} plays)", artist.name, artist.plays);
+    }
+
     Ok(())
 }
@@ -1,19 +1,150 @@
 use anyhow::{Context, Result};
 use chrono::Datelike;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use sqlx::{Column, Row};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use teal_wrapped_api::{
-    atproto, db, global_stats, wrapped, DayActivity, TopArtist, TopTrack, WrappedData,
+    atproto, db, future::WithPollTimer, global_stats, recommendations, retry_worker, wrapped,
+    DayActivity, EpicSession, MusicBuddy, StreakStats, TopArtist, TopTrack, WrappedData,
 };
 
+/// Threshold above which a per-item future in a bulk pipeline is considered stalled and
+/// starts getting named in warning logs.
+const STALL_WARN_AFTER: Duration = Duration::from_secs(30);
+/// How often aggregate bulk-pipeline progress (in-flight, items/sec, ETA) is logged.
+const PROGRESS_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Tracks aggregate progress for a bulk `buffer_unordered` pipeline and periodically logs
+/// in-flight count, throughput, and ETA, so "the bulk job seems stuck" becomes an actionable
+/// log rather than silence until completion.
+struct BulkProgress {
+    label: &'static str,
+    total: usize,
+    started_at: Instant,
+    completed: Arc<AtomicUsize>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl BulkProgress {
+    fn new(label: &'static str, total: usize) -> Arc<Self> {
+        Arc::new(Self {
+            label,
+            total,
+            started_at: Instant::now(),
+            completed: Arc::new(AtomicUsize::new(0)),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// Spawn a background task that logs progress every `PROGRESS_REPORT_INTERVAL` until all
+    /// `total` items have completed.
+    fn spawn_reporter(self: &Arc<Self>) {
+        let progress = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PROGRESS_REPORT_INTERVAL);
+            interval.tick().await; // first tick fires immediately; skip it
+
+            loop {
+                interval.tick().await;
+                let done = progress.completed.load(Ordering::SeqCst);
+                if done >= progress.total {
+                    return;
+                }
+
+                let elapsed = progress.started_at.elapsed().as_secs_f64().max(0.001);
+                let rate = done as f64 / elapsed;
+                let eta = if rate > 0.0 {
+                    format!("{:.0}s", (progress.total - done) as f64 / rate)
+                } else {
+                    "unknown".to_string()
+                };
+
+                tracing::info!(
+                    "{}: {}/{} done, {} in flight, {:.2} items/sec, eta {}",
+                    progress.label,
+                    done,
+                    progress.total,
+                    progress.in_flight.load(Ordering::SeqCst),
+                    rate,
+                    eta
+                );
+            }
+        });
+    }
+
+    fn start_item(&self) {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn finish_item(&self) {
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.completed.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
 #[derive(Parser)]
 #[command(name = "yircli")]
 #[command(about = "teal wrapped CLI tool", long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Output format: human-readable tracing logs, or a single JSON result envelope on
+    /// stdout so cron wrappers and other services can branch on the outcome
+    #[arg(long, value_enum, global = true, default_value = "text")]
+    output: OutputMode,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum OutputMode {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum RediscoverKind {
+    Artists,
+    Albums,
+    Tracks,
+}
+
+/// CLI-facing mirror of [`wrapped::TimeWindow`] - that type isn't a `ValueEnum` itself since
+/// it's shared with the non-CLI wrapped-stats code, so this just maps onto it.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum RediscoverWindow {
+    All,
+    Yearly,
+    Monthly,
+    Weekly,
+    None,
+}
+
+impl From<RediscoverWindow> for wrapped::TimeWindow {
+    fn from(window: RediscoverWindow) -> Self {
+        match window {
+            RediscoverWindow::All => wrapped::TimeWindow::All,
+            RediscoverWindow::Yearly => wrapped::TimeWindow::Yearly,
+            RediscoverWindow::Monthly => wrapped::TimeWindow::Monthly,
+            RediscoverWindow::Weekly => wrapped::TimeWindow::Weekly,
+            RediscoverWindow::None => wrapped::TimeWindow::None,
+        }
+    }
+}
+
+/// Result envelope printed as the single line of JSON on stdout in `--output json` mode.
+/// `Success`/`Fatal` are used for the command as a whole; `Failure` is reused at the
+/// per-item level inside a `Success` payload for entries that failed without aborting
+/// the rest of the run (e.g. one DID in a bulk import).
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum CliResult<T> {
+    Success { content: T },
+    Failure { message: String },
+    Fatal { message: String },
 }
 
 #[derive(Subcommand)]
@@ -31,6 +162,11 @@ enum Commands {
         /// Number of concurrent imports
         #[arg(short, long, default_value_t = 20)]
         parallelism: usize,
+
+        /// Force a complete resync instead of fetching only what's newer than each DID's
+        /// stored watermark
+        #[arg(long)]
+        full: bool,
     },
 
     /// Calculate statistics
@@ -47,6 +183,68 @@ enum Commands {
 
     /// Backfill missing musicbrainz IDs from existing records
     BackfillMbIds,
+
+    /// Resolve missing musicbrainz IDs by querying the MusicBrainz API directly, for plays
+    /// that have no matching ID anywhere else in the table
+    BackfillMusicbrainzIds {
+        /// Rows to scan per batch
+        #[arg(short, long, default_value_t = 500)]
+        batch_size: i64,
+    },
+
+    /// Recommend artists a user hasn't heard yet, based on their nearest neighbors
+    Recommend {
+        /// User DID to recommend for
+        #[arg(value_name = "DID")]
+        did: String,
+
+        /// Year to base the neighborhood and exclusion set on
+        #[arg(short, long, default_value_t = 2025)]
+        year: u32,
+
+        /// Number of recommendations to return
+        #[arg(short, long, default_value_t = 10)]
+        limit: i64,
+    },
+
+    /// Recommend artists, albums, or tracks a user should rediscover: played heavily in one
+    /// window but not recently, scored by recency-weighted neglect (or shuffled, with
+    /// `--random`). Distinct from `recommend`, which recommends via nearest-neighbor listeners
+    /// rather than the user's own listening history.
+    Rediscover {
+        /// User DID to recommend for
+        #[arg(value_name = "DID")]
+        did: String,
+
+        /// What to recommend
+        #[arg(short, long, value_enum, default_value_t = RediscoverKind::Artists)]
+        kind: RediscoverKind,
+
+        /// Window of plays to consider eligible
+        #[arg(long, value_enum, default_value_t = RediscoverWindow::All)]
+        include: RediscoverWindow,
+
+        /// Window of plays to exclude as "already recently revisited"
+        #[arg(long, value_enum, default_value_t = RediscoverWindow::Monthly)]
+        exclude: RediscoverWindow,
+
+        /// Shuffle the eligible set instead of ranking by neglect score
+        #[arg(long, default_value_t = false)]
+        random: bool,
+
+        /// Number of recommendations to return
+        #[arg(short, long, default_value_t = 10)]
+        limit: i64,
+    },
+
+    /// Run an ad-hoc read-only SQL query against the pool, for exploration without adding a
+    /// new subcommand for every stat. `weekly_plays`/`monthly_plays`/`yearly_plays` views are
+    /// kept up to date by migrations so common rolling-window stats don't need the date math
+    /// spelled out by hand.
+    Sql {
+        /// Query to run; reads from stdin if omitted
+        query: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -63,6 +261,25 @@ enum RetryQueueAction {
 
     /// Clear the retry queue
     Clear,
+
+    /// Inspect or requeue users that exceeded the max retry count
+    DeadLetter {
+        #[command(subcommand)]
+        action: DeadLetterAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum DeadLetterAction {
+    /// List dead-lettered users with their retry count and last error
+    List,
+
+    /// Reset a dead-lettered user back into the normal retry rotation
+    Requeue {
+        /// User DID to requeue
+        #[arg(value_name = "DID")]
+        did: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -84,6 +301,11 @@ enum StatsType {
         /// Skip users with cached stats
         #[arg(long)]
         skip_cached: bool,
+
+        /// Compute user-user collaborative-filtering neighbors and populate
+        /// `similar_users` - an extra full-table pass, so it's opt-in
+        #[arg(long)]
+        compute_similar: bool,
     },
 
     /// Calculate global platform statistics
@@ -102,20 +324,47 @@ async fn main() -> Result<()> {
         .init();
 
     let cli = Cli::parse();
+    let output = cli.output;
+
+    let result = run_command(cli.command).await;
+
+    match output {
+        OutputMode::Text => result.map(|_| ()),
+        OutputMode::Json => {
+            let envelope = match &result {
+                Ok(content) => CliResult::Success { content },
+                Err(e) => CliResult::Fatal {
+                    message: format!("{:#}", e),
+                },
+            };
+            println!(
+                "{}",
+                serde_json::to_string(&envelope).context("Failed to serialize CLI result")?
+            );
+            if result.is_err() {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+    }
+}
 
-    match cli.command {
+async fn run_command(command: Commands) -> Result<serde_json::Value> {
+    match command {
         Commands::Import {
             did,
             year,
             parallelism,
-        } => handle_import(&did, year, parallelism).await,
+            full,
+        } => handle_import(&did, year, parallelism, full).await,
         Commands::Calculate { stats_type } => match stats_type {
             StatsType::Wrapped {
                 did,
                 year,
                 parallelism,
                 skip_cached,
-            } => handle_calculate_wrapped(&did, year, parallelism, skip_cached).await,
+                compute_similar,
+            } => handle_calculate_wrapped(&did, year, parallelism, skip_cached, compute_similar).await,
             StatsType::GlobalStats { year } => handle_calculate_global_stats(year).await,
         },
         Commands::RetryQueue { action } => match action {
@@ -124,12 +373,47 @@ async fn main() -> Result<()> {
                 handle_retry_queue_process(parallelism).await
             }
             RetryQueueAction::Clear => handle_retry_queue_clear().await,
+            RetryQueueAction::DeadLetter { action } => match action {
+                DeadLetterAction::List => handle_dead_letter_list().await,
+                DeadLetterAction::Requeue { did } => handle_dead_letter_requeue(&did).await,
+            },
         },
         Commands::BackfillMbIds => handle_backfill_mbids().await,
+        Commands::BackfillMusicbrainzIds { batch_size } => {
+            handle_backfill_musicbrainz_ids(batch_size).await
+        }
+        Commands::Recommend { did, year, limit } => handle_recommend(&did, year, limit).await,
+        Commands::Rediscover {
+            did,
+            kind,
+            include,
+            exclude,
+            random,
+            limit,
+        } => handle_rediscover(&did, kind, include.into(), exclude.into(), random, limit).await,
+        Commands::Sql { query } => handle_sql(query).await,
     }
 }
 
-async fn handle_import(did: &str, year: Option<u32>, parallelism: usize) -> Result<()> {
+#[derive(Debug, Serialize)]
+struct ImportUserResult {
+    did: String,
+    imported: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ImportSummary {
+    processed: usize,
+    failed: usize,
+    per_did: Vec<CliResult<ImportUserResult>>,
+}
+
+async fn handle_import(
+    did: &str,
+    year: Option<u32>,
+    parallelism: usize,
+    full: bool,
+) -> Result<serde_json::Value> {
     let db_pool = db::init_db()
         .await
         .context("Failed to initialize database")?;
@@ -146,19 +430,28 @@ async fn handle_import(did: &str, year: Option<u32>, parallelism: usize) -> Resu
         let processed = Arc::new(AtomicUsize::new(0));
         let failed = Arc::new(AtomicUsize::new(0));
         let total = dids.len();
+        let per_did = Arc::new(Mutex::new(Vec::with_capacity(total)));
+        let progress = BulkProgress::new("import", total);
+        progress.spawn_reporter();
 
         stream::iter(dids.into_iter())
             .map(|did| {
                 let db_pool = db_pool.clone();
                 let processed = processed.clone();
                 let failed = failed.clone();
+                let per_did = per_did.clone();
+                let progress = progress.clone();
 
                 async move {
-                    let result = import_user_scrobbles(&db_pool, &did, year).await;
+                    progress.start_item();
+                    let result = import_user_scrobbles(&db_pool, &did, year, full)
+                        .with_stall_watchdog(did.clone(), STALL_WARN_AFTER)
+                        .await;
+                    progress.finish_item();
 
                     let current = processed.fetch_add(1, Ordering::SeqCst) + 1;
 
-                    match result {
+                    let outcome = match result {
                         Ok(count) => {
                             tracing::info!(
                                 "[{}/{}] Successfully imported {} scrobbles for {}",
@@ -167,6 +460,12 @@ async fn handle_import(did: &str, year: Option<u32>, parallelism: usize) -> Resu
                                 count,
                                 did
                             );
+                            CliResult::Success {
+                                content: ImportUserResult {
+                                    did: did.clone(),
+                                    imported: count,
+                                },
+                            }
                         }
                         Err(e) => {
                             failed.fetch_add(1, Ordering::SeqCst);
@@ -177,8 +476,12 @@ async fn handle_import(did: &str, year: Option<u32>, parallelism: usize) -> Resu
                                 did,
                                 e
                             );
+                            CliResult::Failure {
+                                message: format!("{}: {:#}", did, e),
+                            }
                         }
-                    }
+                    };
+                    per_did.lock().unwrap().push(outcome);
                 }
             })
             .buffer_unordered(parallelism)
@@ -193,13 +496,20 @@ async fn handle_import(did: &str, year: Option<u32>, parallelism: usize) -> Resu
             failed_count,
             processed_count - failed_count
         );
+
+        let summary = ImportSummary {
+            processed: processed_count,
+            failed: failed_count,
+            per_did: Arc::try_unwrap(per_did).unwrap().into_inner().unwrap(),
+        };
+        Ok(serde_json::to_value(summary)?)
     } else {
         match year {
             Some(y) => tracing::info!("Starting import for DID: {}, Year: {}", did, y),
             None => tracing::info!("Starting import for DID: {} (all years)", did),
         }
 
-        let count = import_user_scrobbles(&db_pool, did, year).await?;
+        let count = import_user_scrobbles(&db_pool, did, year, full).await?;
 
         match year {
             Some(y) => tracing::info!(
@@ -214,9 +524,31 @@ async fn handle_import(did: &str, year: Option<u32>, parallelism: usize) -> Resu
                 did
             ),
         }
+
+        Ok(serde_json::to_value(ImportUserResult {
+            did: did.to_string(),
+            imported: count,
+        })?)
     }
+}
 
-    Ok(())
+/// Minimum number of shared artists before two users are considered for the
+/// `similar_users` collaborative-filtering step.
+const SIMILAR_USERS_MIN_OVERLAP: usize = 3;
+/// Number of neighbors kept per user by the collaborative-filtering step.
+const SIMILAR_USERS_TOP_K: usize = 10;
+
+#[derive(Debug, Serialize)]
+struct WrappedUserResult {
+    did: String,
+    year: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct WrappedSummary {
+    processed: usize,
+    failed: usize,
+    per_user: Vec<CliResult<WrappedUserResult>>,
 }
 
 async fn handle_calculate_wrapped(
@@ -224,11 +556,28 @@ async fn handle_calculate_wrapped(
     year: u32,
     parallelism: usize,
     skip_cached: bool,
-) -> Result<()> {
+    compute_similar: bool,
+) -> Result<serde_json::Value> {
     let db_pool = db::init_db()
         .await
         .context("Failed to initialize database")?;
 
+    let similar_users = if compute_similar {
+        tracing::info!("Computing global user-user similarity for {}...", year);
+        Some(
+            db::compute_global_similar_users(
+                &db_pool,
+                year,
+                SIMILAR_USERS_MIN_OVERLAP,
+                SIMILAR_USERS_TOP_K,
+            )
+            .await
+            .context("Failed to compute global similar users")?,
+        )
+    } else {
+        None
+    };
+
     if did == "all" {
         tracing::info!(
             "Calculating wrapped stats for year {} with parallelism {} (skip_cached: {})",
@@ -252,7 +601,9 @@ async fn handle_calculate_wrapped(
         let users_to_process = if skip_cached {
             let mut filtered = Vec::new();
             for user in users {
-                let cached = db::get_cached_wrapped(&db_pool, &user, year).await?;
+                let cached =
+                    db::get_cached_wrapped(&db_pool, &user, wrapped::ReportWindow::Year(year as i32))
+                        .await?;
                 if cached.is_none() {
                     filtered.push(user);
                 }
@@ -267,19 +618,37 @@ async fn handle_calculate_wrapped(
         let processed = Arc::new(AtomicUsize::new(0));
         let failed = Arc::new(AtomicUsize::new(0));
         let total = users_to_process.len();
+        let per_user = Arc::new(Mutex::new(Vec::with_capacity(total)));
+        let progress = BulkProgress::new("calculate-wrapped", total);
+        progress.spawn_reporter();
 
         stream::iter(users_to_process.into_iter())
             .map(|user_did| {
                 let db_pool = db_pool.clone();
                 let processed = processed.clone();
                 let failed = failed.clone();
+                let per_user = per_user.clone();
+                let progress = progress.clone();
+                let user_similar = similar_users
+                    .as_ref()
+                    .and_then(|m| m.get(&user_did))
+                    .cloned();
 
                 async move {
-                    let result = calculate_and_cache_wrapped(&db_pool, &user_did, year).await;
+                    progress.start_item();
+                    let result = calculate_and_cache_wrapped(
+                        &db_pool,
+                        &user_did,
+                        year,
+                        user_similar.as_deref(),
+                    )
+                    .with_stall_watchdog(user_did.clone(), STALL_WARN_AFTER)
+                    .await;
+                    progress.finish_item();
 
                     let current = processed.fetch_add(1, Ordering::SeqCst) + 1;
 
-                    match result {
+                    let outcome = match result {
                         Ok(()) => {
                             tracing::info!(
                                 "[{}/{}] Cached wrapped stats for {}",
@@ -287,6 +656,12 @@ async fn handle_calculate_wrapped(
                                 total,
                                 user_did
                             );
+                            CliResult::Success {
+                                content: WrappedUserResult {
+                                    did: user_did.clone(),
+                                    year,
+                                },
+                            }
                         }
                         Err(e) => {
                             failed.fetch_add(1, Ordering::SeqCst);
@@ -297,8 +672,12 @@ async fn handle_calculate_wrapped(
                                 user_did,
                                 e
                             );
+                            CliResult::Failure {
+                                message: format!("{}: {:#}", user_did, e),
+                            }
                         }
-                    }
+                    };
+                    per_user.lock().unwrap().push(outcome);
                 }
             })
             .buffer_unordered(parallelism)
@@ -313,22 +692,33 @@ async fn handle_calculate_wrapped(
             failed_count,
             processed_count - failed_count
         );
+
+        let summary = WrappedSummary {
+            processed: processed_count,
+            failed: failed_count,
+            per_user: Arc::try_unwrap(per_user).unwrap().into_inner().unwrap(),
+        };
+        Ok(serde_json::to_value(summary)?)
     } else {
         tracing::info!("Calculating wrapped stats for DID: {}, Year: {}", did, year);
 
-        calculate_and_cache_wrapped(&db_pool, did, year).await?;
+        let user_similar = similar_users.as_ref().and_then(|m| m.get(did)).cloned();
+        calculate_and_cache_wrapped(&db_pool, did, year, user_similar.as_deref()).await?;
 
         tracing::info!(
             "Successfully calculated and cached wrapped stats for DID {} in year {}",
             did,
             year
         );
-    }
 
-    Ok(())
+        Ok(serde_json::to_value(WrappedUserResult {
+            did: did.to_string(),
+            year,
+        })?)
+    }
 }
 
-async fn handle_calculate_global_stats(year: u32) -> Result<()> {
+async fn handle_calculate_global_stats(year: u32) -> Result<serde_json::Value> {
     tracing::info!("Calculating global stats for year {}", year);
 
     let db_pool = db::init_db()
@@ -336,9 +726,13 @@ async fn handle_calculate_global_stats(year: u32) -> Result<()> {
         .context("Failed to initialize database")?;
 
     tracing::info!("Calculating global statistics...");
-    let stats = global_stats::calculate_global_stats(&db_pool, year)
-        .await
-        .context("Failed to calculate global stats")?;
+    let stats = global_stats::calculate_global_stats(
+        &db_pool,
+        year,
+        global_stats::ArtistCreditScope::default(),
+    )
+    .await
+    .context("Failed to calculate global stats")?;
 
     tracing::info!("Global stats calculated:");
     tracing::info!("  Total plays: {}", stats.total_plays);
@@ -354,22 +748,114 @@ async fn handle_calculate_global_stats(year: u32) -> Result<()> {
 
     tracing::info!("Global stats cached successfully for year {}", year);
 
-    Ok(())
+    Ok(serde_json::to_value(stats)?)
+}
+
+async fn handle_recommend(did: &str, year: u32, limit: i64) -> Result<serde_json::Value> {
+    tracing::info!(
+        "Computing artist recommendations for DID {}, year {}",
+        did,
+        year
+    );
+
+    let db_pool = db::init_db()
+        .await
+        .context("Failed to initialize database")?;
+
+    let recommendations = db::recommend_artists(&db_pool, did, year, limit)
+        .await
+        .context("Failed to compute recommendations")?;
+
+    if recommendations.is_empty() {
+        tracing::info!("No recommendations found for DID {}", did);
+        return Ok(serde_json::to_value(&recommendations)?);
+    }
+
+    for rec in &recommendations {
+        tracing::info!(
+            "{}  score={:.2}  neighbors={}",
+            rec.artist,
+            rec.score,
+            rec.neighbor_count
+        );
+    }
+
+    Ok(serde_json::to_value(recommendations)?)
+}
+
+async fn handle_rediscover(
+    did: &str,
+    kind: RediscoverKind,
+    include: wrapped::TimeWindow,
+    exclude: wrapped::TimeWindow,
+    random: bool,
+    limit: i64,
+) -> Result<serde_json::Value> {
+    tracing::info!("Computing {:?} rediscover recommendations for DID {}", kind, did);
+
+    let db_pool = db::init_db()
+        .await
+        .context("Failed to initialize database")?;
+
+    let recommendations = match kind {
+        RediscoverKind::Artists => {
+            wrapped::recommend_artists(&db_pool, did, limit, include, exclude, random).await
+        }
+        RediscoverKind::Albums => {
+            wrapped::recommend_albums(&db_pool, did, limit, include, exclude, random).await
+        }
+        RediscoverKind::Tracks => {
+            recommendations::recommend_tracks(&db_pool, did, limit, include, exclude, random).await
+        }
+    }
+    .context("Failed to compute rediscover recommendations")?;
+
+    for (name, play_count) in &recommendations {
+        tracing::info!("{}  plays={}", name, play_count);
+    }
+
+    Ok(serde_json::to_value(recommendations)?)
 }
 
 async fn import_user_scrobbles(
     db_pool: &sqlx::PgPool,
     did: &str,
     year: Option<u32>,
+    full: bool,
 ) -> Result<usize> {
-    let scrobbles = atproto::fetch_scrobbles(did, 2024)
-        .await
-        .context("Failed to fetch scrobbles")?;
+    let watermark = if full {
+        None
+    } else {
+        db::get_import_watermark(db_pool, did)
+            .await
+            .context("Failed to load import watermark")?
+    };
+
+    let range = match watermark {
+        Some(since) => atproto::DateRange::since(since),
+        None => atproto::DateRange::all_time(),
+    };
+
+    let stream = atproto::fetch_scrobbles_stream(did, range);
+    futures::pin_mut!(stream);
+    let mut scrobbles = Vec::new();
+    while let Some(record) = stream.next().await {
+        scrobbles.push(record.context("Failed to fetch scrobbles")?);
+    }
 
     if scrobbles.is_empty() {
         return Ok(0);
     }
 
+    // The watermark tracks everything we've now seen in this range, independent of the
+    // `year` filter below, so a later run never re-fetches it.
+    let newest_played_at = scrobbles
+        .iter()
+        .filter_map(|s| s.played_time.as_deref())
+        .filter_map(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .max();
+
     let filtered_scrobbles: Vec<_> = if let Some(target_year) = year {
         scrobbles
             .into_iter()
@@ -386,20 +872,31 @@ async fn import_user_scrobbles(
         scrobbles
     };
 
-    if filtered_scrobbles.is_empty() {
-        return Ok(0);
-    }
-
     let count = filtered_scrobbles.len();
 
-    db::store_user_plays(db_pool, did, &filtered_scrobbles)
-        .await
-        .context("Failed to store user plays in the database")?;
+    if !filtered_scrobbles.is_empty() {
+        db::store_user_plays(db_pool, did, &filtered_scrobbles)
+            .await
+            .context("Failed to store user plays in the database")?;
+    }
+
+    if let Some(newest) = newest_played_at {
+        db::set_import_watermark(db_pool, did, newest)
+            .await
+            .context("Failed to update import watermark")?;
+    }
 
     Ok(count)
 }
 
-async fn handle_retry_queue_list() -> Result<()> {
+#[derive(Debug, Serialize)]
+struct RetryQueueEntry {
+    did: String,
+    retry_count: i32,
+    last_attempt: chrono::DateTime<chrono::Utc>,
+}
+
+async fn handle_retry_queue_list() -> Result<serde_json::Value> {
     let db_pool = db::init_db()
         .await
         .context("Failed to initialize database")?;
@@ -410,23 +907,39 @@ async fn handle_retry_queue_list() -> Result<()> {
 
     if queue.is_empty() {
         tracing::info!("Retry queue is empty");
-        return Ok(());
+        return Ok(serde_json::to_value(Vec::<RetryQueueEntry>::new())?);
     }
 
     tracing::info!("Retry queue ({} users):", queue.len());
-    for (did, retry_count, last_attempt) in queue {
-        tracing::info!(
-            "  {} - retries: {}, last attempt: {}",
-            did,
-            retry_count,
-            last_attempt
-        );
-    }
+    let entries: Vec<RetryQueueEntry> = queue
+        .into_iter()
+        .map(|(did, retry_count, last_attempt)| {
+            tracing::info!(
+                "  {} - retries: {}, last attempt: {}",
+                did,
+                retry_count,
+                last_attempt
+            );
+            RetryQueueEntry {
+                did,
+                retry_count,
+                last_attempt,
+            }
+        })
+        .collect();
 
-    Ok(())
+    Ok(serde_json::to_value(entries)?)
+}
+
+#[derive(Debug, Serialize)]
+struct RetryQueueProcessSummary {
+    checked: usize,
+    due: usize,
+    processed: usize,
+    failed: usize,
 }
 
-async fn handle_retry_queue_process(parallelism: usize) -> Result<()> {
+async fn handle_retry_queue_process(parallelism: usize) -> Result<serde_json::Value> {
     let db_pool = db::init_db()
         .await
         .context("Failed to initialize database")?;
@@ -434,32 +947,88 @@ async fn handle_retry_queue_process(parallelism: usize) -> Result<()> {
     let queue = db::get_retry_queue(&db_pool)
         .await
         .context("Failed to fetch retry queue")?;
+    let checked = queue.len();
 
     if queue.is_empty() {
         tracing::info!("Retry queue is empty, nothing to process");
-        return Ok(());
+        return Ok(serde_json::to_value(RetryQueueProcessSummary {
+            checked,
+            due: 0,
+            processed: 0,
+            failed: 0,
+        })?);
+    }
+
+    // Honor the same backoff schedule and max-retry cutoff as the background
+    // `retry_worker`, so a manual `Process` run doesn't hammer a row that isn't due yet.
+    let now = chrono::Utc::now();
+    let due: Vec<(String, i32)> = queue
+        .into_iter()
+        .filter_map(|(did, retry_count, last_attempt)| {
+            let backoff = chrono::Duration::from_std(retry_worker::backoff_for(retry_count)).ok()?;
+            (last_attempt + backoff <= now).then_some((did, retry_count))
+        })
+        .collect();
+
+    if due.is_empty() {
+        tracing::info!("Retry queue has entries, but none are due yet");
+        return Ok(serde_json::to_value(RetryQueueProcessSummary {
+            checked,
+            due: 0,
+            processed: 0,
+            failed: 0,
+        })?);
     }
 
     tracing::info!(
-        "Processing {} users from retry queue with parallelism {}",
-        queue.len(),
+        "Processing {} due users from retry queue with parallelism {}",
+        due.len(),
         parallelism
     );
 
     let processed = Arc::new(AtomicUsize::new(0));
     let failed = Arc::new(AtomicUsize::new(0));
-    let total = queue.len();
+    let total = due.len();
+    let progress = BulkProgress::new("retry-queue-process", total);
+    progress.spawn_reporter();
 
-    stream::iter(queue.into_iter())
-        .map(|(did, _, _)| {
+    stream::iter(due.into_iter())
+        .map(|(did, retry_count)| {
             let db_pool = db_pool.clone();
             let processed = processed.clone();
             let failed = failed.clone();
+            let progress = progress.clone();
 
             async move {
+                progress.start_item();
                 let current = processed.fetch_add(1, Ordering::SeqCst) + 1;
 
-                match db::refresh_user_stats(&db_pool).await {
+                if retry_count >= retry_worker::MAX_RETRY_COUNT {
+                    failed.fetch_add(1, Ordering::SeqCst);
+                    tracing::error!(
+                        "[{}/{}] {} exceeded {} refresh retries, moving to dead letter",
+                        current,
+                        total,
+                        did,
+                        retry_worker::MAX_RETRY_COUNT
+                    );
+                    if let Err(e) = db::mark_retry_dead_letter(
+                        &db_pool,
+                        &did,
+                        &format!("exceeded max retry count ({})", retry_worker::MAX_RETRY_COUNT),
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to dead-letter {}: {}", did, e);
+                    }
+                    progress.finish_item();
+                    return;
+                }
+
+                match db::refresh_user_stats(&db_pool)
+                    .with_stall_watchdog(did.clone(), STALL_WARN_AFTER)
+                    .await
+                {
                     Ok(true) => {
                         tracing::info!(
                             "[{}/{}] Successfully refreshed views for {}",
@@ -479,12 +1048,19 @@ async fn handle_retry_queue_process(parallelism: usize) -> Result<()> {
                             total,
                             did
                         );
+                        if let Err(e) = db::add_to_retry_queue(&db_pool, &did).await {
+                            tracing::warn!("Failed to bump retry count for {}: {}", did, e);
+                        }
                     }
                     Err(e) => {
                         failed.fetch_add(1, Ordering::SeqCst);
                         tracing::error!("[{}/{}] Error processing {}: {}", current, total, did, e);
+                        if let Err(e) = db::add_to_retry_queue(&db_pool, &did).await {
+                            tracing::warn!("Failed to bump retry count for {}: {}", did, e);
+                        }
                     }
                 }
+                progress.finish_item();
             }
         })
         .buffer_unordered(parallelism)
@@ -500,10 +1076,20 @@ async fn handle_retry_queue_process(parallelism: usize) -> Result<()> {
         processed_count - failed_count
     );
 
-    Ok(())
+    Ok(serde_json::to_value(RetryQueueProcessSummary {
+        checked,
+        due: total,
+        processed: processed_count,
+        failed: failed_count,
+    })?)
 }
 
-async fn handle_retry_queue_clear() -> Result<()> {
+#[derive(Debug, Serialize)]
+struct RetryQueueClearSummary {
+    cleared: usize,
+}
+
+async fn handle_retry_queue_clear() -> Result<serde_json::Value> {
     let db_pool = db::init_db()
         .await
         .context("Failed to initialize database")?;
@@ -517,10 +1103,78 @@ async fn handle_retry_queue_clear() -> Result<()> {
 
     tracing::info!("Cleared {} users from retry queue", queue_size);
 
-    Ok(())
+    Ok(serde_json::to_value(RetryQueueClearSummary {
+        cleared: queue_size,
+    })?)
+}
+
+#[derive(Debug, Serialize)]
+struct DeadLetterEntry {
+    did: String,
+    retry_count: i32,
+    last_attempt: chrono::DateTime<chrono::Utc>,
+    last_error: Option<String>,
+}
+
+async fn handle_dead_letter_list() -> Result<serde_json::Value> {
+    let db_pool = db::init_db()
+        .await
+        .context("Failed to initialize database")?;
+
+    let dead_letters = db::get_dead_letter_queue(&db_pool)
+        .await
+        .context("Failed to fetch dead letter queue")?;
+
+    if dead_letters.is_empty() {
+        tracing::info!("Dead letter queue is empty");
+        return Ok(serde_json::to_value(Vec::<DeadLetterEntry>::new())?);
+    }
+
+    tracing::info!("Dead letter queue ({} users):", dead_letters.len());
+    let entries: Vec<DeadLetterEntry> = dead_letters
+        .into_iter()
+        .map(|(did, retry_count, last_attempt, last_error)| {
+            tracing::info!(
+                "  {} - retries: {}, last attempt: {}, last error: {}",
+                did,
+                retry_count,
+                last_attempt,
+                last_error.as_deref().unwrap_or("unknown")
+            );
+            DeadLetterEntry {
+                did,
+                retry_count,
+                last_attempt,
+                last_error,
+            }
+        })
+        .collect();
+
+    Ok(serde_json::to_value(entries)?)
+}
+
+async fn handle_dead_letter_requeue(did: &str) -> Result<serde_json::Value> {
+    let db_pool = db::init_db()
+        .await
+        .context("Failed to initialize database")?;
+
+    db::requeue_dead_letter(&db_pool, did)
+        .await
+        .context("Failed to requeue dead-lettered user")?;
+
+    tracing::info!("Requeued {} for normal retry processing", did);
+
+    Ok(serde_json::json!({ "did": did, "requeued": true }))
 }
 
-async fn handle_backfill_mbids() -> Result<()> {
+#[derive(Debug, Serialize)]
+struct BackfillMbidsSummary {
+    artists_updated: u64,
+    recordings_updated: u64,
+    releases_updated: u64,
+}
+
+async fn handle_backfill_mbids() -> Result<serde_json::Value> {
     let db_pool = db::init_db()
         .await
         .context("Failed to initialize database")?;
@@ -682,17 +1336,194 @@ async fn handle_backfill_mbids() -> Result<()> {
 
     tracing::info!("Backfill complete!");
 
-    Ok(())
+    Ok(serde_json::to_value(BackfillMbidsSummary {
+        artists_updated: artist_result.rows_affected(),
+        recordings_updated: recording_result.rows_affected(),
+        releases_updated: release_result.rows_affected(),
+    })?)
+}
+
+#[derive(Debug, Serialize)]
+struct MusicbrainzBackfillSummary {
+    scanned: usize,
+    resolved: usize,
+}
+
+async fn handle_backfill_musicbrainz_ids(batch_size: i64) -> Result<serde_json::Value> {
+    let db_pool = db::init_db()
+        .await
+        .context("Failed to initialize database")?;
+
+    tracing::info!("Resolving missing musicbrainz IDs via the MusicBrainz API...");
+
+    let mut total_scanned = 0usize;
+    let mut total_resolved = 0usize;
+
+    loop {
+        let batch = atproto::backfill_missing_musicbrainz_ids(&db_pool, batch_size)
+            .await
+            .context("Failed to run musicbrainz backfill batch")?;
+
+        total_scanned += batch.scanned;
+        total_resolved += batch.resolved;
+
+        tracing::info!(
+            "Batch complete: scanned {}, resolved {} (up to id {})",
+            batch.scanned,
+            batch.resolved,
+            batch.last_id
+        );
+
+        if batch.done {
+            break;
+        }
+    }
+
+    tracing::info!(
+        "Musicbrainz backfill complete. Scanned: {}, resolved: {}",
+        total_scanned,
+        total_resolved
+    );
+
+    Ok(serde_json::to_value(MusicbrainzBackfillSummary {
+        scanned: total_scanned,
+        resolved: total_resolved,
+    })?)
+}
+
+async fn handle_sql(query: Option<String>) -> Result<serde_json::Value> {
+    let query = match query {
+        Some(q) => q,
+        None => {
+            let mut buf = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut buf)
+                .context("Failed to read query from stdin")?;
+            buf
+        }
+    };
+
+    if query.trim().is_empty() {
+        anyhow::bail!("No query provided (pass one as an argument or pipe it via stdin)");
+    }
+
+    let db_pool = db::init_db()
+        .await
+        .context("Failed to initialize database")?;
+
+    // Reject anything that mutates by running inside a read-only transaction - Postgres
+    // itself rejects INSERT/UPDATE/DELETE/DDL under `SET TRANSACTION READ ONLY`, so there's
+    // no need to parse or allowlist the query text.
+    let mut tx = db_pool
+        .begin()
+        .await
+        .context("Failed to start transaction")?;
+    sqlx::query("SET TRANSACTION READ ONLY")
+        .execute(&mut *tx)
+        .await
+        .context("Failed to set transaction read only")?;
+
+    let rows = sqlx::query(&query)
+        .fetch_all(&mut *tx)
+        .await
+        .context("Query failed")?;
+
+    tx.rollback().await.ok();
+
+    let columns: Vec<String> = rows
+        .first()
+        .map(|row| row.columns().iter().map(|c| c.name().to_string()).collect())
+        .unwrap_or_default();
+
+    let records: Vec<serde_json::Map<String, serde_json::Value>> = rows
+        .iter()
+        .map(pg_row_to_json)
+        .collect::<Result<_>>()
+        .context("Failed to decode query result")?;
+
+    if columns.is_empty() {
+        tracing::info!("Query returned no rows");
+    } else {
+        tracing::info!("{}", columns.join(" | "));
+        for record in &records {
+            let line = columns
+                .iter()
+                .map(|c| {
+                    record
+                        .get(c)
+                        .map(json_value_to_cell)
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>()
+                .join(" | ");
+            tracing::info!("{}", line);
+        }
+        tracing::info!("({} row(s))", records.len());
+    }
+
+    Ok(serde_json::Value::Array(
+        records.into_iter().map(serde_json::Value::Object).collect(),
+    ))
+}
+
+fn json_value_to_cell(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "NULL".to_string(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Decode a row with no schema known ahead of time by trying the column types `user_plays`
+/// and its rolling-window views actually use, in order, falling back to NULL if none fit.
+fn pg_row_to_json(
+    row: &sqlx::postgres::PgRow,
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+    let mut map = serde_json::Map::new();
+    for column in row.columns() {
+        let name = column.name();
+        let value = if let Ok(v) = row.try_get::<Option<String>, _>(name) {
+            v.map(serde_json::Value::String)
+                .unwrap_or(serde_json::Value::Null)
+        } else if let Ok(v) = row.try_get::<Option<i64>, _>(name) {
+            v.map(|n| serde_json::Value::Number(n.into()))
+                .unwrap_or(serde_json::Value::Null)
+        } else if let Ok(v) = row.try_get::<Option<i32>, _>(name) {
+            v.map(|n| serde_json::Value::Number(n.into()))
+                .unwrap_or(serde_json::Value::Null)
+        } else if let Ok(v) = row.try_get::<Option<f64>, _>(name) {
+            v.and_then(serde_json::Number::from_f64)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null)
+        } else if let Ok(v) = row.try_get::<Option<bool>, _>(name) {
+            v.map(serde_json::Value::Bool)
+                .unwrap_or(serde_json::Value::Null)
+        } else if let Ok(v) = row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>(name) {
+            v.map(|dt| serde_json::Value::String(dt.to_rfc3339()))
+                .unwrap_or(serde_json::Value::Null)
+        } else if let Ok(v) = row.try_get::<Option<serde_json::Value>, _>(name) {
+            v.unwrap_or(serde_json::Value::Null)
+        } else {
+            serde_json::Value::Null
+        };
+        map.insert(name.to_string(), value);
+    }
+    Ok(map)
 }
 
 async fn calculate_and_cache_wrapped(
     db_pool: &sqlx::PgPool,
     user_did: &str,
     year: u32,
+    similar_users: Option<&[db::SimilarUser]>,
 ) -> Result<()> {
-    let stats = wrapped::calculate_wrapped_stats(db_pool, user_did, year)
-        .await
-        .context("Failed to calculate wrapped stats")?;
+    let stats = wrapped::calculate_wrapped_stats(
+        db_pool,
+        user_did,
+        wrapped::ReportWindow::Year(year as i32),
+        None,
+    )
+    .await
+    .context("Failed to calculate wrapped stats")?;
 
     let top_artists: Vec<TopArtist> = stats
         .top_artists
@@ -745,6 +1576,22 @@ async fn calculate_and_cache_wrapped(
         })
         .collect();
 
+    // No handle/profile picture resolution here - this is a bulk offline job, and
+    // those are resolved lazily by the live `get_wrapped` handler on read.
+    let similar_users = similar_users.map(|users| {
+        users
+            .iter()
+            .map(|u| MusicBuddy {
+                did: ids::Did::new_unchecked(u.did.clone()),
+                handle: None,
+                profile_picture: None,
+                similarity_score: u.similarity_score,
+                shared_artist_count: u.shared_artists.len() as u32,
+                shared_artists: u.shared_artists.clone(),
+            })
+            .collect::<Vec<_>>()
+    });
+
     let wrapped_data = WrappedData {
         year,
         total_minutes: stats.total_minutes,
@@ -755,19 +1602,37 @@ async fn calculate_and_cache_wrapped(
         activity_graph,
         weekday_avg_minutes: stats.weekday_avg_minutes,
         weekend_avg_minutes: stats.weekend_avg_minutes,
-        longest_streak: stats.longest_streak,
+        streaks: StreakStats::from(stats.streaks),
         days_active: stats.days_active,
         avg_track_length_ms: stats.avg_track_length_ms,
         listening_diversity: stats.listening_diversity,
         hourly_distribution: stats.hourly_distribution,
         top_hour: stats.top_hour,
         longest_session_minutes: stats.longest_session_minutes,
-        similar_users: None,
+        peak_hour: stats.peak_hour,
+        peak_weekday: stats.peak_weekday,
+        typical_session_hour: stats.typical_session_hour,
+        top_rising_artists: stats.top_rising_artists,
+        session_count: stats.session_count,
+        avg_session_minutes: stats.avg_session_minutes,
+        most_common_session_start_hour: stats.most_common_session_start_hour,
+        epic_session: stats.epic_session.map(EpicSession::from),
+        monthly_plays: stats.monthly_plays,
+        monthly_minutes: stats.monthly_minutes,
+        top_artist_per_month: stats.top_artist_per_month,
+        seasonal_distribution: stats.seasonal_distribution,
+        top_artist_affinities: stats.top_artist_affinities,
+        similar_users,
     };
 
-    db::cache_wrapped(db_pool, user_did, year, &wrapped_data)
-        .await
-        .context("Failed to cache wrapped stats")?;
+    db::cache_wrapped(
+        db_pool,
+        user_did,
+        wrapped::ReportWindow::Year(year as i32),
+        &wrapped_data,
+    )
+    .await
+    .context("Failed to cache wrapped stats")?;
 
     Ok(())
 }
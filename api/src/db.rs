@@ -2,8 +2,30 @@ use anyhow::Result;
 use chrono::Utc;
 use sqlx::postgres::PgPool;
 use sqlx::Row;
+use std::sync::OnceLock;
+use std::time::Duration;
 
-use crate::{atproto::ScrobbleRecord, global_stats::GlobalStats, models::*, WrappedData};
+use crate::{
+    atproto::ScrobbleRecord, future::WithPollTimer, global_stats::GlobalStats, models::*,
+    ttl_cache::TtlCache, wrapped::ReportWindow, WrappedData,
+};
+
+const WRAPPED_MEMORY_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+const WRAPPED_MEMORY_CACHE_CAPACITY: usize = 1000;
+const GLOBAL_STATS_MEMORY_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+const GLOBAL_STATS_MEMORY_CACHE_CAPACITY: usize = 16;
+/// How far ahead of expiry `run_global_stats_rehydrate_task` refreshes an entry.
+const GLOBAL_STATS_REHYDRATE_WINDOW: Duration = Duration::from_secs(60);
+
+fn wrapped_memory_cache() -> &'static TtlCache<(String, String), WrappedData> {
+    static CACHE: OnceLock<TtlCache<(String, String), WrappedData>> = OnceLock::new();
+    CACHE.get_or_init(|| TtlCache::new(WRAPPED_MEMORY_CACHE_TTL, WRAPPED_MEMORY_CACHE_CAPACITY))
+}
+
+fn global_stats_memory_cache() -> &'static TtlCache<u32, GlobalStats> {
+    static CACHE: OnceLock<TtlCache<u32, GlobalStats>> = OnceLock::new();
+    CACHE.get_or_init(|| TtlCache::new(GLOBAL_STATS_MEMORY_CACHE_TTL, GLOBAL_STATS_MEMORY_CACHE_CAPACITY))
+}
 
 pub async fn init_db() -> Result<PgPool> {
     let database_url = std::env::var("DATABASE_URL")
@@ -24,45 +46,78 @@ pub async fn init_db() -> Result<PgPool> {
 pub async fn get_cached_wrapped(
     pool: &PgPool,
     user_did: &str,
-    year: u32,
+    window: ReportWindow,
 ) -> Result<Option<WrappedData>> {
+    let window_key = window.cache_key();
+    let memory_key = (user_did.to_string(), window_key.clone());
+    if let Some(cached) = wrapped_memory_cache().get(&memory_key) {
+        tracing::debug!(
+            "wrapped cache hit in memory for {} window {}",
+            user_did,
+            window_key
+        );
+        return Ok(Some(cached));
+    }
+
     let cached = sqlx::query_as::<_, WrappedCache>(
-        "SELECT user_did, year, data, created_at FROM wrapped_cache WHERE user_did = $1 AND year = $2",
+        "SELECT user_did, window_key, data, created_at FROM wrapped_cache WHERE user_did = $1 AND window_key = $2",
     )
     .bind(user_did)
-    .bind(year as i32)
+    .bind(&window_key)
     .fetch_optional(pool)
     .await?;
 
-    Ok(cached.and_then(|c| serde_json::from_value(c.data).ok()))
+    let data: Option<WrappedData> = cached.and_then(|c| serde_json::from_value(c.data).ok());
+    if let Some(data) = &data {
+        wrapped_memory_cache().insert(memory_key, data.clone());
+    }
+
+    Ok(data)
 }
 
 pub async fn cache_wrapped(
     pool: &PgPool,
     user_did: &str,
-    year: u32,
+    window: ReportWindow,
     data: &WrappedData,
 ) -> Result<()> {
+    let window_key = window.cache_key();
     let json_data = serde_json::to_value(data)?;
 
     sqlx::query(
         r#"
-        INSERT INTO wrapped_cache (user_did, year, data)
+        INSERT INTO wrapped_cache (user_did, window_key, data)
         VALUES ($1, $2, $3)
-        ON CONFLICT (user_did, year)
+        ON CONFLICT (user_did, window_key)
         DO UPDATE SET data = $3, created_at = NOW()
         "#,
     )
     .bind(user_did)
-    .bind(year as i32)
+    .bind(&window_key)
     .bind(json_data)
     .execute(pool)
     .await?;
 
+    wrapped_memory_cache().insert((user_did.to_string(), window_key), data.clone());
+
     Ok(())
 }
 
 pub async fn get_cached_global_stats(pool: &PgPool, year: u32) -> Result<Option<GlobalStats>> {
+    if let Some(cached) = global_stats_memory_cache().get(&year) {
+        tracing::debug!("global stats cache hit in memory for year {}", year);
+        return Ok(Some(cached));
+    }
+
+    let data = fetch_global_stats_from_postgres(pool, year).await?;
+    if let Some(data) = &data {
+        global_stats_memory_cache().insert(year, data.clone());
+    }
+
+    Ok(data)
+}
+
+async fn fetch_global_stats_from_postgres(pool: &PgPool, year: u32) -> Result<Option<GlobalStats>> {
     let cached =
         sqlx::query("SELECT year, data, created_at FROM global_stats_cache WHERE year = $1")
             .bind(year as i32)
@@ -91,9 +146,95 @@ pub async fn cache_global_stats(pool: &PgPool, year: u32, data: &GlobalStats) ->
     .execute(pool)
     .await?;
 
+    global_stats_memory_cache().insert(year, data.clone());
+
+    Ok(())
+}
+
+/// Returns the cached `GlobalStats` for `year` if it was computed within `max_age`,
+/// recomputing and upserting it otherwise - mirroring the `WrappedCache` get-or-compute
+/// pattern, but driven by a caller-supplied freshness window instead of always trusting
+/// whatever's cached.
+pub async fn get_or_compute_global_stats(
+    pool: &PgPool,
+    year: u32,
+    max_age: Duration,
+) -> Result<GlobalStats> {
+    if let Some(cached) = fetch_global_stats_from_postgres_if_fresh(pool, year, max_age).await? {
+        global_stats_memory_cache().insert(year, cached.clone());
+        return Ok(cached);
+    }
+
+    tracing::info!("global stats cache stale or missing for year {}, recomputing", year);
+    let data = crate::global_stats::calculate_global_stats(
+        pool,
+        year,
+        crate::global_stats::ArtistCreditScope::default(),
+    )
+    .await?;
+
+    cache_global_stats(pool, year, &data).await?;
+
+    Ok(data)
+}
+
+async fn fetch_global_stats_from_postgres_if_fresh(
+    pool: &PgPool,
+    year: u32,
+    max_age: Duration,
+) -> Result<Option<GlobalStats>> {
+    let cached = sqlx::query("SELECT data, created_at FROM global_stats_cache WHERE year = $1")
+        .bind(year as i32)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(cached.and_then(|row| {
+        let created_at: chrono::DateTime<Utc> = row.get("created_at");
+        let age = Utc::now().signed_duration_since(created_at).to_std().ok()?;
+        if age > max_age {
+            return None;
+        }
+
+        let data: serde_json::Value = row.get("data");
+        serde_json::from_value(data).ok()
+    }))
+}
+
+/// Drop the cached `GlobalStats` for `year`, in both Postgres and memory - for use after a
+/// bulk ingest backfills plays for that year and the cached aggregates are now stale.
+pub async fn invalidate_global_stats(pool: &PgPool, year: u32) -> Result<()> {
+    sqlx::query("DELETE FROM global_stats_cache WHERE year = $1")
+        .bind(year as i32)
+        .execute(pool)
+        .await?;
+
+    global_stats_memory_cache().remove(&year);
+
     Ok(())
 }
 
+/// Periodically refresh memory-cached `GlobalStats` entries that are about to expire, by
+/// re-reading the Postgres cache table, so popular years stay served from memory instead
+/// of every request racing to repopulate the cache right after it lapses.
+pub async fn run_global_stats_rehydrate_task(pool: PgPool) {
+    let mut interval = tokio::time::interval(GLOBAL_STATS_REHYDRATE_WINDOW / 2);
+    loop {
+        interval.tick().await;
+
+        let stale_soon = global_stats_memory_cache().keys_near_expiry(GLOBAL_STATS_REHYDRATE_WINDOW);
+        for year in stale_soon {
+            match fetch_global_stats_from_postgres(&pool, year).await {
+                Ok(Some(data)) => {
+                    global_stats_memory_cache().insert(year, data);
+                    tracing::debug!("rehydrated in-memory global stats cache for year {}", year);
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("failed to rehydrate global stats cache for year {}: {}", year, e),
+            }
+        }
+    }
+}
+
 pub async fn get_scrobbles_for_year(
     pool: &PgPool,
     user_did: &str,
@@ -156,6 +297,7 @@ pub async fn get_scrobbles_for_year(
                 release_mb_id: r.release_mb_id,
                 release_name: r.release_name,
                 artist_mb_ids,
+                spotify_track_url: None,
             }
         })
         .collect();
@@ -175,163 +317,304 @@ fn normalize_name(name: &str) -> String {
         .join(" ")
 }
 
+/// Resolve known MusicBrainz artist IDs for a batch of normalized artist names in one
+/// query, instead of one `SELECT` per scrobble.
+async fn lookup_artist_mb_ids(
+    pool: &PgPool,
+    normalized_names: &std::collections::HashSet<String>,
+) -> Result<std::collections::HashMap<String, String>> {
+    if normalized_names.is_empty() {
+        return Ok(std::collections::HashMap::new());
+    }
+    let names: Vec<String> = normalized_names.iter().cloned().collect();
+
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT ON (normalized)
+            LOWER(TRIM((artists->0)->>'artistName')) as normalized,
+            (artists->0)->>'artistMbId' as mb_id
+        FROM user_plays
+        WHERE LOWER(TRIM((artists->0)->>'artistName')) = ANY($1)
+          AND (artists->0)->>'artistMbId' IS NOT NULL
+        "#,
+    )
+    .bind(&names)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Resolve known MusicBrainz recording IDs for a batch of (normalized track, normalized
+/// first artist) pairs in one query.
+async fn lookup_recording_mb_ids(
+    pool: &PgPool,
+    normalized_pairs: &std::collections::HashSet<(String, String)>,
+) -> Result<std::collections::HashMap<(String, String), String>> {
+    if normalized_pairs.is_empty() {
+        return Ok(std::collections::HashMap::new());
+    }
+    let tracks: Vec<String> = normalized_pairs.iter().map(|(t, _)| t.clone()).collect();
+    let artists: Vec<String> = normalized_pairs.iter().map(|(_, a)| a.clone()).collect();
+
+    let rows: Vec<(String, String, String)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT ON (track, artist)
+            LOWER(TRIM(track_name)) as track,
+            LOWER(TRIM((artists->0)->>'artistName')) as artist,
+            recording_mb_id
+        FROM user_plays
+        WHERE recording_mb_id IS NOT NULL
+          AND (LOWER(TRIM(track_name)), LOWER(TRIM((artists->0)->>'artistName')))
+              IN (SELECT * FROM UNNEST($1::text[], $2::text[]) AS t(track, artist))
+        "#,
+    )
+    .bind(&tracks)
+    .bind(&artists)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(track, artist, mb_id)| ((track, artist), mb_id))
+        .collect())
+}
+
+/// Resolve known MusicBrainz release IDs for a batch of normalized release names in one
+/// query.
+async fn lookup_release_mb_ids(
+    pool: &PgPool,
+    normalized_names: &std::collections::HashSet<String>,
+) -> Result<std::collections::HashMap<String, String>> {
+    if normalized_names.is_empty() {
+        return Ok(std::collections::HashMap::new());
+    }
+    let names: Vec<String> = normalized_names.iter().cloned().collect();
+
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT ON (normalized)
+            LOWER(TRIM(release_name)) as normalized,
+            release_mb_id
+        FROM user_plays
+        WHERE LOWER(TRIM(release_name)) = ANY($1)
+          AND release_mb_id IS NOT NULL
+        "#,
+    )
+    .bind(&names)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// One scrobble's worth of pre-resolved insert columns, staged before the bulk `UNNEST`
+/// insert so the per-row loop below never touches the database.
+struct PreparedPlay {
+    uri: String,
+    track_name: String,
+    artists_json: serde_json::Value,
+    recording_mb_id: Option<String>,
+    track_mb_id: Option<String>,
+    release_mb_id: Option<String>,
+    release_name: Option<String>,
+    duration_ms: Option<i32>,
+    played_at: chrono::DateTime<Utc>,
+}
+
 pub async fn store_user_plays(
     pool: &PgPool,
     user_did: &str,
     scrobbles: &[ScrobbleRecord],
 ) -> Result<()> {
-    let mut tx = pool.begin().await.map_err(|e| {
-        tracing::error!("Failed to begin transaction: {}", e);
-        e
-    })?;
+    // Only scrobbles with a parseable timestamp are insertable; parse that up front so the
+    // rest of the pipeline works off a flat, already-validated list.
+    let parsed: Vec<(&ScrobbleRecord, chrono::DateTime<Utc>)> = scrobbles
+        .iter()
+        .filter_map(|scrobble| {
+            let dt = chrono::DateTime::parse_from_rfc3339(scrobble.played_time.as_ref()?).ok()?;
+            Some((scrobble, dt.with_timezone(&Utc)))
+        })
+        .collect();
 
-    for scrobble in scrobbles {
-        if let Some(time_str) = &scrobble.played_time {
-            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(time_str) {
-                let played_at = dt.with_timezone(&Utc);
-                let duration_ms = scrobble
-                    .duration
-                    .and_then(|d| d.checked_mul(1000).and_then(|ms| i32::try_from(ms).ok()));
-
-                // Build artists jsonb array from artist names and mbids
-                // For each artist, if no mb_id, try to find one from existing records
-                let mut artists_data = Vec::new();
-                for (i, name) in scrobble.artists.iter().enumerate() {
-                    let mut mb_id = scrobble
-                        .artist_mb_ids
-                        .as_ref()
-                        .and_then(|ids| ids.get(i))
-                        .cloned();
-
-                    // If no mb_id provided, look for existing records with this artist name
-                    if mb_id.is_none() {
-                        let normalized = normalize_name(name);
-                        let existing = sqlx::query!(
-                            r#"
-                            SELECT DISTINCT (artists->0)->>'artistMbId' as mb_id
-                            FROM user_plays
-                            WHERE LOWER(TRIM((artists->0)->>'artistName')) = $1
-                            AND (artists->0)->>'artistMbId' IS NOT NULL
-                            LIMIT 1
-                            "#,
-                            normalized
-                        )
-                        .fetch_optional(&mut *tx)
-                        .await?;
-
-                        if let Some(row) = existing {
-                            mb_id = row.mb_id;
-                            if mb_id.is_some() {
-                                tracing::debug!(
-                                    "inherited mb_id for artist '{}' from existing records",
-                                    name
-                                );
-                            }
-                        }
-                    }
-
-                    artists_data.push(serde_json::json!({
-                        "artistName": name,
-                        "artistMbId": mb_id
-                    }));
-                }
+    if parsed.is_empty() {
+        return Ok(());
+    }
 
-                let artists_json = serde_json::json!(artists_data);
-
-                // Normalize recording_mb_id from existing records if not provided
-                let mut recording_mb_id = scrobble.recording_mb_id.clone();
-                if recording_mb_id.is_none() && !scrobble.artists.is_empty() {
-                    let normalized_track = normalize_name(&scrobble.track_name);
-                    let normalized_artist = normalize_name(&scrobble.artists[0]);
-
-                    let existing = sqlx::query!(
-                        r#"
-                        SELECT DISTINCT recording_mb_id
-                        FROM user_plays
-                        WHERE LOWER(TRIM(track_name)) = $1
-                        AND LOWER(TRIM((artists->0)->>'artistName')) = $2
-                        AND recording_mb_id IS NOT NULL
-                        LIMIT 1
-                        "#,
-                        normalized_track,
-                        normalized_artist
-                    )
-                    .fetch_optional(&mut *tx)
-                    .await?;
-
-                    if let Some(row) = existing {
-                        recording_mb_id = row.recording_mb_id;
-                        if recording_mb_id.is_some() {
-                            tracing::debug!("inherited recording_mb_id for track '{}' by '{}' from existing records", scrobble.track_name, scrobble.artists[0]);
-                        }
-                    }
-                }
+    // Collect the distinct normalized keys this batch needs MBIDs for, so we can resolve
+    // them in three grouped queries instead of up to three queries per scrobble.
+    let mut normalized_artists = std::collections::HashSet::new();
+    let mut normalized_tracks: std::collections::HashSet<(String, String)> = std::collections::HashSet::new();
+    let mut normalized_releases = std::collections::HashSet::new();
 
-                // Normalize release_mb_id from existing records if not provided
-                let mut release_mb_id = scrobble.release_mb_id.clone();
-                if release_mb_id.is_none() && scrobble.release_name.is_some() {
-                    let release_name = scrobble.release_name.as_ref().unwrap();
-                    let normalized_release = normalize_name(release_name);
-
-                    let existing = sqlx::query!(
-                        r#"
-                        SELECT DISTINCT release_mb_id
-                        FROM user_plays
-                        WHERE LOWER(TRIM(release_name)) = $1
-                        AND release_mb_id IS NOT NULL
-                        LIMIT 1
-                        "#,
-                        normalized_release
-                    )
-                    .fetch_optional(&mut *tx)
-                    .await?;
-
-                    if let Some(row) = existing {
-                        release_mb_id = row.release_mb_id;
-                        if release_mb_id.is_some() {
-                            tracing::debug!(
-                                "inherited release_mb_id for release '{}' from existing records",
-                                release_name
-                            );
-                        }
-                    }
-                }
+    for (scrobble, _) in &parsed {
+        for (i, name) in scrobble.artists.iter().enumerate() {
+            let has_mb_id = scrobble
+                .artist_mb_ids
+                .as_ref()
+                .and_then(|ids| ids.get(i))
+                .is_some();
+            if !has_mb_id {
+                normalized_artists.insert(normalize_name(name));
+            }
+        }
+
+        if scrobble.recording_mb_id.is_none() {
+            if let Some(first_artist) = scrobble.artists.first() {
+                normalized_tracks.insert((
+                    normalize_name(&scrobble.track_name),
+                    normalize_name(first_artist),
+                ));
+            }
+        }
 
-                sqlx::query(
-                    r#"
-                    INSERT INTO user_plays (
-                        user_did, uri, track_name, artists,
-                        recording_mb_id, track_mb_id, release_mb_id, release_name,
-                        duration_ms, played_at
-                    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-                    ON CONFLICT (uri) DO NOTHING
-                    "#,
-                )
-                .bind(user_did)
-                .bind(&scrobble.uri)
-                .bind(&scrobble.track_name)
-                .bind(&artists_json)
-                .bind(&recording_mb_id)
-                .bind(&scrobble.track_mb_id)
-                .bind(&release_mb_id)
-                .bind(&scrobble.release_name)
-                .bind(duration_ms)
-                .bind(played_at)
-                .execute(&mut *tx)
-                .await
-                .map_err(|e| {
-                    tracing::error!("Failed to insert play for uri {}: {}", &scrobble.uri, e);
-                    e
-                })?;
+        if scrobble.release_mb_id.is_none() {
+            if let Some(release_name) = &scrobble.release_name {
+                normalized_releases.insert(normalize_name(release_name));
             }
         }
     }
 
+    let artist_mb_ids = lookup_artist_mb_ids(pool, &normalized_artists).await?;
+    let recording_mb_ids = lookup_recording_mb_ids(pool, &normalized_tracks).await?;
+    let release_mb_ids = lookup_release_mb_ids(pool, &normalized_releases).await?;
+
+    let mut prepared = Vec::with_capacity(parsed.len());
+    for (scrobble, played_at) in parsed {
+        let duration_ms = scrobble
+            .duration
+            .and_then(|d| d.checked_mul(1000).and_then(|ms| i32::try_from(ms).ok()));
+
+        let artists_data: Vec<serde_json::Value> = scrobble
+            .artists
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let mb_id = scrobble
+                    .artist_mb_ids
+                    .as_ref()
+                    .and_then(|ids| ids.get(i))
+                    .cloned()
+                    .or_else(|| artist_mb_ids.get(&normalize_name(name)).cloned());
+
+                serde_json::json!({ "artistName": name, "artistMbId": mb_id })
+            })
+            .collect();
+
+        let recording_mb_id = scrobble.recording_mb_id.clone().or_else(|| {
+            let first_artist = scrobble.artists.first()?;
+            recording_mb_ids
+                .get(&(
+                    normalize_name(&scrobble.track_name),
+                    normalize_name(first_artist),
+                ))
+                .cloned()
+        });
+
+        let release_mb_id = scrobble.release_mb_id.clone().or_else(|| {
+            let release_name = scrobble.release_name.as_ref()?;
+            release_mb_ids.get(&normalize_name(release_name)).cloned()
+        });
+
+        prepared.push(PreparedPlay {
+            uri: scrobble.uri.clone(),
+            track_name: scrobble.track_name.clone(),
+            artists_json: serde_json::json!(artists_data),
+            recording_mb_id,
+            track_mb_id: scrobble.track_mb_id.clone(),
+            release_mb_id,
+            release_name: scrobble.release_name.clone(),
+            duration_ms,
+            played_at,
+        });
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        tracing::error!("Failed to begin transaction: {}", e);
+        e
+    })?;
+
+    // Insert the whole batch in chunks via a single multi-row UNNEST insert per chunk,
+    // instead of one round-trip per scrobble. `RETURNING uri` tracks which rows the
+    // `ON CONFLICT DO NOTHING` actually inserted, since `prepared` can contain plays already
+    // stored from a previous import run (e.g. a re-import over a range that was already
+    // imported) - folding those into
+    // `upsert_daily_rollups` too would inflate the day's count for a play that was never
+    // newly added.
+    let mut inserted_uris: std::collections::HashSet<String> =
+        std::collections::HashSet::with_capacity(prepared.len());
+    const CHUNK_SIZE: usize = 500;
+    for chunk in prepared.chunks(CHUNK_SIZE) {
+        let user_dids = vec![user_did.to_string(); chunk.len()];
+        let uris: Vec<&str> = chunk.iter().map(|p| p.uri.as_str()).collect();
+        let track_names: Vec<&str> = chunk.iter().map(|p| p.track_name.as_str()).collect();
+        let artists: Vec<&serde_json::Value> = chunk.iter().map(|p| &p.artists_json).collect();
+        let recording_mb_ids: Vec<Option<&str>> = chunk
+            .iter()
+            .map(|p| p.recording_mb_id.as_deref())
+            .collect();
+        let track_mb_ids: Vec<Option<&str>> = chunk.iter().map(|p| p.track_mb_id.as_deref()).collect();
+        let release_mb_ids: Vec<Option<&str>> = chunk
+            .iter()
+            .map(|p| p.release_mb_id.as_deref())
+            .collect();
+        let release_names: Vec<Option<&str>> = chunk.iter().map(|p| p.release_name.as_deref()).collect();
+        let duration_ms: Vec<Option<i32>> = chunk.iter().map(|p| p.duration_ms).collect();
+        let played_ats: Vec<chrono::DateTime<Utc>> = chunk.iter().map(|p| p.played_at).collect();
+
+        sqlx::query(
+            r#"
+            INSERT INTO user_plays (
+                user_did, uri, track_name, artists,
+                recording_mb_id, track_mb_id, release_mb_id, release_name,
+                duration_ms, played_at
+            )
+            SELECT * FROM UNNEST(
+                $1::text[], $2::text[], $3::text[], $4::jsonb[],
+                $5::text[], $6::text[], $7::text[], $8::text[],
+                $9::int4[], $10::timestamptz[]
+            )
+            ON CONFLICT (uri) DO NOTHING
+            RETURNING uri
+            "#,
+        )
+        .bind(&user_dids)
+        .bind(&uris)
+        .bind(&track_names)
+        .bind(&artists)
+        .bind(&recording_mb_ids)
+        .bind(&track_mb_ids)
+        .bind(&release_mb_ids)
+        .bind(&release_names)
+        .bind(&duration_ms)
+        .bind(&played_ats)
+        .fetch_all(&mut *tx)
+        .with_poll_timer("store_user_plays_bulk_insert")
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to bulk-insert plays for {}: {}", user_did, e);
+            e
+        })?
+        .into_iter()
+        .for_each(|row| {
+            inserted_uris.insert(row.get("uri"));
+        });
+    }
+
     tx.commit().await.map_err(|e| {
         tracing::error!("Failed to commit transaction: {}", e);
         e
     })?;
 
+    let newly_inserted: Vec<PreparedPlay> = prepared
+        .into_iter()
+        .filter(|p| inserted_uris.contains(&p.uri))
+        .collect();
+
+    if let Err(e) = upsert_daily_rollups(pool, user_did, &newly_inserted).await {
+        tracing::warn!("failed to upsert daily rollups for {}: {}", user_did, e);
+    }
+
     // Refresh materialized views after batch insert
     // If refresh fails after retries, we'll log it but continue
     // The data is safely committed, refresh can be done later
@@ -343,6 +626,193 @@ pub async fn store_user_plays(
     Ok(())
 }
 
+/// Fold a freshly-inserted batch of plays into `user_daily_rollups`: group by calendar day,
+/// then upsert the play-count/duration deltas and merge in any newly-seen artist/track names
+/// (deduped), via a single multi-row `UNNEST` upsert rather than one round trip per day.
+async fn upsert_daily_rollups(
+    pool: &PgPool,
+    user_did: &str,
+    prepared: &[PreparedPlay],
+) -> Result<()> {
+    use std::collections::BTreeMap;
+
+    #[derive(Default)]
+    struct DayDelta {
+        play_count: i32,
+        total_duration_ms: i64,
+        artist_names: std::collections::BTreeSet<String>,
+        track_names: std::collections::BTreeSet<String>,
+    }
+
+    let mut by_day: BTreeMap<chrono::NaiveDate, DayDelta> = BTreeMap::new();
+
+    for play in prepared {
+        let delta = by_day.entry(play.played_at.date_naive()).or_default();
+        delta.play_count += 1;
+        delta.total_duration_ms += play.duration_ms.unwrap_or(210000) as i64;
+        delta.track_names.insert(play.track_name.clone());
+        if let Some(artists) = play.artists_json.as_array() {
+            for artist in artists {
+                if let Some(name) = artist.get("artistName").and_then(|v| v.as_str()) {
+                    delta.artist_names.insert(name.to_string());
+                }
+            }
+        }
+    }
+
+    if by_day.is_empty() {
+        return Ok(());
+    }
+
+    let user_dids = vec![user_did.to_string(); by_day.len()];
+    let days: Vec<chrono::NaiveDate> = by_day.keys().copied().collect();
+    let play_counts: Vec<i32> = by_day.values().map(|d| d.play_count).collect();
+    let total_duration_ms: Vec<i64> = by_day.values().map(|d| d.total_duration_ms).collect();
+    let artist_names: Vec<Vec<String>> = by_day
+        .values()
+        .map(|d| d.artist_names.iter().cloned().collect())
+        .collect();
+    let track_names: Vec<Vec<String>> = by_day
+        .values()
+        .map(|d| d.track_names.iter().cloned().collect())
+        .collect();
+
+    sqlx::query(
+        r#"
+        INSERT INTO user_daily_rollups (user_did, day, play_count, total_duration_ms, artist_names, track_names)
+        SELECT * FROM UNNEST(
+            $1::text[], $2::date[], $3::int4[], $4::int8[], $5::text[][], $6::text[][]
+        )
+        ON CONFLICT (user_did, day) DO UPDATE
+            SET play_count = user_daily_rollups.play_count + EXCLUDED.play_count,
+                total_duration_ms = user_daily_rollups.total_duration_ms + EXCLUDED.total_duration_ms,
+                artist_names = (
+                    SELECT array_agg(DISTINCT name)
+                    FROM unnest(user_daily_rollups.artist_names || EXCLUDED.artist_names) AS name
+                ),
+                track_names = (
+                    SELECT array_agg(DISTINCT name)
+                    FROM unnest(user_daily_rollups.track_names || EXCLUDED.track_names) AS name
+                ),
+                updated_at = NOW()
+        "#,
+    )
+    .bind(&user_dids)
+    .bind(&days)
+    .bind(&play_counts)
+    .bind(&total_duration_ms)
+    .bind(&artist_names)
+    .bind(&track_names)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Backfill `user_daily_rollups` for every user from raw `user_plays` rows in `window`,
+/// replacing whatever rollup rows already exist for those days. Used to seed the table for
+/// historical data, or to repair it after a gap in incremental upserts (e.g. a failed import).
+pub async fn rebuild_rollups(pool: &PgPool, window: crate::wrapped::ReportWindow) -> Result<()> {
+    let (start, end) = window.bounds();
+
+    sqlx::query(
+        r#"
+        DELETE FROM user_daily_rollups
+        WHERE day >= $1::date AND day < $2::date
+        "#,
+    )
+    .bind(start)
+    .bind(end)
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        WITH play_totals AS (
+            SELECT
+                user_did,
+                played_at::date AS day,
+                COUNT(*) AS play_count,
+                SUM(COALESCE(duration_ms, 210000)) AS total_duration_ms,
+                array_agg(DISTINCT track_name) AS track_names
+            FROM user_plays
+            WHERE played_at >= $1 AND played_at < $2
+            GROUP BY user_did, played_at::date
+        ),
+        artist_totals AS (
+            SELECT
+                user_did,
+                played_at::date AS day,
+                array_agg(DISTINCT artist->>'artistName') FILTER (WHERE artist->>'artistName' IS NOT NULL) AS artist_names
+            FROM user_plays, jsonb_array_elements(artists) AS artist
+            WHERE played_at >= $1 AND played_at < $2
+            GROUP BY user_did, played_at::date
+        )
+        INSERT INTO user_daily_rollups (user_did, day, play_count, total_duration_ms, artist_names, track_names)
+        SELECT
+            pt.user_did,
+            pt.day,
+            pt.play_count,
+            pt.total_duration_ms,
+            COALESCE(at.artist_names, '{}'),
+            pt.track_names
+        FROM play_totals pt
+        LEFT JOIN artist_totals at USING (user_did, day)
+        ON CONFLICT (user_did, day) DO UPDATE
+            SET play_count = EXCLUDED.play_count,
+                total_duration_ms = EXCLUDED.total_duration_ms,
+                artist_names = EXCLUDED.artist_names,
+                track_names = EXCLUDED.track_names,
+                updated_at = NOW()
+        "#,
+    )
+    .bind(start)
+    .bind(end)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Most recent `played_time` seen by an import of `user_did`, used to bound an incremental
+/// import to only what's newer. `None` means the DID has never been imported.
+pub async fn get_import_watermark(
+    pool: &PgPool,
+    user_did: &str,
+) -> Result<Option<chrono::DateTime<Utc>>> {
+    let watermark: Option<chrono::DateTime<Utc>> = sqlx::query_scalar(
+        "SELECT last_played_at FROM import_watermarks WHERE user_did = $1",
+    )
+    .bind(user_did)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(watermark)
+}
+
+/// Advance `user_did`'s import watermark to `played_at`, if it's newer than what's stored.
+pub async fn set_import_watermark(
+    pool: &PgPool,
+    user_did: &str,
+    played_at: chrono::DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO import_watermarks (user_did, last_played_at, updated_at)
+        VALUES ($1, $2, NOW())
+        ON CONFLICT (user_did) DO UPDATE
+        SET last_played_at = GREATEST(import_watermarks.last_played_at, EXCLUDED.last_played_at),
+            updated_at = NOW()
+        "#,
+    )
+    .bind(user_did)
+    .bind(played_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 /// Refresh materialized views with concurrent refresh to allow reads during update
 /// Returns Ok(true) if successful, Ok(false) if should be retried later
 pub async fn refresh_user_stats(pool: &PgPool) -> Result<bool> {
@@ -394,21 +864,25 @@ pub async fn refresh_user_stats(pool: &PgPool) -> Result<bool> {
 async fn try_refresh_views(pool: &PgPool) -> Result<()> {
     sqlx::query("REFRESH MATERIALIZED VIEW CONCURRENTLY user_artist_stats")
         .execute(pool)
+        .with_poll_timer("refresh_user_artist_stats")
         .await?;
 
     sqlx::query("REFRESH MATERIALIZED VIEW CONCURRENTLY user_track_stats")
         .execute(pool)
+        .with_poll_timer("refresh_user_track_stats")
         .await?;
 
     sqlx::query("REFRESH MATERIALIZED VIEW CONCURRENTLY user_daily_activity")
         .execute(pool)
+        .with_poll_timer("refresh_user_daily_activity")
         .await?;
 
     Ok(())
 }
 
-/// Add a user to the retry queue for failed materialized view refreshes
-async fn add_to_retry_queue(pool: &PgPool, user_did: &str) -> Result<()> {
+/// Add a user to the retry queue for failed materialized view refreshes, or bump its
+/// retry count and `last_attempt` if it's already queued.
+pub async fn add_to_retry_queue(pool: &PgPool, user_did: &str) -> Result<()> {
     sqlx::query(
         r#"
         INSERT INTO refresh_retry_queue (user_did, retry_count, last_attempt)
@@ -425,12 +899,13 @@ async fn add_to_retry_queue(pool: &PgPool, user_did: &str) -> Result<()> {
     Ok(())
 }
 
-/// Get all users in the retry queue
+/// Get all users still pending in the retry queue (excludes dead-lettered rows)
 pub async fn get_retry_queue(pool: &PgPool) -> Result<Vec<(String, i32, chrono::DateTime<Utc>)>> {
     let rows = sqlx::query(
         r#"
         SELECT user_did, retry_count, last_attempt
         FROM refresh_retry_queue
+        WHERE NOT dead_letter
         ORDER BY last_attempt ASC
         "#,
     )
@@ -459,6 +934,112 @@ pub async fn remove_from_retry_queue(pool: &PgPool, user_did: &str) -> Result<()
     Ok(())
 }
 
+/// Move a user's retry queue row into the dead-letter state after it has exhausted its
+/// retry budget, recording the last error so an operator can see why without replaying
+/// the whole backoff history.
+pub async fn mark_retry_dead_letter(pool: &PgPool, user_did: &str, last_error: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE refresh_retry_queue
+        SET dead_letter = TRUE, last_error = $2, last_attempt = NOW()
+        WHERE user_did = $1
+        "#,
+    )
+    .bind(user_did)
+    .bind(last_error)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// List every dead-lettered row, most recently dead-lettered first, so an operator can
+/// inspect why a DID stopped being retried before deciding to requeue it.
+pub async fn get_dead_letter_queue(
+    pool: &PgPool,
+) -> Result<Vec<(String, i32, chrono::DateTime<Utc>, Option<String>)>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT user_did, retry_count, last_attempt, last_error
+        FROM refresh_retry_queue
+        WHERE dead_letter
+        ORDER BY last_attempt DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            (
+                row.get("user_did"),
+                row.get("retry_count"),
+                row.get("last_attempt"),
+                row.get("last_error"),
+            )
+        })
+        .collect())
+}
+
+/// Move a dead-lettered user back into the normal retry rotation, resetting its backoff
+/// so it's picked up on the next `Process` or worker poll.
+pub async fn requeue_dead_letter(pool: &PgPool, user_did: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE refresh_retry_queue
+        SET dead_letter = FALSE, retry_count = 0, last_error = NULL, last_attempt = NOW()
+        WHERE user_did = $1
+        "#,
+    )
+    .bind(user_did)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Upsert a user's Spotify OAuth tokens after a login or refresh.
+pub async fn store_spotify_tokens(
+    pool: &PgPool,
+    user_did: &str,
+    access_token: &str,
+    refresh_token: &str,
+    expires_at: chrono::DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO spotify_oauth_tokens (user_did, access_token, refresh_token, expires_at, updated_at)
+        VALUES ($1, $2, $3, $4, NOW())
+        ON CONFLICT (user_did)
+        DO UPDATE SET access_token = $2, refresh_token = $3, expires_at = $4, updated_at = NOW()
+        "#,
+    )
+    .bind(user_did)
+    .bind(access_token)
+    .bind(refresh_token)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Fetch a user's stored Spotify OAuth tokens, if they've ever connected their account.
+pub async fn get_spotify_tokens(
+    pool: &PgPool,
+    user_did: &str,
+) -> Result<Option<SpotifyOAuthTokens>> {
+    let tokens = sqlx::query_as::<_, SpotifyOAuthTokens>(
+        "SELECT user_did, access_token, refresh_token, expires_at FROM spotify_oauth_tokens WHERE user_did = $1",
+    )
+    .bind(user_did)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(tokens)
+}
+
 #[derive(Debug, Clone)]
 pub struct SimilarUser {
     pub did: String,
@@ -466,72 +1047,335 @@ pub struct SimilarUser {
     pub shared_artists: Vec<String>,
 }
 
-/// Find users with similar music taste using artist-level comparison
+/// Find users with similar music taste, ranked by TF-IDF weighted cosine similarity over
+/// per-user artist play-count vectors.
+///
+/// Raw shared-artist cardinality lets ubiquitous artists (Taylor Swift, The Beatles)
+/// dominate the ranking even though everyone overlaps on them. Instead, each artist is
+/// weighted by its inverse document frequency across all listeners for the year, so
+/// overlap on a niche artist counts for far more than overlap on one everybody plays.
 pub async fn find_similar_users(
     pool: &PgPool,
     user_did: &str,
     year: u32,
     limit: i64,
 ) -> Result<Vec<SimilarUser>> {
-    let rows = sqlx::query(
+    let rows: Vec<(String, String, i64)> = sqlx::query_as(
         r#"
-        WITH user_artists AS (
-            SELECT DISTINCT artist->>'artistName' as artist
-            FROM user_plays, jsonb_array_elements(artists) as artist
-            WHERE user_did = $1
-              AND EXTRACT(YEAR FROM played_at) = $2
-        ),
-        other_users AS (
-            SELECT
-                user_did,
-                array_agg(DISTINCT artist->>'artistName') as artists
-            FROM user_plays, jsonb_array_elements(artists) as artist
-            WHERE user_did != $1
-              AND EXTRACT(YEAR FROM played_at) = $2
-            GROUP BY user_did
-        )
         SELECT
-            ou.user_did,
-            cardinality(ARRAY(
-                SELECT unnest(ou.artists)
-                INTERSECT
-                SELECT artist FROM user_artists
-            )) as shared_count,
-            ARRAY(
-                SELECT unnest(ou.artists)
-                INTERSECT
-                SELECT artist FROM user_artists
-            ) as shared_artists
-        FROM other_users ou
-        WHERE cardinality(ARRAY(
-            SELECT unnest(ou.artists)
-            INTERSECT
-            SELECT artist FROM user_artists
-        )) > 0
-        ORDER BY shared_count DESC
-        LIMIT $3
+            user_did,
+            artist->>'artistName' as artist,
+            COUNT(*) as play_count
+        FROM user_plays, jsonb_array_elements(artists) as artist
+        WHERE EXTRACT(YEAR FROM played_at) = $1
+        GROUP BY user_did, artist
         "#,
     )
-    .bind(user_did)
     .bind(year as i32)
-    .bind(limit)
     .fetch_all(pool)
+    .with_poll_timer("find_similar_users_play_counts")
     .await?;
 
-    let similar_users = rows
-        .into_iter()
-        .map(|row| {
-            let did: String = row.get("user_did");
-            let shared_count: i32 = row.get("shared_count");
-            let shared_artists: Vec<String> = row.get("shared_artists");
+    // Per-user artist -> play count.
+    let mut user_counts: std::collections::HashMap<String, std::collections::HashMap<String, i64>> =
+        std::collections::HashMap::new();
+    for (did, artist, play_count) in rows {
+        *user_counts
+            .entry(did)
+            .or_default()
+            .entry(artist)
+            .or_insert(0) += play_count;
+    }
+
+    let total_users = user_counts.len() as f64;
+
+    // Document frequency: number of distinct users who played each artist at all.
+    let mut doc_freq: std::collections::HashMap<&str, f64> = std::collections::HashMap::new();
+    for counts in user_counts.values() {
+        for artist in counts.keys() {
+            *doc_freq.entry(artist.as_str()).or_insert(0.0) += 1.0;
+        }
+    }
+
+    let idf = |artist: &str| -> f64 {
+        let df = doc_freq.get(artist).copied().unwrap_or(0.0);
+        if df <= 0.0 {
+            0.0
+        } else {
+            (total_users / df).ln().max(0.0)
+        }
+    };
+
+    // TF-IDF weighted vector, and its L2 norm, for every user up front so we only compute
+    // each user's norm once regardless of how many candidates we compare them against.
+    let weighted_vectors: std::collections::HashMap<String, std::collections::HashMap<String, f64>> =
+        user_counts
+            .iter()
+            .map(|(did, counts)| {
+                let vector: std::collections::HashMap<String, f64> = counts
+                    .iter()
+                    .map(|(artist, count)| {
+                        let weight = (1.0 + *count as f64).ln() * idf(artist);
+                        (artist.clone(), weight)
+                    })
+                    .collect();
+                (did.clone(), vector)
+            })
+            .collect();
+
+    let norm = |vector: &std::collections::HashMap<String, f64>| -> f64 {
+        vector.values().map(|w| w * w).sum::<f64>().sqrt()
+    };
+
+    let Some(query_vector) = weighted_vectors.get(user_did) else {
+        return Ok(Vec::new());
+    };
+    let query_norm = norm(query_vector);
+    if query_norm == 0.0 {
+        return Ok(Vec::new());
+    }
+
+    let mut scored: Vec<SimilarUser> = weighted_vectors
+        .iter()
+        .filter(|(did, _)| did.as_str() != user_did)
+        .filter_map(|(did, candidate_vector)| {
+            let candidate_norm = norm(candidate_vector);
+            if candidate_norm == 0.0 {
+                return None;
+            }
 
-            SimilarUser {
-                did,
-                similarity_score: shared_count as f64,
+            let shared_artists: Vec<String> = query_vector
+                .keys()
+                .filter(|artist| candidate_vector.contains_key(*artist))
+                .cloned()
+                .collect();
+
+            if shared_artists.is_empty() {
+                return None;
+            }
+
+            let dot: f64 = shared_artists
+                .iter()
+                .map(|artist| query_vector[artist] * candidate_vector[artist])
+                .sum();
+
+            let similarity_score = dot / (query_norm * candidate_norm);
+
+            Some(SimilarUser {
+                did: did.clone(),
+                similarity_score,
                 shared_artists,
+            })
+        })
+        .collect();
+
+    scored.sort_by(|a, b| {
+        b.similarity_score
+            .partial_cmp(&a.similarity_score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    scored.truncate(limit.max(0) as usize);
+
+    Ok(scored)
+}
+
+/// Bulk counterpart to `find_similar_users`, used by `calculate_and_cache_wrapped` so
+/// `WrappedData.similar_users` can be precomputed for every user in one pass instead of
+/// `None`. Builds each user's artist play-count vector (keyed by `artistMbId`, falling back to
+/// a normalized `artistName` when no MBID is present), then for every user scores only the
+/// candidates that show up in the inverted artist->users index for at least one of their
+/// artists - never the full user x user cross product - and keeps those sharing at least
+/// `min_overlap` artists, ranked by raw cosine similarity.
+pub async fn compute_global_similar_users(
+    pool: &PgPool,
+    year: u32,
+    min_overlap: usize,
+    top_k: usize,
+) -> Result<std::collections::HashMap<String, Vec<SimilarUser>>> {
+    use std::collections::{HashMap, HashSet};
+
+    let rows: Vec<(String, Option<String>, String, i64)> = sqlx::query_as(
+        r#"
+        SELECT
+            user_did,
+            artist->>'artistMbId' as mb_id,
+            artist->>'artistName' as artist_name,
+            COUNT(*) as play_count
+        FROM user_plays, jsonb_array_elements(artists) as artist
+        WHERE EXTRACT(YEAR FROM played_at) = $1
+        GROUP BY user_did, mb_id, artist_name
+        "#,
+    )
+    .bind(year as i32)
+    .fetch_all(pool)
+    .with_poll_timer("compute_global_similar_users_play_counts")
+    .await?;
+
+    // Per-user artist-key -> play count, an inverted artist-key -> users index, and a
+    // display name for each artist key (preferring whichever name we saw first).
+    let mut user_counts: HashMap<String, HashMap<String, i64>> = HashMap::new();
+    let mut inverted_index: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut artist_display_names: HashMap<String, String> = HashMap::new();
+
+    for (did, mb_id, artist_name, play_count) in rows {
+        let key = mb_id
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| format!("name:{}", artist_name.to_lowercase().trim()));
+
+        *user_counts
+            .entry(did.clone())
+            .or_default()
+            .entry(key.clone())
+            .or_insert(0) += play_count;
+        inverted_index.entry(key.clone()).or_default().insert(did);
+        artist_display_names.entry(key).or_insert(artist_name);
+    }
+
+    let norm = |counts: &HashMap<String, i64>| -> f64 {
+        counts.values().map(|c| (*c as f64).powi(2)).sum::<f64>().sqrt()
+    };
+    let norms: HashMap<String, f64> = user_counts
+        .iter()
+        .map(|(did, counts)| (did.clone(), norm(counts)))
+        .collect();
+
+    let mut results: HashMap<String, Vec<SimilarUser>> = HashMap::new();
+
+    for (did, counts) in &user_counts {
+        let my_norm = norms[did];
+        if my_norm == 0.0 {
+            continue;
+        }
+
+        // Candidates restricted to users sharing at least one artist, discovered through the
+        // inverted index rather than scanning every other user.
+        let mut candidates: HashMap<String, (f64, Vec<String>)> = HashMap::new();
+        for (artist_key, my_count) in counts {
+            let Some(users) = inverted_index.get(artist_key) else {
+                continue;
+            };
+            for other_did in users {
+                if other_did == did {
+                    continue;
+                }
+                let other_count = user_counts[other_did][artist_key] as f64;
+                let entry = candidates.entry(other_did.clone()).or_insert((0.0, Vec::new()));
+                entry.0 += *my_count as f64 * other_count;
+                entry.1.push(artist_display_names[artist_key].clone());
+            }
+        }
+
+        let mut scored: Vec<SimilarUser> = candidates
+            .into_iter()
+            .filter(|(_, (_, shared_artists))| shared_artists.len() >= min_overlap)
+            .filter_map(|(other_did, (dot, shared_artists))| {
+                let other_norm = norms.get(&other_did).copied().unwrap_or(0.0);
+                if other_norm == 0.0 {
+                    return None;
+                }
+                Some(SimilarUser {
+                    did: other_did,
+                    similarity_score: dot / (my_norm * other_norm),
+                    shared_artists,
+                })
+            })
+            .collect();
+
+        scored.sort_by(|a, b| {
+            b.similarity_score
+                .partial_cmp(&a.similarity_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        scored.truncate(top_k);
+
+        results.insert(did.clone(), scored);
+    }
+
+    Ok(results)
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ArtistRecommendation {
+    pub artist: String,
+    pub score: f64,
+    pub neighbor_count: u32,
+}
+
+/// Recommend artists `user_did` hasn't played in `year`, sourced from their nearest
+/// neighbors (per [`find_similar_users`]) and ranked by the summed
+/// `similarity_score * neighbor_play_count` across every neighbor who played the artist.
+pub async fn recommend_artists(
+    pool: &PgPool,
+    user_did: &str,
+    year: u32,
+    limit: i64,
+) -> Result<Vec<ArtistRecommendation>> {
+    use std::collections::{HashMap, HashSet};
+
+    let neighbors = find_similar_users(pool, user_did, year, 10).await?;
+    if neighbors.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let already_played: HashSet<String> = sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT artist->>'artistName' as artist
+        FROM user_plays, jsonb_array_elements(artists) as artist
+        WHERE user_did = $1 AND EXTRACT(YEAR FROM played_at) = $2
+        "#,
+    )
+    .bind(user_did)
+    .bind(year as i32)
+    .fetch_all(pool)
+    .with_poll_timer("recommend_artists_already_played")
+    .await?
+    .into_iter()
+    .collect();
+
+    let mut scores: HashMap<String, (f64, HashSet<String>)> = HashMap::new();
+
+    for neighbor in &neighbors {
+        let neighbor_counts: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT artist->>'artistName' as artist, COUNT(*) as play_count
+            FROM user_plays, jsonb_array_elements(artists) as artist
+            WHERE user_did = $1 AND EXTRACT(YEAR FROM played_at) = $2
+            GROUP BY artist
+            "#,
+        )
+        .bind(&neighbor.did)
+        .bind(year as i32)
+        .fetch_all(pool)
+        .with_poll_timer("recommend_artists_neighbor_counts")
+        .await?;
+
+        for (artist, play_count) in neighbor_counts {
+            if already_played.contains(&artist) {
+                continue;
             }
+            let entry = scores
+                .entry(artist)
+                .or_insert_with(|| (0.0, HashSet::new()));
+            entry.0 += neighbor.similarity_score * play_count as f64;
+            entry.1.insert(neighbor.did.clone());
+        }
+    }
+
+    let mut recommendations: Vec<ArtistRecommendation> = scores
+        .into_iter()
+        .map(|(artist, (score, contributors))| ArtistRecommendation {
+            artist,
+            score,
+            neighbor_count: contributors.len() as u32,
         })
         .collect();
 
-    Ok(similar_users)
+    recommendations.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    recommendations.truncate(limit.max(0) as usize);
+
+    Ok(recommendations)
 }
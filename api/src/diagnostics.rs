@@ -0,0 +1,92 @@
+//! Opt-in failure reports for external API calls.
+//!
+//! `lookup_release_from_recording` and the `fanart`/`atproto` HTTP call sites used to treat
+//! an unexpected status or a failed `response.json()` as a silent `None`/logged error, with
+//! the raw response gone by the time anyone went looking for it. Setting `DIAGNOSTIC_REPORTS=1`
+//! makes those call sites additionally dump a timestamped report under `reports/` with the
+//! request URL, status, and raw body, so a maintainer has something to reproduce the failure
+//! from. A no-op (no env lookup beyond the first call, no filesystem access at all) when unset.
+
+use std::sync::OnceLock;
+
+fn enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("DIAGNOSTIC_REPORTS")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+    })
+}
+
+/// Query parameter names that carry a credential rather than request data - reports get
+/// written to plaintext files under `reports/`, so any of these showing up in a logged URL
+/// (e.g. fanart.tv's `api_key=...`) would leak it to disk.
+const SENSITIVE_QUERY_PARAMS: &[&str] = &[
+    "api_key", "apikey", "key", "access_token", "token", "secret", "client_secret", "password",
+];
+
+/// Replace the value of any [`SENSITIVE_QUERY_PARAMS`] query parameter in `url` with
+/// `"REDACTED"`, so a credential embedded in a request URL never reaches a diagnostic report.
+/// Falls back to returning `url` unchanged if it doesn't parse as a URL at all.
+fn redact_url(url: &str) -> String {
+    let Ok(mut parsed) = reqwest::Url::parse(url) else {
+        return url.to_string();
+    };
+
+    let redacted: Vec<(String, String)> = parsed
+        .query_pairs()
+        .map(|(k, v)| {
+            if SENSITIVE_QUERY_PARAMS.contains(&k.to_lowercase().as_str()) {
+                (k.into_owned(), "REDACTED".to_string())
+            } else {
+                (k.into_owned(), v.into_owned())
+            }
+        })
+        .collect();
+
+    parsed
+        .query_pairs_mut()
+        .clear()
+        .extend_pairs(redacted)
+        .finish();
+
+    parsed.into()
+}
+
+/// Dump a report for a failed external call if `DIAGNOSTIC_REPORTS` is set. `status` is
+/// `None` for a failure that isn't a bad HTTP status (e.g. a body that failed to parse).
+/// Best-effort: a failure to write the report itself is only logged, never propagated.
+/// `url` has any credential-bearing query parameter (see [`SENSITIVE_QUERY_PARAMS`]) redacted
+/// before it's ever written to disk, regardless of whether the caller already scrubbed it.
+pub async fn report_failure(source: &str, url: &str, status: Option<reqwest::StatusCode>, body: &str) {
+    if !enabled() {
+        return;
+    }
+
+    let url = redact_url(url);
+    if let Err(e) = write_report(source, &url, status, body).await {
+        tracing::warn!("failed to write diagnostic report for {}: {}", source, e);
+    }
+}
+
+async fn write_report(
+    source: &str,
+    url: &str,
+    status: Option<reqwest::StatusCode>,
+    body: &str,
+) -> anyhow::Result<()> {
+    tokio::fs::create_dir_all("reports").await?;
+
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+    let path = format!("reports/{}_{}.txt", source, timestamp);
+
+    let status_line = status
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "n/a (parse failure)".to_string());
+
+    let contents = format!("url: {}\nstatus: {}\n\n{}\n", url, status_line, body);
+
+    tokio::fs::write(&path, contents).await?;
+    tracing::info!("wrote diagnostic report to {}", path);
+    Ok(())
+}
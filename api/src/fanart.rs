@@ -1,11 +1,66 @@
+use anyhow::Context;
 use reqwest::Client;
 use serde::Deserialize;
-use sqlx::PgPool;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
+/// How long a cached image (or negative "none" result) is trusted before it's re-resolved.
+const CACHE_DURATION_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// Spotify's `/v1/artists` batch-lookup endpoint caps out at 50 ids per request.
+const SPOTIFY_ARTISTS_BATCH_SIZE: usize = 50;
+
+/// Target width for [`ImageQuality::Thumbnail`] - small enough for a feed-list avatar.
+const THUMBNAIL_TARGET_WIDTH: u32 = 300;
+
+/// Which size image a caller wants back. Spotify returns several sizes per artist; fanart.tv
+/// returns exactly one with no size metadata, so fanart-sourced images are returned regardless
+/// of preset (best-effort, not worth turning down a found image over).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageQuality {
+    /// The largest image Spotify has - full-size, for profile pages.
+    Largest,
+    /// A small image for feed lists/avatars (~300px).
+    Thumbnail,
+    /// The Spotify image whose width is closest to the given target.
+    Closest(u32),
+}
+
+impl ImageQuality {
+    /// `None` for `Largest`, since "largest" isn't a fixed target width to aim for.
+    fn target_width(self) -> Option<u32> {
+        match self {
+            ImageQuality::Largest => None,
+            ImageQuality::Thumbnail => Some(THUMBNAIL_TARGET_WIDTH),
+            ImageQuality::Closest(target) => Some(target),
+        }
+    }
+}
+
+impl std::fmt::Display for ImageQuality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageQuality::Largest => write!(f, "largest"),
+            ImageQuality::Thumbnail => write!(f, "thumb"),
+            ImageQuality::Closest(target) => write!(f, "closest{}", target),
+        }
+    }
+}
+
+/// Does a cache entry recorded under `cached_quality` satisfy a request for `quality`? Since
+/// `artist_images` has one row per `mb_id` (`ON CONFLICT (mb_id)` in `cache_image`), a
+/// `Thumbnail` entry and a `Largest` entry for the same artist overwrite each other, so width
+/// alone can't tell them apart - a 300px thumbnail would otherwise satisfy a later full-size
+/// request. `cached_quality` is `None` only for rows written before this column existed;
+/// those are treated as a miss so they get re-resolved (and tagged) once.
+fn width_satisfies(quality: ImageQuality, cached_quality: Option<&str>) -> bool {
+    cached_quality == Some(quality.to_string().as_str())
+}
+
 #[derive(Debug, Deserialize)]
 struct FanartResponse {
     #[serde(default)]
@@ -29,6 +84,7 @@ struct SpotifyArtists {
 
 #[derive(Debug, Deserialize)]
 struct SpotifyArtist {
+    id: String,
     images: Vec<SpotifyImage>,
 }
 
@@ -39,80 +95,355 @@ struct SpotifyImage {
     width: u32,
 }
 
+/// The response shape of Spotify's `GET /v1/artists?ids=...` batch lookup.
 #[derive(Debug, Deserialize)]
-struct SpotifyTokenResponse {
-    access_token: String,
+struct SpotifyArtistsBatchResponse {
+    #[serde(default)]
+    artists: Vec<Option<SpotifyArtist>>,
+}
+
+/// A `/search` match: the image if Spotify has one, plus the artist's Spotify id so a later
+/// `get_artist_images_batch` call can refresh the image via `/v1/artists` instead of searching
+/// by name again.
+struct SpotifyArtistMatch {
+    spotify_id: String,
+    image_url: Option<String>,
+    image_width: Option<u32>,
+}
+
+/// Result of resolving a single artist through the Spotify-then-fanart.tv chain, shared by
+/// `get_artist_image` and `get_artist_images_batch`'s per-artist fallback.
+struct ResolvedImage {
+    image_url: Option<String>,
+    image_width: Option<u32>,
+    spotify_artist_id: Option<String>,
+    source: &'static str,
+    /// Set when a source errored out after exhausting retries (rate-limited/unavailable)
+    /// rather than giving a genuine negative, so the caller knows not to cache a "none".
+    inconclusive: bool,
+}
+
+pub struct BatchArtistImage {
+    pub mb_id: String,
+    pub image_url: Option<String>,
 }
 
 pub async fn get_artist_image(
     pool: &PgPool,
     mb_id: &str,
     artist_name: &str,
-    spotify_client_id: &str,
-    spotify_client_secret: &str,
+    spotify_app_token: &crate::spotify::SpotifyAppToken,
     fanart_api_key: &str,
+    quality: ImageQuality,
 ) -> Result<Option<String>, anyhow::Error> {
     // Check cache first
-    if let Some(cached_path) = check_cache(pool, mb_id).await? {
+    if let Some(cached_path) = check_cache(pool, mb_id, quality).await? {
         tracing::debug!("using cached image for {}: {}", artist_name, cached_path);
         return Ok(Some(cached_path));
     }
 
     let client = Client::new();
+    let resolved = resolve_artist_image(
+        &client,
+        mb_id,
+        artist_name,
+        spotify_app_token,
+        fanart_api_key,
+        quality,
+    )
+    .await;
+
+    // Download and store the image locally
+    if let Some(url) = &resolved.image_url {
+        match download_and_store_image(&client, mb_id, url).await {
+            Ok(local_path) => {
+                cache_image(
+                    pool,
+                    mb_id,
+                    Some(&local_path),
+                    resolved.source,
+                    resolved.spotify_artist_id.as_deref(),
+                    resolved.image_width,
+                    quality,
+                )
+                .await?;
+                return Ok(Some(local_path));
+            }
+            Err(e) => {
+                tracing::warn!("failed to download image for {}: {}", mb_id, e);
+            }
+        }
+    }
+
+    if resolved.inconclusive {
+        // A source was rate-limited/unavailable rather than giving a genuine negative - don't
+        // poison the 30-day cache with "none", so the next lookup gets to try again.
+        return Ok(None);
+    }
+
+    // Cache the miss to avoid repeated API calls, but keep any Spotify id we did resolve so
+    // a later batch refresh doesn't have to search by name again.
+    cache_image(
+        pool,
+        mb_id,
+        None,
+        "none",
+        resolved.spotify_artist_id.as_deref(),
+        None,
+        quality,
+    )
+    .await?;
+    Ok(None)
+}
+
+/// Resolve one artist's image via Spotify search, falling back to fanart.tv - the shared
+/// resolution logic behind both `get_artist_image` and `get_artist_images_batch`'s per-artist
+/// fallback.
+async fn resolve_artist_image(
+    client: &Client,
+    mb_id: &str,
+    artist_name: &str,
+    spotify_app_token: &crate::spotify::SpotifyAppToken,
+    fanart_api_key: &str,
+    quality: ImageQuality,
+) -> ResolvedImage {
     let mut image_url: Option<String> = None;
+    let mut image_width: Option<u32> = None;
+    let mut spotify_artist_id: Option<String> = None;
     let mut source = "none";
+    let mut inconclusive = false;
 
     // Try Spotify first
-    if !spotify_client_id.is_empty() && !spotify_client_secret.is_empty() {
+    if spotify_app_token.is_configured() {
         tracing::debug!("trying spotify for artist: {}", artist_name);
-        match fetch_spotify_image(
-            &client,
-            artist_name,
-            spotify_client_id,
-            spotify_client_secret,
-        )
-        .await
-        {
-            Ok(Some(url)) => {
-                tracing::info!("found spotify image for {}", artist_name);
-                image_url = Some(url);
-                source = "spotify";
+        match fetch_spotify_image(client, artist_name, spotify_app_token, quality).await {
+            Ok(Some(found)) => {
+                spotify_artist_id = Some(found.spotify_id);
+                if let Some(url) = found.image_url {
+                    tracing::info!("found spotify image for {}", artist_name);
+                    image_url = Some(url);
+                    image_width = found.image_width;
+                    source = "spotify";
+                } else {
+                    tracing::debug!("no spotify image found for {}", artist_name);
+                }
             }
             Ok(None) => {
-                tracing::debug!("no spotify image found for {}", artist_name);
+                tracing::debug!("no spotify match found for {}", artist_name);
             }
             Err(e) => {
                 tracing::warn!("spotify fetch error for {}: {}", artist_name, e);
+                inconclusive = true;
             }
         }
     } else {
         tracing::debug!("spotify credentials not set, skipping");
     }
 
-    // Fallback to fanart.tv
+    // Fallback to fanart.tv - no size metadata, so `image_width` stays `None`.
     if image_url.is_none() && !fanart_api_key.is_empty() {
-        if let Ok(Some(url)) = fetch_fanart_image(&client, mb_id, fanart_api_key).await {
-            image_url = Some(url);
-            source = "fanart";
+        match fetch_fanart_image(client, mb_id, fanart_api_key).await {
+            Ok(Some(url)) => {
+                image_url = Some(url);
+                source = "fanart";
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!("fanart fetch error for {}: {}", mb_id, e);
+                inconclusive = true;
+            }
         }
     }
 
-    // Download and store the image locally
-    if let Some(url) = image_url {
-        match download_and_store_image(&client, mb_id, &url).await {
-            Ok(local_path) => {
-                cache_image(pool, mb_id, Some(&local_path), source).await?;
-                return Ok(Some(local_path));
+    ResolvedImage {
+        image_url,
+        image_width,
+        spotify_artist_id,
+        source,
+        inconclusive,
+    }
+}
+
+/// Resolve artist images for many artists at once, for warming the whole cache table from a
+/// scrobble import instead of paying one `/search` per artist. Artists with a fresh cache
+/// entry are skipped entirely; artists with a previously-resolved Spotify id are refreshed via
+/// the `/v1/artists` batch endpoint (up to `SPOTIFY_ARTISTS_BATCH_SIZE` per request); everyone
+/// else still goes through the per-artist Spotify-then-fanart.tv chain to discover one.
+pub async fn get_artist_images_batch(
+    pool: &PgPool,
+    artists: &[(String, String)],
+    spotify_app_token: &crate::spotify::SpotifyAppToken,
+    fanart_api_key: &str,
+    quality: ImageQuality,
+) -> Result<Vec<BatchArtistImage>, anyhow::Error> {
+    if artists.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mb_ids: Vec<String> = artists.iter().map(|(mb_id, _)| mb_id.clone()).collect();
+    let cached = fetch_cached_entries(pool, &mb_ids).await?;
+    let cache_cutoff =
+        SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64 - CACHE_DURATION_SECS;
+
+    let mut results = Vec::with_capacity(artists.len());
+    let mut with_known_id: Vec<(&(String, String), String)> = Vec::new();
+    let mut without_known_id: Vec<&(String, String)> = Vec::new();
+
+    for entry in artists {
+        match cached.get(&entry.0) {
+            Some(hit) if hit.cached_at >= cache_cutoff && width_satisfies(quality, hit.image_quality.as_deref()) => {
+                results.push(BatchArtistImage {
+                    mb_id: entry.0.clone(),
+                    image_url: hit.image_url.clone(),
+                });
             }
-            Err(e) => {
-                tracing::warn!("failed to download image for {}: {}", mb_id, e);
+            Some(hit) if hit.spotify_artist_id.is_some() => {
+                with_known_id.push((entry, hit.spotify_artist_id.clone().unwrap()));
             }
+            _ => without_known_id.push(entry),
         }
     }
 
-    // Cache the miss to avoid repeated API calls
-    cache_image(pool, mb_id, None, "none").await?;
-    Ok(None)
+    let client = Client::new();
+
+    if spotify_app_token.is_configured() {
+        for chunk in with_known_id.chunks(SPOTIFY_ARTISTS_BATCH_SIZE) {
+            let ids: Vec<&str> = chunk.iter().map(|(_, id)| id.as_str()).collect();
+            match fetch_spotify_artists_by_id(&client, spotify_app_token, &ids, quality).await {
+                Ok(images_by_id) => {
+                    for (entry, spotify_id) in chunk {
+                        let found = images_by_id.get(spotify_id).cloned().flatten();
+                        let (image_url, image_width) = match found {
+                            Some((url, width)) => (Some(url), Some(width)),
+                            None => (None, None),
+                        };
+                        let source = if image_url.is_some() { "spotify" } else { "none" };
+                        cache_image(
+                            pool,
+                            &entry.0,
+                            image_url.as_deref(),
+                            source,
+                            Some(spotify_id),
+                            image_width,
+                            quality,
+                        )
+                        .await?;
+                        results.push(BatchArtistImage {
+                            mb_id: entry.0.clone(),
+                            image_url,
+                        });
+                    }
+                }
+                Err(e) => {
+                    // Don't silently drop these artists if the batch itself failed (e.g. still
+                    // rate-limited after retries) - fall back to resolving them individually.
+                    tracing::warn!("spotify batch artist lookup failed: {}", e);
+                    without_known_id.extend(chunk.iter().map(|(entry, _)| *entry));
+                }
+            }
+        }
+    } else {
+        without_known_id.extend(with_known_id.iter().map(|(entry, _)| *entry));
+    }
+
+    for (mb_id, artist_name) in without_known_id {
+        let resolved = resolve_artist_image(
+            &client,
+            mb_id,
+            artist_name,
+            spotify_app_token,
+            fanart_api_key,
+            quality,
+        )
+        .await;
+
+        let local_path = match &resolved.image_url {
+            Some(url) => match download_and_store_image(&client, mb_id, url).await {
+                Ok(path) => Some(path),
+                Err(e) => {
+                    tracing::warn!("failed to download image for {}: {}", mb_id, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        if !resolved.inconclusive {
+            let source = if local_path.is_some() { resolved.source } else { "none" };
+            cache_image(
+                pool,
+                mb_id,
+                local_path.as_deref(),
+                source,
+                resolved.spotify_artist_id.as_deref(),
+                resolved.image_width,
+                quality,
+            )
+            .await?;
+        }
+
+        results.push(BatchArtistImage {
+            mb_id: mb_id.clone(),
+            image_url: local_path,
+        });
+    }
+
+    Ok(results)
+}
+
+/// Images wider or taller than this are downscaled (and re-encoded to PNG) before being
+/// written to disk, so a source returning something unexpectedly huge doesn't balloon
+/// `./images` or the bandwidth for every page that embeds it.
+const MAX_IMAGE_DIMENSION: u32 = 1280;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SniffedImageFormat {
+    Jpeg,
+    Png,
+    WebP,
+    Gif,
+}
+
+impl SniffedImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            SniffedImageFormat::Jpeg => "jpg",
+            SniffedImageFormat::Png => "png",
+            SniffedImageFormat::WebP => "webp",
+            SniffedImageFormat::Gif => "gif",
+        }
+    }
+}
+
+fn sniff_magic_bytes(bytes: &[u8]) -> Option<SniffedImageFormat> {
+    if bytes.starts_with(&[0xFF, 0xD8]) {
+        Some(SniffedImageFormat::Jpeg)
+    } else if bytes.starts_with(&[0x89, 0x50, 0x4E, 0x47]) {
+        Some(SniffedImageFormat::Png)
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some(SniffedImageFormat::WebP)
+    } else if bytes.starts_with(b"GIF8") {
+        Some(SniffedImageFormat::Gif)
+    } else {
+        None
+    }
+}
+
+fn format_from_content_type(content_type: &str) -> Option<SniffedImageFormat> {
+    match content_type.split(';').next()?.trim() {
+        "image/jpeg" | "image/jpg" => Some(SniffedImageFormat::Jpeg),
+        "image/png" => Some(SniffedImageFormat::Png),
+        "image/webp" => Some(SniffedImageFormat::WebP),
+        "image/gif" => Some(SniffedImageFormat::Gif),
+        _ => None,
+    }
+}
+
+/// Resolve the true image format from the body's leading magic bytes, falling back to the
+/// `Content-Type` header when sniffing is inconclusive. Magic bytes win on disagreement - a
+/// server can send a wrong/missing `Content-Type` but can't fake what the bytes decode as.
+fn detect_image_format(content_type: Option<&str>, bytes: &[u8]) -> Option<SniffedImageFormat> {
+    sniff_magic_bytes(bytes).or_else(|| content_type.and_then(format_from_content_type))
 }
 
 async fn download_and_store_image(
@@ -126,28 +457,48 @@ async fn download_and_store_image(
 
     // Download the image
     let response = client.get(image_url).send().await?;
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
     let bytes = response.bytes().await?;
 
-    // Determine file extension from URL or content-type
-    let extension = image_url
-        .split('.')
-        .last()
-        .and_then(|ext| {
-            let ext = ext.split('?').next()?;
-            if matches!(ext, "jpg" | "jpeg" | "png" | "webp") {
-                Some(ext)
-            } else {
-                None
+    let format = detect_image_format(content_type.as_deref(), &bytes).ok_or_else(|| {
+        anyhow::anyhow!(
+            "response from {} is not a recognized image (content-type: {:?})",
+            image_url,
+            content_type
+        )
+    })?;
+
+    let (output_bytes, extension): (std::borrow::Cow<'_, [u8]>, &str) =
+        match image::load_from_memory(&bytes) {
+            Ok(decoded)
+                if decoded.width() > MAX_IMAGE_DIMENSION || decoded.height() > MAX_IMAGE_DIMENSION =>
+            {
+                let resized = decoded.resize(
+                    MAX_IMAGE_DIMENSION,
+                    MAX_IMAGE_DIMENSION,
+                    image::imageops::FilterType::Lanczos3,
+                );
+                let mut buffer = Vec::new();
+                resized
+                    .write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+                    .context("failed to re-encode oversized image")?;
+                (buffer.into(), "png")
             }
-        })
-        .unwrap_or("jpg");
+            // Already a reasonable size - write the original bytes as downloaded, rather than
+            // paying a decode/re-encode round trip for every image.
+            _ => (bytes.to_vec().into(), format.extension()),
+        };
 
     // Save to disk
     let filename = format!("{}.{}", mb_id, extension);
     let filepath = images_dir.join(&filename);
 
     let mut file = fs::File::create(&filepath).await?;
-    file.write_all(&bytes).await?;
+    file.write_all(&output_bytes).await?;
 
     Ok(format!("/images/{}", filename))
 }
@@ -155,51 +506,160 @@ async fn download_and_store_image(
 async fn fetch_spotify_image(
     client: &Client,
     artist_name: &str,
-    client_id: &str,
-    client_secret: &str,
-) -> Result<Option<String>, anyhow::Error> {
-    // Get access token
-    let auth = format!("{}:{}", client_id, client_secret);
-    let encoded =
-        base64::Engine::encode(&base64::engine::general_purpose::STANDARD, auth.as_bytes());
-
-    let token_response = client
-        .post("https://accounts.spotify.com/api/token")
-        .header("Authorization", format!("Basic {}", encoded))
-        .form(&[("grant_type", "client_credentials")])
-        .send()
+    spotify_app_token: &crate::spotify::SpotifyAppToken,
+    quality: ImageQuality,
+) -> Result<Option<SpotifyArtistMatch>, anyhow::Error> {
+    let mut retried_unauthorized = false;
+
+    loop {
+        let access_token = spotify_app_token.get().await?;
+
+        // Search for artist, retrying 429/5xx/transport errors with backoff before giving up.
+        let search_response = crate::http_retry::send_with_retry("fanart_spotify_search", || {
+            client
+                .get("https://api.spotify.com/v1/search")
+                .header("Authorization", format!("Bearer {}", access_token))
+                .query(&[("q", artist_name), ("type", "artist"), ("limit", "1")])
+                .send()
+        })
         .await?;
 
-    if !token_response.status().is_success() {
-        return Ok(None);
+        let search_status = search_response.status();
+
+        if search_status == reqwest::StatusCode::UNAUTHORIZED && !retried_unauthorized {
+            tracing::debug!("spotify token rejected, refreshing and retrying once");
+            spotify_app_token.invalidate().await;
+            retried_unauthorized = true;
+            continue;
+        }
+
+        if search_status == reqwest::StatusCode::TOO_MANY_REQUESTS || search_status.is_server_error()
+        {
+            let body = search_response.text().await.unwrap_or_default();
+            crate::diagnostics::report_failure(
+                "fanart_spotify_search",
+                "https://api.spotify.com/v1/search",
+                Some(search_status),
+                &body,
+            )
+            .await;
+            anyhow::bail!(
+                "spotify search still rate-limited/unavailable after retries ({})",
+                search_status
+            );
+        }
+
+        if !search_status.is_success() {
+            let body = search_response.text().await.unwrap_or_default();
+            crate::diagnostics::report_failure(
+                "fanart_spotify_search",
+                "https://api.spotify.com/v1/search",
+                Some(search_status),
+                &body,
+            )
+            .await;
+            return Ok(None);
+        }
+
+        let search_body = search_response.text().await?;
+        let search: SpotifySearchResponse = match serde_json::from_str(&search_body) {
+            Ok(search) => search,
+            Err(_) => {
+                crate::diagnostics::report_failure(
+                    "fanart_spotify_search",
+                    "https://api.spotify.com/v1/search",
+                    Some(search_status),
+                    &search_body,
+                )
+                .await;
+                return Ok(None);
+            }
+        };
+
+        let matched = search.artists.items.into_iter().next().map(|artist| {
+            let picked = pick_spotify_image(&artist.images, quality);
+            SpotifyArtistMatch {
+                spotify_id: artist.id,
+                image_url: picked.as_ref().map(|(url, _)| url.clone()),
+                image_width: picked.map(|(_, width)| width),
+            }
+        });
+
+        return Ok(matched);
     }
+}
 
-    let token: SpotifyTokenResponse = token_response.json().await?;
+/// Pick the image matching `quality` out of Spotify's size options for one artist, returning
+/// its URL and width.
+fn pick_spotify_image(images: &[SpotifyImage], quality: ImageQuality) -> Option<(String, u32)> {
+    match quality.target_width() {
+        None => images.iter().max_by_key(|img| img.width),
+        Some(target) => images
+            .iter()
+            .min_by_key(|img| (img.width as i64 - target as i64).abs()),
+    }
+    .map(|img| (img.url.clone(), img.width))
+}
 
-    // Search for artist
-    let search_response = client
-        .get("https://api.spotify.com/v1/search")
-        .header("Authorization", format!("Bearer {}", token.access_token))
-        .query(&[("q", artist_name), ("type", "artist"), ("limit", "1")])
-        .send()
+/// Refresh images for artists whose Spotify id is already known, via `GET /v1/artists?ids=...`.
+/// `ids` must not exceed `SPOTIFY_ARTISTS_BATCH_SIZE`. Returns the image URL and width (`None`
+/// if Spotify has no image, or the artist id was dropped/unknown) keyed by Spotify artist id.
+async fn fetch_spotify_artists_by_id(
+    client: &Client,
+    spotify_app_token: &crate::spotify::SpotifyAppToken,
+    ids: &[&str],
+    quality: ImageQuality,
+) -> Result<HashMap<String, Option<(String, u32)>>, anyhow::Error> {
+    let ids_param = ids.join(",");
+    let mut retried_unauthorized = false;
+
+    loop {
+        let access_token = spotify_app_token.get().await?;
+
+        let response = crate::http_retry::send_with_retry("fanart_spotify_artists_batch", || {
+            client
+                .get("https://api.spotify.com/v1/artists")
+                .header("Authorization", format!("Bearer {}", access_token))
+                .query(&[("ids", ids_param.as_str())])
+                .send()
+        })
         .await?;
 
-    if !search_response.status().is_success() {
-        return Ok(None);
-    }
+        let status = response.status();
 
-    let search: SpotifySearchResponse = search_response.json().await?;
+        if status == reqwest::StatusCode::UNAUTHORIZED && !retried_unauthorized {
+            tracing::debug!("spotify token rejected, refreshing and retrying once");
+            spotify_app_token.invalidate().await;
+            retried_unauthorized = true;
+            continue;
+        }
 
-    // Get the largest image
-    let image_url = search.artists.items.first().and_then(|artist| {
-        artist
-            .images
-            .iter()
-            .max_by_key(|img| img.width)
-            .map(|img| img.url.clone())
-    });
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            crate::diagnostics::report_failure(
+                "fanart_spotify_artists_batch",
+                "https://api.spotify.com/v1/artists",
+                Some(status),
+                &body,
+            )
+            .await;
+            anyhow::bail!("spotify batch artist lookup returned status {}", status);
+        }
 
-    Ok(image_url)
+        let body = response.text().await?;
+        let parsed: SpotifyArtistsBatchResponse =
+            serde_json::from_str(&body).context("failed to parse spotify batch artist response")?;
+
+        return Ok(parsed
+            .artists
+            .into_iter()
+            .flatten()
+            .map(|artist| {
+                let picked = pick_spotify_image(&artist.images, quality);
+                (artist.id, picked)
+            })
+            .collect());
+    }
 }
 
 async fn fetch_fanart_image(
@@ -212,22 +672,45 @@ async fn fetch_fanart_image(
         mb_id, api_key
     );
 
-    let response = client.get(&url).send().await?;
+    let response =
+        crate::http_retry::send_with_retry("fanart_artist_lookup", || client.get(&url).send())
+            .await?;
+    let status = response.status();
 
-    if !response.status().is_success() {
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+        let body = response.text().await.unwrap_or_default();
+        crate::diagnostics::report_failure("fanart_artist_lookup", &url, Some(status), &body).await;
+        anyhow::bail!("fanart.tv still rate-limited/unavailable after retries ({})", status);
+    }
+
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        crate::diagnostics::report_failure("fanart_artist_lookup", &url, Some(status), &body).await;
         return Ok(None);
     }
 
-    let fanart: FanartResponse = response.json().await?;
+    let body = response.text().await?;
+    let fanart: FanartResponse = match serde_json::from_str(&body) {
+        Ok(fanart) => fanart,
+        Err(_) => {
+            crate::diagnostics::report_failure("fanart_artist_lookup", &url, Some(status), &body)
+                .await;
+            return Ok(None);
+        }
+    };
     let image_url = fanart.artistthumb.first().map(|t| t.url.clone());
 
     Ok(image_url)
 }
 
-async fn check_cache(pool: &PgPool, mb_id: &str) -> Result<Option<String>, anyhow::Error> {
+async fn check_cache(
+    pool: &PgPool,
+    mb_id: &str,
+    quality: ImageQuality,
+) -> Result<Option<String>, anyhow::Error> {
     let result = sqlx::query!(
         r#"
-        SELECT image_url, cached_at
+        SELECT image_url, cached_at, image_quality
         FROM artist_images
         WHERE mb_id = $1
         "#,
@@ -237,11 +720,11 @@ async fn check_cache(pool: &PgPool, mb_id: &str) -> Result<Option<String>, anyho
     .await?;
 
     if let Some(record) = result {
-        // Cache for 30 days
-        let cache_duration = 30 * 24 * 60 * 60; // 30 days in seconds
         let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
 
-        if now - record.cached_at < cache_duration {
+        if now - record.cached_at < CACHE_DURATION_SECS
+            && width_satisfies(quality, record.image_quality.as_deref())
+        {
             return Ok(record.image_url);
         }
     }
@@ -249,25 +732,77 @@ async fn check_cache(pool: &PgPool, mb_id: &str) -> Result<Option<String>, anyho
     Ok(None)
 }
 
+struct CachedArtistEntry {
+    image_url: Option<String>,
+    cached_at: i64,
+    spotify_artist_id: Option<String>,
+    image_quality: Option<String>,
+}
+
+/// Bulk variant of `check_cache` for `get_artist_images_batch` - one query for every artist in
+/// the batch instead of one round trip per artist. Returns every matching row regardless of
+/// freshness; callers compare `cached_at` themselves since a fresh image and a reusable-but-
+/// stale Spotify id are judged against different lifetimes.
+async fn fetch_cached_entries(
+    pool: &PgPool,
+    mb_ids: &[String],
+) -> Result<HashMap<String, CachedArtistEntry>, anyhow::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT mb_id, image_url, cached_at, spotify_artist_id, image_quality
+        FROM artist_images
+        WHERE mb_id = ANY($1)
+        "#,
+    )
+    .bind(mb_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let mb_id: String = row.get("mb_id");
+            let entry = CachedArtistEntry {
+                image_url: row.get("image_url"),
+                cached_at: row.get("cached_at"),
+                spotify_artist_id: row.get("spotify_artist_id"),
+                image_quality: row.get("image_quality"),
+            };
+            (mb_id, entry)
+        })
+        .collect())
+}
+
 async fn cache_image(
     pool: &PgPool,
     mb_id: &str,
     image_url: Option<&str>,
     source: &str,
+    spotify_artist_id: Option<&str>,
+    image_width: Option<u32>,
+    quality: ImageQuality,
 ) -> Result<(), anyhow::Error> {
     let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let image_width = image_width.map(|w| w as i32);
+    let image_quality = quality.to_string();
 
     sqlx::query!(
         r#"
-        INSERT INTO artist_images (mb_id, image_url, image_source, cached_at)
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO artist_images (mb_id, image_url, image_source, cached_at, spotify_artist_id, image_width, image_quality)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
         ON CONFLICT (mb_id)
-        DO UPDATE SET image_url = $2, image_source = $3, cached_at = $4
+        DO UPDATE SET image_url = $2, image_source = $3, cached_at = $4,
+            spotify_artist_id = COALESCE($5, artist_images.spotify_artist_id),
+            image_width = $6,
+            image_quality = $7
         "#,
         mb_id,
         image_url,
         source,
-        now
+        now,
+        spotify_artist_id,
+        image_width,
+        image_quality
     )
     .execute(pool)
     .await?;
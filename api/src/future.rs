@@ -0,0 +1,118 @@
+//! Poll-timer instrumentation for DB-bound futures.
+//!
+//! Large imports occasionally stall inside a materialized-view refresh or a big batch
+//! insert with no visibility into which statement is slow. `WithPollTimer` wraps any
+//! future, times how long it takes to resolve, and emits a `tracing::warn!` (with
+//! structured `operation`/`elapsed_ms` fields so they can be scraped as metrics) when it
+//! exceeds a threshold, or a `tracing::debug!` otherwise.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+/// Default threshold above which a named operation logs a warning instead of a debug line.
+pub const DEFAULT_SLOW_THRESHOLD: Duration = Duration::from_secs(2);
+
+/// How often a still-pending `with_stall_watchdog` future re-warns.
+pub const STALL_REWARN_INTERVAL: Duration = Duration::from_secs(30);
+
+pub trait WithPollTimer: Future + Sized + Send
+where
+    Self::Output: Send,
+{
+    /// Time this future, warning if it takes longer than `DEFAULT_SLOW_THRESHOLD`.
+    fn with_poll_timer<'a>(
+        self,
+        operation: &'static str,
+    ) -> Pin<Box<dyn Future<Output = Self::Output> + Send + 'a>>
+    where
+        Self: 'a,
+    {
+        self.with_poll_timer_threshold(operation, DEFAULT_SLOW_THRESHOLD)
+    }
+
+    /// Time this future, warning if it takes longer than `threshold`.
+    fn with_poll_timer_threshold<'a>(
+        self,
+        operation: &'static str,
+        threshold: Duration,
+    ) -> Pin<Box<dyn Future<Output = Self::Output> + Send + 'a>>
+    where
+        Self: 'a,
+    {
+        Box::pin(async move {
+            let started = Instant::now();
+            let result = self.await;
+            let elapsed = started.elapsed();
+
+            if elapsed > threshold {
+                tracing::warn!(
+                    operation,
+                    elapsed_ms = elapsed.as_millis() as u64,
+                    threshold_ms = threshold.as_millis() as u64,
+                    "db operation exceeded poll-timer threshold"
+                );
+            } else {
+                tracing::debug!(
+                    operation,
+                    elapsed_ms = elapsed.as_millis() as u64,
+                    "db operation completed"
+                );
+            }
+
+            result
+        })
+    }
+
+    /// Wrap a per-item future from a bulk `buffer_unordered` pipeline so a single stuck item
+    /// (a slow AT Protocol fetch, a wedged DB call) doesn't just silently occupy a concurrency
+    /// slot. Logs a warning naming `label` (e.g. the DID being processed) and the elapsed time
+    /// once the future has been pending longer than `warn_after`, then keeps re-warning every
+    /// `STALL_REWARN_INTERVAL` for as long as it's still pending.
+    fn with_stall_watchdog<'a>(
+        self,
+        label: String,
+        warn_after: Duration,
+    ) -> Pin<Box<dyn Future<Output = Self::Output> + Send + 'a>>
+    where
+        Self: 'a,
+    {
+        Box::pin(async move {
+            tokio::pin!(self);
+            let started = Instant::now();
+            let mut period = warn_after;
+            let mut stalled = false;
+
+            loop {
+                match tokio::time::timeout(period, &mut self).await {
+                    Ok(result) => {
+                        if stalled {
+                            tracing::info!(
+                                label = %label,
+                                elapsed_ms = started.elapsed().as_millis() as u64,
+                                "stalled operation finally completed"
+                            );
+                        }
+                        return result;
+                    }
+                    Err(_) => {
+                        stalled = true;
+                        tracing::warn!(
+                            label = %label,
+                            elapsed_ms = started.elapsed().as_millis() as u64,
+                            "operation still in flight, possible stall"
+                        );
+                        period = STALL_REWARN_INTERVAL;
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl<F> WithPollTimer for F
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+}
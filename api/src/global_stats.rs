@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgPool;
 use sqlx::Row;
@@ -40,51 +41,476 @@ pub struct TopUser {
     pub listening_minutes: f64,
 }
 
-pub async fn calculate_global_stats(pool: &PgPool, year: u32) -> Result<GlobalStats> {
-    // Get basic stats
-    let basic_stats = sqlx::query(
+/// A single page of a ranked result set, alongside enough information to render pagination
+/// controls without a second round-trip just to find out how many pages exist.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PagedResult<T> {
+    pub results: Vec<T>,
+    pub total_pages: i64,
+    pub current_page: u32,
+}
+
+/// `calculate_global_stats`'s `top_artists`/`top_tracks`/`top_users` are hard-capped at the
+/// first 100 rows, which is fine for a wrapped summary but can't back a leaderboard that pages
+/// through the full ranked set. These paginated variants run the same grouped query with an
+/// `OFFSET`/`FETCH NEXT` window plus a `ceil(count(*) / per_page)` companion query for the page
+/// count, so a caller can request any page instead of only the top slice.
+pub async fn global_top_artists(
+    pool: &PgPool,
+    year: u32,
+    page: u32,
+    per_page: u32,
+    artist_scope: ArtistCreditScope,
+) -> Result<PagedResult<GlobalArtist>> {
+    let offset = (page.saturating_sub(1)) as i64 * per_page as i64;
+
+    let rows = match artist_scope {
+        ArtistCreditScope::PrimaryOnly => {
+            sqlx::query(
+                r#"
+                SELECT
+                    (artists->0)->>'artistName' as artist_name,
+                    (artists->0)->>'artistMbId' as mb_id,
+                    COUNT(*) as play_count,
+                    COUNT(DISTINCT user_did) as user_count
+                FROM user_plays
+                WHERE EXTRACT(YEAR FROM played_at) = $1
+                  AND jsonb_array_length(artists) > 0
+                GROUP BY (artists->0)->>'artistName', (artists->0)->>'artistMbId'
+                ORDER BY play_count DESC
+                OFFSET $2 FETCH NEXT $3 ROWS ONLY
+                "#,
+            )
+            .bind(year as i32)
+            .bind(offset)
+            .bind(per_page as i64)
+            .fetch_all(pool)
+            .await?
+        }
+        ArtistCreditScope::AllCredited => {
+            sqlx::query(
+                r#"
+                SELECT
+                    elem->>'artistName' as artist_name,
+                    elem->>'artistMbId' as mb_id,
+                    COUNT(*) as play_count,
+                    COUNT(DISTINCT user_did) as user_count
+                FROM user_plays, jsonb_array_elements(artists) elem
+                WHERE EXTRACT(YEAR FROM played_at) = $1
+                GROUP BY elem->>'artistName', elem->>'artistMbId'
+                ORDER BY play_count DESC
+                OFFSET $2 FETCH NEXT $3 ROWS ONLY
+                "#,
+            )
+            .bind(year as i32)
+            .bind(offset)
+            .bind(per_page as i64)
+            .fetch_all(pool)
+            .await?
+        }
+    };
+
+    let results: Vec<GlobalArtist> = rows
+        .iter()
+        .map(|row| GlobalArtist {
+            name: row.get("artist_name"),
+            play_count: row.get("play_count"),
+            user_count: row.get("user_count"),
+            mb_id: row.get("mb_id"),
+        })
+        .collect();
+
+    let groups_query = match artist_scope {
+        ArtistCreditScope::PrimaryOnly => {
+            r#"
+            SELECT CEIL(COUNT(*)::float8 / $2) as total_pages
+            FROM (
+                SELECT 1
+                FROM user_plays
+                WHERE EXTRACT(YEAR FROM played_at) = $1
+                  AND jsonb_array_length(artists) > 0
+                GROUP BY (artists->0)->>'artistName', (artists->0)->>'artistMbId'
+            ) groups
+            "#
+        }
+        ArtistCreditScope::AllCredited => {
+            r#"
+            SELECT CEIL(COUNT(*)::float8 / $2) as total_pages
+            FROM (
+                SELECT 1
+                FROM user_plays, jsonb_array_elements(artists) elem
+                WHERE EXTRACT(YEAR FROM played_at) = $1
+                GROUP BY elem->>'artistName', elem->>'artistMbId'
+            ) groups
+            "#
+        }
+    };
+
+    let total_pages = sqlx::query(groups_query)
+        .bind(year as i32)
+        .bind(per_page as i64)
+        .fetch_one(pool)
+        .await?
+        .get::<f64, _>("total_pages") as i64;
+
+    Ok(PagedResult {
+        results,
+        total_pages,
+        current_page: page,
+    })
+}
+
+/// Paginated counterpart to `calculate_global_stats`'s `top_tracks` query; see
+/// `global_top_artists` for the pagination approach.
+pub async fn global_top_tracks(
+    pool: &PgPool,
+    year: u32,
+    page: u32,
+    per_page: u32,
+) -> Result<PagedResult<GlobalTrack>> {
+    let offset = (page.saturating_sub(1)) as i64 * per_page as i64;
+
+    let rows = sqlx::query(
         r#"
         SELECT
-            COUNT(*) as total_plays,
-            SUM(COALESCE(duration_ms, 210000)) as total_duration_ms,
-            COUNT(DISTINCT user_did) as unique_users,
-            COUNT(DISTINCT (artists->0)->>'artistName') as unique_artists,
-            COUNT(DISTINCT track_name) as unique_tracks
+            track_name,
+            (artists->0)->>'artistName' as artist_name,
+            recording_mb_id,
+            COUNT(*) as play_count,
+            COUNT(DISTINCT user_did) as user_count
         FROM user_plays
         WHERE EXTRACT(YEAR FROM played_at) = $1
+          AND jsonb_array_length(artists) > 0
+        GROUP BY track_name, (artists->0)->>'artistName', recording_mb_id
+        ORDER BY play_count DESC
+        OFFSET $2 FETCH NEXT $3 ROWS ONLY
         "#,
     )
     .bind(year as i32)
+    .bind(offset)
+    .bind(per_page as i64)
+    .fetch_all(pool)
+    .await?;
+
+    let results: Vec<GlobalTrack> = rows
+        .iter()
+        .map(|row| GlobalTrack {
+            track_name: row.get("track_name"),
+            artist_name: row.get("artist_name"),
+            play_count: row.get("play_count"),
+            user_count: row.get("user_count"),
+            recording_mb_id: row.get("recording_mb_id"),
+        })
+        .collect();
+
+    let total_pages = sqlx::query(
+        r#"
+        SELECT CEIL(COUNT(*)::float8 / $2) as total_pages
+        FROM (
+            SELECT 1
+            FROM user_plays
+            WHERE EXTRACT(YEAR FROM played_at) = $1
+              AND jsonb_array_length(artists) > 0
+            GROUP BY track_name, (artists->0)->>'artistName', recording_mb_id
+        ) groups
+        "#,
+    )
+    .bind(year as i32)
+    .bind(per_page as i64)
     .fetch_one(pool)
+    .await?
+    .get::<f64, _>("total_pages") as i64;
+
+    Ok(PagedResult {
+        results,
+        total_pages,
+        current_page: page,
+    })
+}
+
+/// Paginated counterpart to `calculate_global_stats`'s `top_users` query; see
+/// `global_top_artists` for the pagination approach.
+pub async fn global_top_users(
+    pool: &PgPool,
+    year: u32,
+    page: u32,
+    per_page: u32,
+) -> Result<PagedResult<TopUser>> {
+    let offset = (page.saturating_sub(1)) as i64 * per_page as i64;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            user_did,
+            COUNT(*) as play_count,
+            SUM(COALESCE(duration_ms, 210000)) as total_duration_ms
+        FROM user_plays
+        WHERE EXTRACT(YEAR FROM played_at) = $1
+        GROUP BY user_did
+        ORDER BY play_count DESC
+        OFFSET $2 FETCH NEXT $3 ROWS ONLY
+        "#,
+    )
+    .bind(year as i32)
+    .bind(offset)
+    .bind(per_page as i64)
+    .fetch_all(pool)
     .await?;
 
-    let total_plays: i64 = basic_stats.get("total_plays");
-    let total_duration_ms: Option<i64> = basic_stats.get("total_duration_ms");
-    let total_minutes = total_duration_ms.unwrap_or(0) as f64 / (1000.0 * 60.0);
-    let unique_users: i64 = basic_stats.get("unique_users");
-    let unique_artists: i64 = basic_stats.get("unique_artists");
-    let unique_tracks: i64 = basic_stats.get("unique_tracks");
+    let results: Vec<TopUser> = rows
+        .iter()
+        .map(|row| {
+            let total_duration_ms: Option<i64> = row.get("total_duration_ms");
+            TopUser {
+                user_did: row.get("user_did"),
+                play_count: row.get("play_count"),
+                listening_minutes: total_duration_ms.unwrap_or(0) as f64 / (1000.0 * 60.0),
+            }
+        })
+        .collect();
+
+    let total_pages = sqlx::query(
+        r#"
+        SELECT CEIL(COUNT(*)::float8 / $2) as total_pages
+        FROM (
+            SELECT 1
+            FROM user_plays
+            WHERE EXTRACT(YEAR FROM played_at) = $1
+            GROUP BY user_did
+        ) groups
+        "#,
+    )
+    .bind(year as i32)
+    .bind(per_page as i64)
+    .fetch_one(pool)
+    .await?
+    .get::<f64, _>("total_pages") as i64;
+
+    Ok(PagedResult {
+        results,
+        total_pages,
+        current_page: page,
+    })
+}
+
+/// A `GlobalArtist` ranked by how closely its name matched a fuzzy search query.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScoredArtist {
+    pub artist: GlobalArtist,
+    pub similarity: f32,
+}
+
+/// A `GlobalTrack` ranked by how closely its name matched a fuzzy search query.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScoredTrack {
+    pub track: GlobalTrack,
+    pub similarity: f32,
+}
 
-    // Get top artists
-    let top_artists_rows = sqlx::query(
+/// Fuzzy artist lookup via `pg_trgm`, so a misspelled or accent-dropped query still finds a
+/// match instead of requiring an exact string against the jsonb-derived artist name. `%`
+/// filters to names within Postgres's configured trigram similarity threshold before ranking,
+/// so this stays index-backed (see the `idx_user_plays_artist_name_trgm` GIN index) rather
+/// than scanning every row.
+pub async fn search_artists(
+    pool: &PgPool,
+    query: &str,
+    year: u32,
+    limit: u32,
+) -> Result<Vec<ScoredArtist>> {
+    let rows = sqlx::query(
         r#"
         SELECT
             (artists->0)->>'artistName' as artist_name,
             (artists->0)->>'artistMbId' as mb_id,
             COUNT(*) as play_count,
-            COUNT(DISTINCT user_did) as user_count
+            COUNT(DISTINCT user_did) as user_count,
+            similarity((artists->0)->>'artistName', $1) as similarity
         FROM user_plays
-        WHERE EXTRACT(YEAR FROM played_at) = $1
+        WHERE EXTRACT(YEAR FROM played_at) = $2
           AND jsonb_array_length(artists) > 0
+          AND (artists->0)->>'artistName' % $1
         GROUP BY (artists->0)->>'artistName', (artists->0)->>'artistMbId'
-        ORDER BY play_count DESC
-        LIMIT 100
+        ORDER BY similarity DESC
+        LIMIT $3
         "#,
     )
+    .bind(query)
     .bind(year as i32)
+    .bind(limit as i64)
     .fetch_all(pool)
     .await?;
 
+    Ok(rows
+        .iter()
+        .map(|row| ScoredArtist {
+            artist: GlobalArtist {
+                name: row.get("artist_name"),
+                play_count: row.get("play_count"),
+                user_count: row.get("user_count"),
+                mb_id: row.get("mb_id"),
+            },
+            similarity: row.get("similarity"),
+        })
+        .collect())
+}
+
+/// Fuzzy track lookup via `pg_trgm`; see `search_artists` for the matching approach.
+pub async fn search_tracks(
+    pool: &PgPool,
+    query: &str,
+    year: u32,
+    limit: u32,
+) -> Result<Vec<ScoredTrack>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            track_name,
+            (artists->0)->>'artistName' as artist_name,
+            recording_mb_id,
+            COUNT(*) as play_count,
+            COUNT(DISTINCT user_did) as user_count,
+            similarity(track_name, $1) as similarity
+        FROM user_plays
+        WHERE EXTRACT(YEAR FROM played_at) = $2
+          AND jsonb_array_length(artists) > 0
+          AND track_name % $1
+        GROUP BY track_name, (artists->0)->>'artistName', recording_mb_id
+        ORDER BY similarity DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(query)
+    .bind(year as i32)
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| ScoredTrack {
+            track: GlobalTrack {
+                track_name: row.get("track_name"),
+                artist_name: row.get("artist_name"),
+                play_count: row.get("play_count"),
+                user_count: row.get("user_count"),
+                recording_mb_id: row.get("recording_mb_id"),
+            },
+            similarity: row.get("similarity"),
+        })
+        .collect())
+}
+
+/// Whether an artist-credit aggregation counts only the primary artist (`artists[0]`) or
+/// every artist credited on the track (`artists[0..]`, e.g. featured/collaborating artists
+/// stored later in the array). `PrimaryOnly` matches the aggregations' original behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArtistCreditScope {
+    #[default]
+    PrimaryOnly,
+    AllCredited,
+}
+
+pub async fn calculate_global_stats(
+    pool: &PgPool,
+    year: u32,
+    artist_scope: ArtistCreditScope,
+) -> Result<GlobalStats> {
+    // Get basic stats
+    let basic_stats = sqlx::query(
+        r#"
+        SELECT
+            COUNT(*) as total_plays,
+            SUM(COALESCE(duration_ms, 210000)) as total_duration_ms,
+            COUNT(DISTINCT user_did) as unique_users,
+            COUNT(DISTINCT track_name) as unique_tracks
+        FROM user_plays
+        WHERE EXTRACT(YEAR FROM played_at) = $1
+        "#,
+    )
+    .bind(year as i32)
+    .fetch_one(pool)
+    .await?;
+
+    let total_plays: i64 = basic_stats.get("total_plays");
+    let total_duration_ms: Option<i64> = basic_stats.get("total_duration_ms");
+    let total_minutes = total_duration_ms.unwrap_or(0) as f64 / (1000.0 * 60.0);
+    let unique_users: i64 = basic_stats.get("unique_users");
+    let unique_tracks: i64 = basic_stats.get("unique_tracks");
+
+    let unique_artists: i64 = match artist_scope {
+        ArtistCreditScope::PrimaryOnly => {
+            sqlx::query(
+                r#"
+                SELECT COUNT(DISTINCT (artists->0)->>'artistName') as unique_artists
+                FROM user_plays
+                WHERE EXTRACT(YEAR FROM played_at) = $1
+                "#,
+            )
+            .bind(year as i32)
+            .fetch_one(pool)
+            .await?
+            .get("unique_artists")
+        }
+        ArtistCreditScope::AllCredited => {
+            sqlx::query(
+                r#"
+                SELECT COUNT(DISTINCT elem->>'artistName') as unique_artists
+                FROM user_plays, jsonb_array_elements(artists) elem
+                WHERE EXTRACT(YEAR FROM played_at) = $1
+                "#,
+            )
+            .bind(year as i32)
+            .fetch_one(pool)
+            .await?
+            .get("unique_artists")
+        }
+    };
+
+    // Get top artists. `PrimaryOnly` groups on `artists[0]` alone; `AllCredited` expands the
+    // full credits array first, so a track credited to "A feat. B" contributes a play to both.
+    let top_artists_rows = match artist_scope {
+        ArtistCreditScope::PrimaryOnly => {
+            sqlx::query(
+                r#"
+                SELECT
+                    (artists->0)->>'artistName' as artist_name,
+                    (artists->0)->>'artistMbId' as mb_id,
+                    COUNT(*) as play_count,
+                    COUNT(DISTINCT user_did) as user_count
+                FROM user_plays
+                WHERE EXTRACT(YEAR FROM played_at) = $1
+                  AND jsonb_array_length(artists) > 0
+                GROUP BY (artists->0)->>'artistName', (artists->0)->>'artistMbId'
+                ORDER BY play_count DESC
+                LIMIT 100
+                "#,
+            )
+            .bind(year as i32)
+            .fetch_all(pool)
+            .await?
+        }
+        ArtistCreditScope::AllCredited => {
+            sqlx::query(
+                r#"
+                SELECT
+                    elem->>'artistName' as artist_name,
+                    elem->>'artistMbId' as mb_id,
+                    COUNT(*) as play_count,
+                    COUNT(DISTINCT user_did) as user_count
+                FROM user_plays, jsonb_array_elements(artists) elem
+                WHERE EXTRACT(YEAR FROM played_at) = $1
+                GROUP BY elem->>'artistName', elem->>'artistMbId'
+                ORDER BY play_count DESC
+                LIMIT 100
+                "#,
+            )
+            .bind(year as i32)
+            .fetch_all(pool)
+            .await?
+        }
+    };
+
     let top_artists: Vec<GlobalArtist> = top_artists_rows
         .iter()
         .filter_map(|row| {
@@ -185,3 +611,265 @@ pub async fn calculate_global_stats(pool: &PgPool, year: u32) -> Result<GlobalSt
         top_users,
     })
 }
+
+/// An explicit `[start, end)` window, so the same top-artists/tracks/users aggregation that
+/// powers year-end wrapped can also back rolling recap widgets (last 7/30/365 days, this
+/// month) instead of only a calendar year.
+#[derive(Debug, Clone, Copy)]
+pub struct TimeWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl TimeWindow {
+    pub fn last_7_days() -> Self {
+        Self::trailing(Duration::days(7))
+    }
+
+    pub fn last_30_days() -> Self {
+        Self::trailing(Duration::days(30))
+    }
+
+    pub fn last_365_days() -> Self {
+        Self::trailing(Duration::days(365))
+    }
+
+    /// From midnight UTC on the 1st of the current month through now.
+    pub fn this_month() -> Self {
+        let now = Utc::now();
+        let start = Utc
+            .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+            .single()
+            .unwrap_or(now);
+        TimeWindow { start, end: now }
+    }
+
+    /// The same `[Jan 1, next Jan 1)` span `calculate_global_stats` computes via
+    /// `EXTRACT(YEAR FROM played_at)`, expressed as an explicit range instead.
+    pub fn calendar_year(year: u32) -> Self {
+        let start = Utc
+            .with_ymd_and_hms(year as i32, 1, 1, 0, 0, 0)
+            .single()
+            .expect("valid calendar year");
+        let end = Utc
+            .with_ymd_and_hms(year as i32 + 1, 1, 1, 0, 0, 0)
+            .single()
+            .expect("valid calendar year");
+        TimeWindow { start, end }
+    }
+
+    fn trailing(span: Duration) -> Self {
+        let end = Utc::now();
+        TimeWindow {
+            start: end - span,
+            end,
+        }
+    }
+}
+
+/// Stats for an arbitrary `TimeWindow`; the rolling-recap counterpart to `GlobalStats`, which
+/// is scoped to a single calendar year.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RangeStats {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub total_plays: i64,
+    pub total_minutes: f64,
+    pub unique_users: i64,
+    pub unique_artists: i64,
+    pub unique_tracks: i64,
+    pub top_artists: Vec<GlobalArtist>,
+    pub top_tracks: Vec<GlobalTrack>,
+    pub top_users: Vec<TopUser>,
+}
+
+/// Same aggregation as `calculate_global_stats`, but over an explicit `[start, end)` range
+/// instead of a calendar year - `played_at >= $1 AND played_at < $2` so Postgres can use a
+/// range index on the column rather than evaluating `EXTRACT(YEAR FROM ...)` per row.
+pub async fn calculate_global_stats_range(
+    pool: &PgPool,
+    window: TimeWindow,
+    artist_scope: ArtistCreditScope,
+) -> Result<RangeStats> {
+    let basic_stats = sqlx::query(
+        r#"
+        SELECT
+            COUNT(*) as total_plays,
+            SUM(COALESCE(duration_ms, 210000)) as total_duration_ms,
+            COUNT(DISTINCT user_did) as unique_users,
+            COUNT(DISTINCT track_name) as unique_tracks
+        FROM user_plays
+        WHERE played_at >= $1 AND played_at < $2
+        "#,
+    )
+    .bind(window.start)
+    .bind(window.end)
+    .fetch_one(pool)
+    .await?;
+
+    let total_plays: i64 = basic_stats.get("total_plays");
+    let total_duration_ms: Option<i64> = basic_stats.get("total_duration_ms");
+    let total_minutes = total_duration_ms.unwrap_or(0) as f64 / (1000.0 * 60.0);
+    let unique_users: i64 = basic_stats.get("unique_users");
+    let unique_tracks: i64 = basic_stats.get("unique_tracks");
+
+    let unique_artists: i64 = match artist_scope {
+        ArtistCreditScope::PrimaryOnly => {
+            sqlx::query(
+                r#"
+                SELECT COUNT(DISTINCT (artists->0)->>'artistName') as unique_artists
+                FROM user_plays
+                WHERE played_at >= $1 AND played_at < $2
+                "#,
+            )
+            .bind(window.start)
+            .bind(window.end)
+            .fetch_one(pool)
+            .await?
+            .get("unique_artists")
+        }
+        ArtistCreditScope::AllCredited => {
+            sqlx::query(
+                r#"
+                SELECT COUNT(DISTINCT elem->>'artistName') as unique_artists
+                FROM user_plays, jsonb_array_elements(artists) elem
+                WHERE played_at >= $1 AND played_at < $2
+                "#,
+            )
+            .bind(window.start)
+            .bind(window.end)
+            .fetch_one(pool)
+            .await?
+            .get("unique_artists")
+        }
+    };
+
+    let top_artists_rows = match artist_scope {
+        ArtistCreditScope::PrimaryOnly => {
+            sqlx::query(
+                r#"
+                SELECT
+                    (artists->0)->>'artistName' as artist_name,
+                    (artists->0)->>'artistMbId' as mb_id,
+                    COUNT(*) as play_count,
+                    COUNT(DISTINCT user_did) as user_count
+                FROM user_plays
+                WHERE played_at >= $1 AND played_at < $2
+                  AND jsonb_array_length(artists) > 0
+                GROUP BY (artists->0)->>'artistName', (artists->0)->>'artistMbId'
+                ORDER BY play_count DESC
+                LIMIT 100
+                "#,
+            )
+            .bind(window.start)
+            .bind(window.end)
+            .fetch_all(pool)
+            .await?
+        }
+        ArtistCreditScope::AllCredited => {
+            sqlx::query(
+                r#"
+                SELECT
+                    elem->>'artistName' as artist_name,
+                    elem->>'artistMbId' as mb_id,
+                    COUNT(*) as play_count,
+                    COUNT(DISTINCT user_did) as user_count
+                FROM user_plays, jsonb_array_elements(artists) elem
+                WHERE played_at >= $1 AND played_at < $2
+                GROUP BY elem->>'artistName', elem->>'artistMbId'
+                ORDER BY play_count DESC
+                LIMIT 100
+                "#,
+            )
+            .bind(window.start)
+            .bind(window.end)
+            .fetch_all(pool)
+            .await?
+        }
+    };
+
+    let top_artists: Vec<GlobalArtist> = top_artists_rows
+        .iter()
+        .map(|row| GlobalArtist {
+            name: row.get("artist_name"),
+            play_count: row.get("play_count"),
+            user_count: row.get("user_count"),
+            mb_id: row.get("mb_id"),
+        })
+        .collect();
+
+    let top_tracks_rows = sqlx::query(
+        r#"
+        SELECT
+            track_name,
+            (artists->0)->>'artistName' as artist_name,
+            recording_mb_id,
+            COUNT(*) as play_count,
+            COUNT(DISTINCT user_did) as user_count
+        FROM user_plays
+        WHERE played_at >= $1 AND played_at < $2
+          AND jsonb_array_length(artists) > 0
+        GROUP BY track_name, (artists->0)->>'artistName', recording_mb_id
+        ORDER BY play_count DESC
+        LIMIT 100
+        "#,
+    )
+    .bind(window.start)
+    .bind(window.end)
+    .fetch_all(pool)
+    .await?;
+
+    let top_tracks: Vec<GlobalTrack> = top_tracks_rows
+        .iter()
+        .map(|row| GlobalTrack {
+            track_name: row.get("track_name"),
+            artist_name: row.get("artist_name"),
+            play_count: row.get("play_count"),
+            user_count: row.get("user_count"),
+            recording_mb_id: row.get("recording_mb_id"),
+        })
+        .collect();
+
+    let top_users_rows = sqlx::query(
+        r#"
+        SELECT
+            user_did,
+            COUNT(*) as play_count,
+            SUM(COALESCE(duration_ms, 210000)) as total_duration_ms
+        FROM user_plays
+        WHERE played_at >= $1 AND played_at < $2
+        GROUP BY user_did
+        ORDER BY play_count DESC
+        LIMIT 100
+        "#,
+    )
+    .bind(window.start)
+    .bind(window.end)
+    .fetch_all(pool)
+    .await?;
+
+    let top_users: Vec<TopUser> = top_users_rows
+        .iter()
+        .map(|row| {
+            let total_duration_ms: Option<i64> = row.get("total_duration_ms");
+            TopUser {
+                user_did: row.get("user_did"),
+                play_count: row.get("play_count"),
+                listening_minutes: total_duration_ms.unwrap_or(0) as f64 / (1000.0 * 60.0),
+            }
+        })
+        .collect();
+
+    Ok(RangeStats {
+        start: window.start,
+        end: window.end,
+        total_plays,
+        total_minutes,
+        unique_users,
+        unique_artists,
+        unique_tracks,
+        top_artists,
+        top_tracks,
+        top_users,
+    })
+}
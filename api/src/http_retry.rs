@@ -0,0 +1,86 @@
+//! Retry-with-backoff wrapper for ad hoc external HTTP calls (Spotify, fanart.tv, and AT
+//! Protocol PDS/`plc.directory` lookups) that don't warrant a dedicated client like
+//! `musicbrainz`'s. Those call sites used to treat any non-success status as a flat failure,
+//! so a transient `429`/`5xx` looked identical to a genuine miss - for `fanart::get_artist_image`
+//! that meant a rate limit got permanently cached as "no image" for 30 days. `send_with_retry`
+//! retries on `429`/`5xx` or a transport error, honoring `Retry-After` (seconds) when present
+//! and otherwise backing off exponentially with jitter, then hands back the final
+//! response/error once attempts are exhausted - success or not - so the caller still decides
+//! what a non-success status means.
+
+use rand::Rng;
+use std::future::Future;
+use std::time::Duration;
+
+/// First backoff delay when `Retry-After` isn't present; doubles per retry.
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Call `request` up to `MAX_ATTEMPTS` times, retrying on a `429`, a `5xx`, or a transport
+/// error. `label` is only used for the warning log (e.g. `"fanart_spotify_search"`).
+pub async fn send_with_retry<F, Fut>(label: &str, mut request: F) -> reqwest::Result<reqwest::Response>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = reqwest::Result<reqwest::Response>>,
+{
+    let mut attempt = 0u32;
+    let mut backoff = BASE_BACKOFF;
+
+    loop {
+        attempt += 1;
+
+        match request().await {
+            Ok(response) => {
+                let status = response.status();
+                let retryable =
+                    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+
+                if !retryable || attempt >= MAX_ATTEMPTS {
+                    return Ok(response);
+                }
+
+                let wait = response
+                    .headers()
+                    .get("Retry-After")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .map(Duration::from_secs)
+                    .unwrap_or_else(|| with_jitter(backoff));
+
+                tracing::warn!(
+                    "{} returned {}, retrying in {:?} (attempt {}/{})",
+                    label,
+                    status,
+                    wait,
+                    attempt,
+                    MAX_ATTEMPTS
+                );
+                tokio::time::sleep(wait).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+            Err(e) => {
+                if attempt >= MAX_ATTEMPTS {
+                    return Err(e);
+                }
+
+                let wait = with_jitter(backoff);
+                tracing::warn!(
+                    "{} request failed ({}), retrying in {:?} (attempt {}/{})",
+                    label,
+                    e,
+                    wait,
+                    attempt,
+                    MAX_ATTEMPTS
+                );
+                tokio::time::sleep(wait).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+    }
+}
+
+fn with_jitter(base: Duration) -> Duration {
+    let jitter_ms = rand::thread_rng().gen_range(0..250);
+    base + Duration::from_millis(jitter_ms)
+}
@@ -0,0 +1,175 @@
+//! Shared-listening comparison between two users, computed directly from their AT Protocol
+//! repos (via [`atproto::fetch_scrobbles_stream`]) rather than `user_plays`, so it works even
+//! for a user who has never been imported into this instance's database.
+
+use crate::atproto::{self, DateRange, ScrobbleRecord};
+use anyhow::Result;
+use futures::StreamExt;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum TrackKey {
+    Mbid(String),
+    Named(String, String),
+}
+
+fn track_key(record: &ScrobbleRecord) -> TrackKey {
+    if let Some(mb_id) = &record.recording_mb_id {
+        return TrackKey::Mbid(mb_id.clone());
+    }
+    let artist = record
+        .artists
+        .first()
+        .map(|a| a.to_lowercase())
+        .unwrap_or_default();
+    TrackKey::Named(record.track_name.to_lowercase(), artist)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ArtistKey {
+    Mbid(String),
+    Named(String),
+}
+
+/// Pairs each of `record`'s artists with a key, preferring `artist_mb_ids` when it's present
+/// and aligned 1:1 with `artists` - a mismatched length means the position-based pairing can't
+/// be trusted, so every artist on that record falls back to a name-based key instead.
+fn artist_keys_with_names(record: &ScrobbleRecord) -> Vec<(ArtistKey, String)> {
+    match &record.artist_mb_ids {
+        Some(mb_ids) if mb_ids.len() == record.artists.len() => record
+            .artists
+            .iter()
+            .zip(mb_ids.iter())
+            .map(|(name, mb_id)| (ArtistKey::Mbid(mb_id.clone()), name.clone()))
+            .collect(),
+        _ => record
+            .artists
+            .iter()
+            .map(|name| (ArtistKey::Named(name.to_lowercase()), name.clone()))
+            .collect(),
+    }
+}
+
+struct TrackTally {
+    track_name: String,
+    artist_name: String,
+    count: u32,
+}
+
+fn tally_tracks(records: &[ScrobbleRecord]) -> HashMap<TrackKey, TrackTally> {
+    let mut tally = HashMap::new();
+    for record in records {
+        let entry = tally.entry(track_key(record)).or_insert_with(|| TrackTally {
+            track_name: record.track_name.clone(),
+            artist_name: record.artists.first().cloned().unwrap_or_default(),
+            count: 0,
+        });
+        entry.count += 1;
+    }
+    tally
+}
+
+struct ArtistTally {
+    artist_name: String,
+    count: u32,
+}
+
+fn tally_artists(records: &[ScrobbleRecord]) -> HashMap<ArtistKey, ArtistTally> {
+    let mut tally = HashMap::new();
+    for record in records {
+        for (key, name) in artist_keys_with_names(record) {
+            let entry = tally
+                .entry(key)
+                .or_insert_with(|| ArtistTally { artist_name: name, count: 0 });
+            entry.count += 1;
+        }
+    }
+    tally
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedTrack {
+    pub track_name: String,
+    pub artist_name: String,
+    pub count_a: u32,
+    pub count_b: u32,
+    pub shared_count: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedArtist {
+    pub artist_name: String,
+    pub count_a: u32,
+    pub count_b: u32,
+    pub shared_count: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Intersection {
+    pub shared_tracks: Vec<SharedTrack>,
+    pub shared_artists: Vec<SharedArtist>,
+    pub total_plays_a: usize,
+    pub total_plays_b: usize,
+}
+
+async fn collect_all_scrobbles(did: &str) -> Result<Vec<ScrobbleRecord>> {
+    let stream = atproto::fetch_scrobbles_stream(did, DateRange::all_time());
+    futures::pin_mut!(stream);
+
+    let mut records = Vec::new();
+    while let Some(record) = stream.next().await {
+        records.push(record?);
+    }
+    Ok(records)
+}
+
+/// Compute the overlap between two users' entire listening histories: shared tracks (keyed on
+/// `recording_mb_id` where present, else normalized track/first-artist name) and shared artists
+/// (keyed on `artist_mb_ids` where present, else normalized name), both ranked by
+/// `min(count_a, count_b)` so a track/artist both users play a lot outranks one either barely
+/// touched.
+pub async fn intersect_scrobbles(did_a: &str, did_b: &str) -> Result<Intersection> {
+    let (records_a, records_b) =
+        futures::try_join!(collect_all_scrobbles(did_a), collect_all_scrobbles(did_b))?;
+
+    let tracks_a = tally_tracks(&records_a);
+    let tracks_b = tally_tracks(&records_b);
+    let mut shared_tracks: Vec<SharedTrack> = tracks_a
+        .iter()
+        .filter_map(|(key, a)| {
+            let b = tracks_b.get(key)?;
+            Some(SharedTrack {
+                track_name: a.track_name.clone(),
+                artist_name: a.artist_name.clone(),
+                count_a: a.count,
+                count_b: b.count,
+                shared_count: a.count.min(b.count),
+            })
+        })
+        .collect();
+    shared_tracks.sort_by(|x, y| y.shared_count.cmp(&x.shared_count));
+
+    let artists_a = tally_artists(&records_a);
+    let artists_b = tally_artists(&records_b);
+    let mut shared_artists: Vec<SharedArtist> = artists_a
+        .iter()
+        .filter_map(|(key, a)| {
+            let b = artists_b.get(key)?;
+            Some(SharedArtist {
+                artist_name: a.artist_name.clone(),
+                count_a: a.count,
+                count_b: b.count,
+                shared_count: a.count.min(b.count),
+            })
+        })
+        .collect();
+    shared_artists.sort_by(|x, y| y.shared_count.cmp(&x.shared_count));
+
+    Ok(Intersection {
+        shared_tracks,
+        shared_artists,
+        total_plays_a: records_a.len(),
+        total_plays_b: records_b.len(),
+    })
+}
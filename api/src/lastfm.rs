@@ -0,0 +1,185 @@
+//! Last.fm scrobble import backend.
+//!
+//! Implements the same `ScrobbleSource` trait as `atproto::AtprotoSource` so a user can
+//! pass either a DID or a Last.fm username and get a unified `Vec<ScrobbleRecord>` back.
+//! Last.fm scrobbles never carry MusicBrainz IDs, so those fields are always left `None`
+//! here - `atproto`'s MBID backfill pass is what fills them in afterward.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::atproto::{ScrobbleRecord, ScrobbleSource};
+
+const API_BASE: &str = "https://ws.audioscrobbler.com/2.0/";
+const PAGE_SIZE: u32 = 200;
+
+pub struct LastfmSource {
+    api_key: String,
+}
+
+impl LastfmSource {
+    pub fn new(api_key: String) -> Self {
+        LastfmSource { api_key }
+    }
+}
+
+impl ScrobbleSource for LastfmSource {
+    fn fetch_scrobbles<'a>(
+        &'a self,
+        username: &'a str,
+        year: u32,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<ScrobbleRecord>>> + Send + 'a>> {
+        Box::pin(fetch_scrobbles(self, username, year))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RecentTracksResponse {
+    recenttracks: RecentTracks,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecentTracks {
+    #[serde(default)]
+    track: Vec<Track>,
+    #[serde(rename = "@attr")]
+    attr: Option<RecentTracksAttr>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecentTracksAttr {
+    #[serde(rename = "totalPages")]
+    total_pages: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Track {
+    artist: TextField,
+    name: String,
+    album: TextField,
+    date: Option<DateField>,
+    #[serde(rename = "@attr")]
+    attr: Option<TrackAttr>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrackAttr {
+    nowplaying: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TextField {
+    #[serde(rename = "#text")]
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DateField {
+    uts: String,
+}
+
+/// Fetch a Last.fm user's scrobbles for a given year, paging through
+/// `user.getRecentTracks` until `totalPages` is exhausted.
+async fn fetch_scrobbles(
+    source: &LastfmSource,
+    username: &str,
+    year: u32,
+) -> Result<Vec<ScrobbleRecord>> {
+    let from = year_bound_unix(year);
+    let to = year_bound_unix(year + 1);
+
+    let client = reqwest::Client::new();
+    let mut scrobbles = Vec::new();
+    let mut page: u32 = 1;
+
+    loop {
+        tracing::debug!("fetching last.fm recent tracks for {} page {}", username, page);
+
+        let response: RecentTracksResponse = client
+            .get(API_BASE)
+            .query(&[
+                ("method", "user.getRecentTracks"),
+                ("user", username),
+                ("api_key", source.api_key.as_str()),
+                ("format", "json"),
+                ("limit", &PAGE_SIZE.to_string()),
+                ("page", &page.to_string()),
+                ("from", &from.to_string()),
+                ("to", &to.to_string()),
+            ])
+            .send()
+            .await
+            .context("failed to reach last.fm")?
+            .json()
+            .await
+            .context("failed to parse last.fm response")?;
+
+        let total_pages: u32 = response
+            .recenttracks
+            .attr
+            .as_ref()
+            .and_then(|attr| attr.total_pages.parse().ok())
+            .unwrap_or(1);
+
+        for track in response.recenttracks.track {
+            // The currently-playing track has no `date` field - skip it rather than
+            // treating it as a completed play.
+            let is_now_playing = track
+                .attr
+                .as_ref()
+                .and_then(|attr| attr.nowplaying.as_deref())
+                == Some("true");
+            if is_now_playing {
+                continue;
+            }
+
+            let Some(date) = &track.date else {
+                continue;
+            };
+            let Ok(uts) = date.uts.parse::<i64>() else {
+                continue;
+            };
+            let Some(played_at) = chrono::DateTime::from_timestamp(uts, 0) else {
+                continue;
+            };
+
+            scrobbles.push(ScrobbleRecord {
+                uri: format!("lastfm://{}/{}", username, uts),
+                cid: String::new(),
+                track_name: track.name,
+                artists: vec![track.artist.text],
+                played_time: Some(played_at.to_rfc3339()),
+                duration: None,
+                recording_mb_id: None,
+                track_mb_id: None,
+                release_mb_id: None,
+                release_name: (!track.album.text.is_empty()).then_some(track.album.text),
+                artist_mb_ids: None,
+                spotify_track_url: None,
+            });
+        }
+
+        if page >= total_pages {
+            break;
+        }
+        page += 1;
+    }
+
+    tracing::info!(
+        "fetched {} last.fm scrobbles for {} in {}",
+        scrobbles.len(),
+        username,
+        year
+    );
+
+    Ok(scrobbles)
+}
+
+fn year_bound_unix(year: u32) -> i64 {
+    chrono::NaiveDate::from_ymd_opt(year as i32, 1, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc().timestamp())
+        .unwrap_or(0)
+}
@@ -2,45 +2,54 @@ use axum::{
     body::Body,
     extract::{Path, Query, State},
     http::header,
-    response::{Json, Response},
-    routing::get,
+    response::{Json, Redirect, Response},
+    routing::{get, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgPool;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs::File;
 use tokio_util::io::ReaderStream;
 use tower_http::cors::CorsLayer;
 use tracing_subscriber;
 
+pub mod aggregate;
 pub mod atproto;
 pub mod db;
+pub mod diagnostics;
 pub mod fanart;
+pub mod future;
+pub mod http_retry;
+pub mod ids;
+pub mod intersection;
+pub mod lastfm;
+pub mod metadata;
 pub mod models;
+pub mod musicbrainz;
+pub mod oauth;
 pub mod og_image;
+pub mod recommendations;
+pub mod rekordbox;
+pub mod retry_worker;
+pub mod spotify;
+pub mod ttl_cache;
 pub mod wrapped;
 
 async fn lookup_release_from_recording(
-    client: &reqwest::Client,
     recording_mb_id: &str,
-) -> Result<Option<String>, reqwest::Error> {
+) -> anyhow::Result<Option<String>> {
     let url = format!(
         "https://musicbrainz.org/ws/2/recording/{}?fmt=json&inc=releases",
         recording_mb_id
     );
 
-    let response = client
-        .get(&url)
-        .header("User-Agent", "TealWrapped/1.0 (https://teal.fm)")
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
+    let Some(data) = musicbrainz::get_json(&url, &[]).await? else {
         return Ok(None);
-    }
+    };
 
-    let data: serde_json::Value = response.json().await?;
     let releases = data.get("releases").and_then(|r| r.as_array());
 
     if let Some(releases) = releases {
@@ -54,16 +63,55 @@ async fn lookup_release_from_recording(
     Ok(None)
 }
 
+/// How long an artist image URL stays cached in memory before a fresh lookup is allowed -
+/// artist artwork essentially never changes, so this can be long.
+const ARTIST_IMAGE_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// How long a resolved profile picture URL stays cached - shorter than artist images since
+/// users update their avatar far more often than an artist's canonical art changes.
+const PROFILE_PICTURE_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+/// How long a recording -> release MBID lookup stays cached - MusicBrainz relationships
+/// are effectively static.
+const RELEASE_LOOKUP_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const EXTERNAL_LOOKUP_CACHE_CAPACITY: usize = 10_000;
+/// Concurrent in-flight logins are a tiny fraction of wrapped-page traffic, so this can be
+/// far smaller than the external-lookup caches above.
+const PENDING_LOGIN_CACHE_CAPACITY: usize = 1_000;
+
 #[derive(Clone)]
 struct AppState {
     db: PgPool,
     http_client: reqwest::Client,
     spotify_client_id: String,
     spotify_client_secret: String,
+    /// Caches the client-credentials bearer token shared by `spotify` (below) and
+    /// `providers`' `FanartProvider`, so neither has to authenticate against Spotify on
+    /// every request.
+    spotify_app_token: Arc<spotify::SpotifyAppToken>,
     fanart_api_key: String,
+    /// Dedupes concurrent `fanart::get_artist_image` calls across requests, keyed by
+    /// `"{mbid}:{quality}"` so a thumbnail request and a full-size request for the same
+    /// artist don't collide on the same cache entry.
+    artist_image_cache: Arc<ttl_cache::TtlCache<String, Option<String>>>,
+    /// Dedupes concurrent `atproto::fetch_profile_picture` calls, keyed by DID.
+    profile_picture_cache: Arc<ttl_cache::TtlCache<String, Option<String>>>,
+    /// Dedupes concurrent `lookup_release_from_recording` calls, keyed by recording MBID.
+    release_lookup_cache: Arc<ttl_cache::TtlCache<String, Option<String>>>,
+    /// Fallback chain of artist/track art sources, tried in order (fanart.tv/Spotify, then
+    /// Deezer, then YouTube thumbnails) so coverage degrades gracefully instead of going
+    /// straight to `None`.
+    providers: Arc<metadata::ProviderChain>,
+    /// Resolves preview URLs and audio features for a year's top tracks. Shared across
+    /// requests so its cached client-credentials token isn't refetched per request.
+    spotify: Arc<spotify::SpotifyLinkResolver>,
+    /// Where Spotify redirects back to after a user approves `/api/auth/login` - must match
+    /// a URI registered on the app's Spotify developer dashboard exactly.
+    spotify_redirect_uri: String,
+    /// CSRF `state` tokens issued by `/api/auth/login`, pending redemption by
+    /// `/api/auth/callback`. Single-use: consumed via `TtlCache::remove`.
+    pending_logins: Arc<ttl_cache::TtlCache<String, oauth::PendingLogin>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct WrappedData {
     year: u32,
     total_minutes: f64,
@@ -74,14 +122,35 @@ pub struct WrappedData {
     activity_graph: Vec<DayActivity>,
     weekday_avg_minutes: f64,
     weekend_avg_minutes: f64,
-    longest_streak: u32,
+    streaks: StreakStats,
     days_active: u32,
     pub avg_track_length_ms: i32,
-    pub listening_diversity: f64,       // unique tracks / total plays
+    pub listening_diversity: f64, // normalized Shannon entropy over per-track play counts, 0.0-1.0
     pub hourly_distribution: [u32; 24], // plays per hour (UTC)
     pub top_hour: u8,                   // hour with most plays (0-23)
     pub longest_session_minutes: u32,   // longest continuous listening session
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_hour: Option<u8>, // modal listening hour (UTC), via MODE()
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_weekday: Option<u8>, // modal day of week (0 = Sunday), via MODE()
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typical_session_hour: Option<u8>, // median listening hour (UTC), via PERCENTILE_DISC(0.5)
+    /// Artists trending right now ("what you're into right now"), distinct from the
+    /// all-time `top_artists` above.
+    pub top_rising_artists: Vec<(String, f64)>,
+    pub session_count: u32,
+    pub avg_session_minutes: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    most_common_session_start_hour: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    epic_session: Option<EpicSession>,
+    monthly_plays: [u32; 12],
+    monthly_minutes: [f64; 12],
+    top_artist_per_month: [Option<(String, u32)>; 12],
+    seasonal_distribution: wrapped::SeasonalDistribution,
+    /// Strongest artist-pair co-occurrences this year ("you always listen to X alongside Y").
+    top_artist_affinities: Vec<(String, String, u32)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     similar_users: Option<Vec<MusicBuddy>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     profile_picture: Option<String>,
@@ -100,6 +169,11 @@ pub struct GlobalWrappedData {
     #[serde(skip_serializing_if = "Option::is_none")]
     user_percentile: Option<GlobalUserPercentile>,
     distribution: GlobalDistribution,
+    /// Artists trending right now across all users.
+    top_rising_artists: Vec<(String, f64)>,
+    /// Histogram of how many users listened at least N minutes, e.g. `[(1, 4213), (10, 3050),
+    /// (60, 980), (600, 112)]`.
+    engagement_cohorts: Vec<(u32, u32)>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -107,7 +181,7 @@ struct GlobalTopArtist {
     name: String,
     plays: u32,
     minutes: f64,
-    mb_id: Option<String>,
+    mb_id: Option<ids::Mbid<'static>>,
     image_url: Option<String>,
 }
 
@@ -134,9 +208,9 @@ struct TopUser {
     minutes: f64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct MusicBuddy {
-    did: String,
+    did: ids::Did<'static>,
     #[serde(skip_serializing_if = "Option::is_none")]
     handle: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -146,13 +220,13 @@ struct MusicBuddy {
     shared_artist_count: u32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct TopArtist {
     name: String,
     plays: u32,
     minutes: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
-    mb_id: Option<String>,
+    mb_id: Option<ids::Mbid<'static>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     image_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -163,39 +237,330 @@ struct TopArtist {
     top_track_duration_ms: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct TopTrack {
     title: String,
     artist: String,
     plays: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
-    recording_mb_id: Option<String>,
+    recording_mb_id: Option<ids::Mbid<'static>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     release_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    release_mb_id: Option<String>,
+    release_mb_id: Option<ids::Mbid<'static>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    preview_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    audio_features: Option<spotify::AudioFeatures>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct DayActivity {
     date: String,
     plays: u32,
     minutes: f64,
 }
 
+/// The year's single longest listening session, so wrapped output can narrate it instead of
+/// reporting `longest_session_minutes` as a bare number.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct EpicSession {
+    date: String,
+    duration_minutes: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_artist: Option<String>,
+}
+
+impl From<wrapped::EpicSession> for EpicSession {
+    fn from(s: wrapped::EpicSession) -> Self {
+        EpicSession {
+            date: s.date.format("%Y-%m-%d").to_string(),
+            duration_minutes: s.duration_minutes,
+            top_artist: s.top_artist,
+        }
+    }
+}
+
+/// The user's longest and current consecutive-day listening runs.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct StreakStats {
+    longest: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    longest_start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    longest_end: Option<String>,
+    current: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current_start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current_end: Option<String>,
+}
+
+impl From<wrapped::StreakStats> for StreakStats {
+    fn from(s: wrapped::StreakStats) -> Self {
+        StreakStats {
+            longest: s.longest,
+            longest_start: s.longest_start.map(|d| d.format("%Y-%m-%d").to_string()),
+            longest_end: s.longest_end.map(|d| d.format("%Y-%m-%d").to_string()),
+            current: s.current,
+            current_start: s.current_start.map(|d| d.format("%Y-%m-%d").to_string()),
+            current_end: s.current_end.map(|d| d.format("%Y-%m-%d").to_string()),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct WrappedQuery {
-    did: String,
+    did: ids::Did<'static>,
 }
 
 #[derive(Debug, Deserialize)]
 struct GlobalWrappedQuery {
-    did: Option<String>,
+    did: Option<ids::Did<'static>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LeaderboardQuery {
+    #[serde(default = "default_leaderboard_page")]
+    page: u32,
+    #[serde(default = "default_leaderboard_per_page")]
+    per_page: u32,
+}
+
+fn default_leaderboard_page() -> u32 {
+    1
+}
+
+fn default_leaderboard_per_page() -> u32 {
+    100
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    q: String,
+    #[serde(default = "default_search_limit")]
+    limit: u32,
+}
+
+fn default_search_limit() -> u32 {
+    10
 }
 
 #[derive(Debug, Deserialize)]
 struct OgImageQuery {
     handle: String,
+    /// Optional render backend override; `"svg"` selects the vector template path,
+    /// anything else (including absent) keeps the default imageproc raster path.
+    format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResolveQuery {
+    /// An `open.spotify.com/{track,album,playlist}/{id}` share link.
+    url: String,
+}
+
+/// Resolve a Spotify share link (track/album/playlist) to its metadata, so a wrapped-style
+/// page can be generated for an arbitrary shared link instead of only a precomputed yearly
+/// aggregate.
+async fn resolve_spotify_link(
+    State(state): State<AppState>,
+    Query(params): Query<ResolveQuery>,
+) -> Result<Json<spotify::ResolvedSpotifyEntry>, axum::http::StatusCode> {
+    let link = spotify::parse_share_link(&params.url).ok_or(axum::http::StatusCode::BAD_REQUEST)?;
+
+    spotify::resolve_share_link(&state.http_client, &state.spotify_app_token, &link)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::warn!("failed to resolve spotify link '{}': {}", params.url, e);
+            axum::http::StatusCode::BAD_GATEWAY
+        })
+}
+
+#[derive(Debug, Deserialize)]
+struct SpotifyLoginQuery {
+    did: ids::Did<'static>,
+    /// The caller's own atproto PDS session access token, proving they control `did` rather
+    /// than just naming it - DIDs are public and used throughout the app's read endpoints,
+    /// so `did` alone isn't proof of anything.
+    atproto_access_token: String,
+}
+
+/// Redirect to Spotify's consent screen, remembering which DID this login is for so the
+/// callback knows whose tokens to store. Rejects the request unless `atproto_access_token`
+/// proves the caller actually controls `did` - otherwise an attacker could bind their own
+/// Spotify consent to a victim's DID and have the victim's wrapped page show the attacker's
+/// listening history.
+async fn spotify_login(
+    State(state): State<AppState>,
+    Query(params): Query<SpotifyLoginQuery>,
+) -> Result<Redirect, axum::http::StatusCode> {
+    let did = params.did.to_string();
+    let owns_did = atproto::verify_session_owns_did(&did, &params.atproto_access_token)
+        .await
+        .map_err(|e| {
+            tracing::warn!("failed to verify atproto session for {}: {}", did, e);
+            axum::http::StatusCode::UNAUTHORIZED
+        })?;
+    if !owns_did {
+        return Err(axum::http::StatusCode::UNAUTHORIZED);
+    }
+
+    let csrf_state = oauth::generate_state();
+    state
+        .pending_logins
+        .insert(csrf_state.clone(), oauth::PendingLogin { did });
+
+    let url = oauth::authorize_url(
+        &state.spotify_client_id,
+        &state.spotify_redirect_uri,
+        &csrf_state,
+    );
+    Ok(Redirect::temporary(&url))
+}
+
+#[derive(Debug, Deserialize)]
+struct SpotifyCallbackQuery {
+    code: String,
+    state: String,
+}
+
+/// Exchange the authorization `code` for tokens and persist them, rejecting anything whose
+/// `state` doesn't match a login we actually issued.
+async fn spotify_callback(
+    State(state): State<AppState>,
+    Query(params): Query<SpotifyCallbackQuery>,
+) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
+    let pending = state
+        .pending_logins
+        .remove(&params.state)
+        .ok_or(axum::http::StatusCode::BAD_REQUEST)?;
+
+    let tokens = oauth::exchange_code(
+        &state.http_client,
+        &state.spotify_client_id,
+        &state.spotify_client_secret,
+        &params.code,
+        &state.spotify_redirect_uri,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("failed to exchange spotify oauth code: {}", e);
+        axum::http::StatusCode::BAD_GATEWAY
+    })?;
+
+    db::store_spotify_tokens(
+        &state.db,
+        &pending.did,
+        &tokens.access_token,
+        &tokens.refresh_token,
+        tokens.expires_at,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("failed to store spotify oauth tokens for {}: {}", pending.did, e);
+        axum::http::StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(serde_json::json!({ "connected": true })))
+}
+
+/// Refresh and persist `did`'s stored Spotify OAuth tokens, returning the new access token.
+async fn refresh_and_store_spotify_token(
+    state: &AppState,
+    did: &str,
+    refresh_token: &str,
+) -> Option<String> {
+    let refreshed = oauth::refresh_token(
+        &state.http_client,
+        &state.spotify_client_id,
+        &state.spotify_client_secret,
+        refresh_token,
+    )
+    .await
+    .map_err(|e| tracing::warn!("failed to refresh spotify oauth token for {}: {}", did, e))
+    .ok()?;
+
+    if let Err(e) = db::store_spotify_tokens(
+        &state.db,
+        did,
+        &refreshed.access_token,
+        &refreshed.refresh_token,
+        refreshed.expires_at,
+    )
+    .await
+    {
+        tracing::warn!("failed to persist refreshed spotify token for {}: {}", did, e);
+    }
+
+    Some(refreshed.access_token)
+}
+
+/// If `did` has connected their Spotify account, fetch their actual top artists/tracks
+/// instead of relying on local scrobbles. The access token is refreshed proactively if it's
+/// past its recorded `expires_at`, and reactively if Spotify rejects it anyway (a 401
+/// surfaces as an `Err`, so any fetch failure gets one refresh-and-retry). Returns `None` if
+/// they haven't connected, or the refresh/fetch still fails after that - callers fall back
+/// to the local-DB path in that case.
+async fn spotify_oauth_top(
+    state: &AppState,
+    did: &str,
+) -> Option<(Vec<TopArtist>, Vec<TopTrack>)> {
+    let stored = db::get_spotify_tokens(&state.db, did).await.ok().flatten()?;
+
+    let mut access_token = if stored.expires_at <= chrono::Utc::now() {
+        refresh_and_store_spotify_token(state, did, &stored.refresh_token).await?
+    } else {
+        stored.access_token.clone()
+    };
+
+    let mut top_artists = spotify::fetch_user_top_artists(&state.http_client, &access_token).await;
+    let mut top_tracks = spotify::fetch_user_top_tracks(&state.http_client, &access_token).await;
+
+    if top_artists.is_err() || top_tracks.is_err() {
+        tracing::debug!("spotify oauth fetch failed for {}, refreshing and retrying once", did);
+        access_token =
+            refresh_and_store_spotify_token(state, did, &stored.refresh_token).await?;
+        top_artists = spotify::fetch_user_top_artists(&state.http_client, &access_token).await;
+        top_tracks = spotify::fetch_user_top_tracks(&state.http_client, &access_token).await;
+    }
+
+    let top_artists = top_artists
+        .map_err(|e| tracing::warn!("failed to fetch spotify top artists for {}: {}", did, e))
+        .ok()?;
+    let top_tracks = top_tracks
+        .map_err(|e| tracing::warn!("failed to fetch spotify top tracks for {}: {}", did, e))
+        .ok()?;
+
+    Some((
+        top_artists
+            .into_iter()
+            .map(|a| TopArtist {
+                name: a.name,
+                plays: 0,
+                minutes: 0.0,
+                mb_id: None,
+                image_url: a.image_url,
+                top_track: None,
+                top_track_plays: None,
+                top_track_duration_ms: None,
+            })
+            .collect(),
+        top_tracks
+            .into_iter()
+            .map(|t| TopTrack {
+                title: t.title,
+                artist: t.artist,
+                plays: 0,
+                recording_mb_id: None,
+                release_name: None,
+                release_mb_id: None,
+                preview_url: t.preview_url,
+                audio_features: None,
+            })
+            .collect(),
+    ))
 }
 
 #[axum::debug_handler]
@@ -206,7 +571,9 @@ async fn get_wrapped(
 ) -> Result<Json<WrappedData>, axum::http::StatusCode> {
     let did = &params.did;
 
-    if let Ok(Some(cached)) = db::get_cached_wrapped(&state.db, did, year).await {
+    if let Ok(Some(cached)) =
+        db::get_cached_wrapped(&state.db, did, wrapped::ReportWindow::Year(year as i32)).await
+    {
         tracing::info!("returning cached data for {} year {}", did, year);
         return Ok(Json(cached));
     }
@@ -247,12 +614,17 @@ async fn get_wrapped(
         return Err(axum::http::StatusCode::NOT_FOUND);
     }
 
-    let stats = wrapped::calculate_wrapped_stats(&state.db, did, year)
-        .await
-        .map_err(|e| {
-            tracing::error!("failed to calculate wrapped stats: {}", e);
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+    let stats = wrapped::calculate_wrapped_stats(
+        &state.db,
+        did,
+        wrapped::ReportWindow::Year(year as i32),
+        None,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("failed to calculate wrapped stats: {}", e);
+        axum::http::StatusCode::INTERNAL_SERVER_ERROR
+    })?;
 
     let mut top_artists = Vec::new();
     for (name, plays, minutes, mb_id) in stats.top_artists {
@@ -265,15 +637,14 @@ async fn get_wrapped(
         // Fetch artist image if we have an MB ID
         let image_url = if let Some(ref mbid) = mb_id {
             tracing::info!("fetching artist image for {} (mbid: {})", name, mbid);
-            match fanart::get_artist_image(
-                &state.db,
-                mbid,
-                &name,
-                &state.spotify_client_id,
-                &state.spotify_client_secret,
-                &state.fanart_api_key,
-            )
-            .await
+            let quality = metadata::ImageQuality::Largest;
+            let cache_key = format!("{}:{}", mbid, quality);
+            match state
+                .artist_image_cache
+                .get_or_fetch(&cache_key, || {
+                    state.providers.artist_image(Some(mbid), &name, quality)
+                })
+                .await
             {
                 Ok(url) => {
                     if let Some(ref u) = url {
@@ -297,7 +668,7 @@ async fn get_wrapped(
             name,
             plays,
             minutes,
-            mb_id,
+            mb_id: mb_id.and_then(|s| ids::Mbid::try_from(s).ok()),
             image_url,
             top_track,
             top_track_plays,
@@ -305,19 +676,53 @@ async fn get_wrapped(
         });
     }
 
-    let top_tracks = stats
+    let mut top_tracks: Vec<TopTrack> = stats
         .top_tracks
         .into_iter()
         .map(|((title, artist), plays, metadata)| TopTrack {
             title,
             artist,
             plays,
-            recording_mb_id: metadata.recording_mb_id,
+            recording_mb_id: metadata.recording_mb_id.and_then(|s| ids::Mbid::try_from(s).ok()),
             release_name: metadata.release_name,
-            release_mb_id: metadata.release_mb_id,
+            release_mb_id: metadata.release_mb_id.and_then(|s| ids::Mbid::try_from(s).ok()),
+            preview_url: None,
+            audio_features: None,
         })
         .collect();
 
+    // Fall back to the metadata provider chain (Deezer, then YouTube) for tracks whose
+    // release name never resolved from the stored plays or a direct MusicBrainz lookup.
+    for track in top_tracks.iter_mut() {
+        if track.release_name.is_some() {
+            continue;
+        }
+        let recording_mb_id = track.recording_mb_id.as_ref().map(|id| id.as_str());
+        match state
+            .providers
+            .track_release(recording_mb_id, &track.title, &track.artist)
+            .await
+        {
+            Ok(Some(info)) => track.release_name = info.release_name,
+            Ok(None) => {}
+            Err(e) => tracing::debug!("failed to fetch track release for {}: {}", track.title, e),
+        }
+    }
+
+    // Resolve Spotify previews/audio features for the top tracks (no-ops if credentials
+    // aren't configured, same as the artist-image providers).
+    let enrichment_keys: Vec<(String, String)> = top_tracks
+        .iter()
+        .map(|t| (t.title.clone(), t.artist.clone()))
+        .collect();
+    let enrichment = state.spotify.enrich_top_tracks(&enrichment_keys).await;
+    for track in top_tracks.iter_mut() {
+        if let Some(entry) = enrichment.get(&(track.title.clone(), track.artist.clone())) {
+            track.preview_url = entry.preview_url.clone();
+            track.audio_features = entry.audio_features.clone();
+        }
+    }
+
     let mut activity_graph: Vec<DayActivity> = stats
         .daily_plays
         .into_iter()
@@ -339,7 +744,11 @@ async fn get_wrapped(
                 // Resolve handle and profile picture for each similar user
                 let (handle, profile_picture) = match atproto::resolve_did_document(&u.did).await {
                     Ok(doc) => {
-                        let pfp = match atproto::fetch_profile_picture(&u.did).await {
+                        let pfp = match state
+                            .profile_picture_cache
+                            .get_or_fetch(&u.did, || atproto::fetch_profile_picture(&u.did))
+                            .await
+                        {
                             Ok(url) => url,
                             Err(e) => {
                                 tracing::debug!("failed to fetch pfp for {}: {}", u.did, e);
@@ -355,7 +764,7 @@ async fn get_wrapped(
                 };
 
                 buddies.push(MusicBuddy {
-                    did: u.did,
+                    did: ids::Did::new_unchecked(u.did),
                     handle,
                     profile_picture,
                     similarity_score: u.similarity_score,
@@ -372,7 +781,11 @@ async fn get_wrapped(
     };
 
     // Fetch profile picture from AT Protocol
-    let profile_picture = match atproto::fetch_profile_picture(did).await {
+    let profile_picture = match state
+        .profile_picture_cache
+        .get_or_fetch(&did.to_string(), || atproto::fetch_profile_picture(did))
+        .await
+    {
         Ok(url) => {
             if url.is_some() {
                 tracing::info!("fetched profile picture for {}", did);
@@ -385,6 +798,17 @@ async fn get_wrapped(
         }
     };
 
+    // A connected Spotify account reflects a user's actual listening history, which is a
+    // better source of top artists/tracks than whatever we've managed to scrobble for them
+    // locally - use it in place of the locally-aggregated lists when available.
+    let (top_artists, top_tracks) = match spotify_oauth_top(&state, &did.to_string()).await {
+        Some((oauth_artists, oauth_tracks)) => {
+            tracing::info!("using spotify oauth top artists/tracks for {}", did);
+            (oauth_artists, oauth_tracks)
+        }
+        None => (top_artists, top_tracks),
+    };
+
     let data = WrappedData {
         year,
         total_minutes: stats.total_minutes,
@@ -395,7 +819,7 @@ async fn get_wrapped(
         activity_graph,
         weekday_avg_minutes: stats.weekday_avg_minutes,
         weekend_avg_minutes: stats.weekend_avg_minutes,
-        longest_streak: stats.longest_streak,
+        streaks: stats.streaks.into(),
         days_active: stats.days_active,
         similar_users,
         avg_track_length_ms: stats.avg_track_length_ms,
@@ -403,10 +827,25 @@ async fn get_wrapped(
         hourly_distribution: stats.hourly_distribution,
         top_hour: stats.top_hour,
         longest_session_minutes: stats.longest_session_minutes,
+        peak_hour: stats.peak_hour,
+        peak_weekday: stats.peak_weekday,
+        typical_session_hour: stats.typical_session_hour,
+        top_rising_artists: stats.top_rising_artists,
+        session_count: stats.session_count,
+        avg_session_minutes: stats.avg_session_minutes,
+        most_common_session_start_hour: stats.most_common_session_start_hour,
+        epic_session: stats.epic_session.map(EpicSession::from),
+        monthly_plays: stats.monthly_plays,
+        monthly_minutes: stats.monthly_minutes,
+        top_artist_per_month: stats.top_artist_per_month,
+        seasonal_distribution: stats.seasonal_distribution,
+        top_artist_affinities: stats.top_artist_affinities,
         profile_picture,
     };
 
-    if let Err(e) = db::cache_wrapped(&state.db, did, year, &data).await {
+    if let Err(e) =
+        db::cache_wrapped(&state.db, did, wrapped::ReportWindow::Year(year as i32), &data).await
+    {
         tracing::warn!("failed to cache wrapped data: {}", e);
     }
 
@@ -419,21 +858,23 @@ async fn get_global_wrapped(
     Path(year): Path<u32>,
     Query(params): Query<GlobalWrappedQuery>,
 ) -> Result<Json<GlobalWrappedData>, axum::http::StatusCode> {
-    if let Ok(Some(_cached)) = wrapped::get_cached_global_wrapped(&state.db, year).await {
+    let window = wrapped::ReportWindow::Year(year as i32);
+
+    if let Ok(Some(_cached)) = wrapped::get_cached_global_wrapped(&state.db, window).await {
         tracing::info!("returning cached global data for year {}", year);
     } else {
         tracing::info!("calculating global wrapped stats for year {}", year);
     }
 
     let user_did = params.did.as_deref();
-    let stats = wrapped::calculate_global_wrapped_stats(&state.db, year, user_did)
+    let stats = wrapped::calculate_global_wrapped_stats(&state.db, window, user_did, None)
         .await
         .map_err(|e| {
             tracing::error!("failed to calculate global wrapped stats: {}", e);
             axum::http::StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    if let Err(e) = wrapped::cache_global_wrapped(&state.db, year, &stats).await {
+    if let Err(e) = wrapped::cache_global_wrapped(&state.db, window, &stats).await {
         tracing::warn!("failed to cache global wrapped data: {}", e);
     }
 
@@ -443,22 +884,18 @@ async fn get_global_wrapped(
         .map(|(did, plays, minutes)| TopUser { did, plays, minutes })
         .collect();
 
-    let spotify_client_id = std::env::var("SPOTIFY_CLIENT_ID").unwrap_or_default();
-    let spotify_client_secret = std::env::var("SPOTIFY_CLIENT_SECRET").unwrap_or_default();
-    let fanart_api_key = std::env::var("FANART_API_KEY").unwrap_or_default();
-
     let mut top_artists: Vec<GlobalTopArtist> = Vec::new();
     for (name, plays, minutes, mb_id) in stats.top_artists {
         let image_url = if let Some(id) = &mb_id {
-            match fanart::get_artist_image(
-                &state.db,
-                id,
-                &name,
-                &spotify_client_id,
-                &spotify_client_secret,
-                &fanart_api_key,
-            )
-            .await
+            // Leaderboard-style listing - a small thumbnail is plenty and keeps the page light.
+            let quality = metadata::ImageQuality::Thumbnail;
+            let cache_key = format!("{}:{}", id, quality);
+            match state
+                .artist_image_cache
+                .get_or_fetch(&cache_key, || {
+                    state.providers.artist_image(Some(id), &name, quality)
+                })
+                .await
             {
                 Ok(Some(url)) => Some(url),
                 Ok(None) => None,
@@ -475,7 +912,7 @@ async fn get_global_wrapped(
             name,
             plays,
             minutes,
-            mb_id,
+            mb_id: mb_id.and_then(|s| ids::Mbid::try_from(s).ok()),
             image_url,
         });
     }
@@ -485,10 +922,17 @@ async fn get_global_wrapped(
         .into_iter()
         .map(|((title, artist), plays, metadata)| async move {
             let mut release_mb_id = metadata.release_mb_id;
+            let mut release_name = metadata.release_name;
 
             if release_mb_id.is_none() {
                 if let Some(ref recording_mb_id) = metadata.recording_mb_id {
-                    match lookup_release_from_recording(&state.http_client, recording_mb_id).await {
+                    let looked_up = state
+                        .release_lookup_cache
+                        .get_or_fetch(recording_mb_id, || {
+                            lookup_release_from_recording(recording_mb_id)
+                        })
+                        .await;
+                    match looked_up {
                         Ok(Some(id)) => release_mb_id = Some(id),
                         Ok(None) => tracing::debug!("no release found for recording {}", recording_mb_id),
                         Err(e) => tracing::warn!("failed to lookup release for {}: {}", recording_mb_id, e),
@@ -496,13 +940,30 @@ async fn get_global_wrapped(
                 }
             }
 
+            // Fall back to the metadata provider chain (Deezer, then YouTube) when MusicBrainz
+            // never turned up a release name for this recording either.
+            if release_name.is_none() {
+                let recording_mb_id = metadata.recording_mb_id.as_deref();
+                match state
+                    .providers
+                    .track_release(recording_mb_id, &title, &artist)
+                    .await
+                {
+                    Ok(Some(info)) => release_name = info.release_name,
+                    Ok(None) => {}
+                    Err(e) => tracing::debug!("failed to fetch track release for {}: {}", title, e),
+                }
+            }
+
             TopTrack {
                 title,
                 artist,
                 plays,
-                recording_mb_id: metadata.recording_mb_id,
-                release_name: metadata.release_name,
-                release_mb_id,
+                recording_mb_id: metadata.recording_mb_id.and_then(|s| ids::Mbid::try_from(s).ok()),
+                release_name,
+                release_mb_id: release_mb_id.and_then(|s| ids::Mbid::try_from(s).ok()),
+                preview_url: None,
+                audio_features: None,
             }
         })
         .collect::<Vec<_>>()
@@ -535,11 +996,100 @@ async fn get_global_wrapped(
         top_tracks,
         user_percentile,
         distribution,
+        top_rising_artists: stats.top_rising_artists,
+        engagement_cohorts: stats.engagement_cohorts,
     };
 
     Ok(Json(data))
 }
 
+/// Leaderboard page of the year's top artists, letting the front end page through the full
+/// ranked set instead of only the top 10 `get_global_wrapped` returns.
+#[axum::debug_handler]
+async fn get_global_top_artists(
+    State(state): State<AppState>,
+    Path(year): Path<u32>,
+    Query(params): Query<LeaderboardQuery>,
+) -> Result<Json<global_stats::PagedResult<global_stats::GlobalArtist>>, axum::http::StatusCode> {
+    global_stats::global_top_artists(
+        &state.db,
+        year,
+        params.page,
+        params.per_page,
+        global_stats::ArtistCreditScope::default(),
+    )
+    .await
+    .map(Json)
+    .map_err(|e| {
+        tracing::error!("failed to page global top artists: {}", e);
+        axum::http::StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Leaderboard page of the year's top tracks; see [`get_global_top_artists`].
+#[axum::debug_handler]
+async fn get_global_top_tracks(
+    State(state): State<AppState>,
+    Path(year): Path<u32>,
+    Query(params): Query<LeaderboardQuery>,
+) -> Result<Json<global_stats::PagedResult<global_stats::GlobalTrack>>, axum::http::StatusCode> {
+    global_stats::global_top_tracks(&state.db, year, params.page, params.per_page)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("failed to page global top tracks: {}", e);
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// Leaderboard page of the year's top listeners; see [`get_global_top_artists`].
+#[axum::debug_handler]
+async fn get_global_top_users(
+    State(state): State<AppState>,
+    Path(year): Path<u32>,
+    Query(params): Query<LeaderboardQuery>,
+) -> Result<Json<global_stats::PagedResult<global_stats::TopUser>>, axum::http::StatusCode> {
+    global_stats::global_top_users(&state.db, year, params.page, params.per_page)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("failed to page global top users: {}", e);
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// Fuzzy artist search within a year's plays, via `pg_trgm` similarity.
+#[axum::debug_handler]
+async fn search_global_artists(
+    State(state): State<AppState>,
+    Path(year): Path<u32>,
+    Query(params): Query<SearchQuery>,
+) -> Result<Json<Vec<global_stats::ScoredArtist>>, axum::http::StatusCode> {
+    global_stats::search_artists(&state.db, &params.q, year, params.limit)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("failed to search global artists: {}", e);
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// Fuzzy track search within a year's plays; see [`search_global_artists`].
+#[axum::debug_handler]
+async fn search_global_tracks(
+    State(state): State<AppState>,
+    Path(year): Path<u32>,
+    Query(params): Query<SearchQuery>,
+) -> Result<Json<Vec<global_stats::ScoredTrack>>, axum::http::StatusCode> {
+    global_stats::search_tracks(&state.db, &params.q, year, params.limit)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("failed to search global tracks: {}", e);
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
 async fn health_check() -> &'static str {
     "ok"
 }
@@ -579,12 +1129,20 @@ async fn get_og_image(
     })?;
 
     // Try to get cached wrapped data first
-    let wrapped_data = if let Ok(Some(cached)) = db::get_cached_wrapped(&state.db, &did, year).await
+    let wrapped_data = if let Ok(Some(cached)) =
+        db::get_cached_wrapped(&state.db, &did, wrapped::ReportWindow::Year(year as i32)).await
     {
         Some(cached)
     } else {
         // Try to calculate it
-        match wrapped::calculate_wrapped_stats(&state.db, &did, year).await {
+        match wrapped::calculate_wrapped_stats(
+            &state.db,
+            &did,
+            wrapped::ReportWindow::Year(year as i32),
+            None,
+        )
+        .await
+        {
             Ok(stats) => {
                 // Get profile picture
                 let mut profile_picture: Option<String> = None;
@@ -598,17 +1156,17 @@ async fn get_og_image(
                 // Get top artist with image for OG background
                 if let Some((name, plays, minutes, mb_id)) = stats.top_artists.first() {
                     let image_url = if let Some(ref mbid) = mb_id {
-                        fanart::get_artist_image(
-                            &state.db,
-                            mbid,
-                            name,
-                            &state.spotify_client_id,
-                            &state.spotify_client_secret,
-                            &state.fanart_api_key,
-                        )
-                        .await
-                        .ok()
-                        .flatten()
+                        // Full-size background for the OG image, not a feed thumbnail.
+                        let quality = metadata::ImageQuality::Largest;
+                        let cache_key = format!("{}:{}", mbid, quality);
+                        state
+                            .artist_image_cache
+                            .get_or_fetch(&cache_key, || {
+                                state.providers.artist_image(Some(mbid), name, quality)
+                            })
+                            .await
+                            .ok()
+                            .flatten()
                     } else {
                         None
                     };
@@ -617,7 +1175,7 @@ async fn get_og_image(
                         name: name.clone(),
                         plays: *plays,
                         minutes: *minutes,
-                        mb_id: mb_id.clone(),
+                        mb_id: mb_id.clone().and_then(|s| ids::Mbid::try_from(s).ok()),
                         image_url,
                         top_track: None,
                         top_track_plays: None,
@@ -635,7 +1193,7 @@ async fn get_og_image(
                     activity_graph: vec![],
                     weekday_avg_minutes: 0.0,
                     weekend_avg_minutes: 0.0,
-                    longest_streak: 0,
+                    streaks: wrapped::StreakStats::default().into(),
                     days_active: 0,
                     similar_users: None,
                     avg_track_length_ms: 0,
@@ -643,6 +1201,19 @@ async fn get_og_image(
                     hourly_distribution: [0; 24],
                     top_hour: 0,
                     longest_session_minutes: 0,
+                    peak_hour: None,
+                    peak_weekday: None,
+                    typical_session_hour: None,
+                    top_rising_artists: vec![],
+                    session_count: 0,
+                    avg_session_minutes: 0.0,
+                    most_common_session_start_hour: None,
+                    epic_session: None,
+                    monthly_plays: [0; 12],
+                    monthly_minutes: [0.0; 12],
+                    top_artist_per_month: Default::default(),
+                    seasonal_distribution: wrapped::SeasonalDistribution::default(),
+                    top_artist_affinities: vec![],
                     profile_picture,
                 })
             }
@@ -668,11 +1239,16 @@ async fn get_og_image(
     );
 
     // Generate the OG image
-    let image_bytes = og_image::generate_og_image(
+    let render_mode = match params.format.as_deref() {
+        Some("svg") => og_image::RenderMode::Svg,
+        _ => og_image::RenderMode::Raster,
+    };
+    let image_bytes = og_image::generate_og_image_with_mode(
         handle,
         year,
         profile_picture.as_deref(),
         top_artist_image.as_deref(),
+        render_mode,
     )
     .await
     .map_err(|e| {
@@ -696,6 +1272,168 @@ async fn get_og_image(
         .unwrap())
 }
 
+#[derive(Debug, Serialize)]
+struct ExportPlaylistResponse {
+    playlist_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportPlaylistQuery {
+    did: ids::Did<'static>,
+    /// The caller's own atproto PDS session access token, proving they control `did` - see
+    /// `SpotifyLoginQuery`. Required here too: without it, anyone who knows a DID (DIDs are
+    /// public) could trigger playlist creation/cover upload on that DID's connected Spotify
+    /// account.
+    atproto_access_token: String,
+}
+
+/// Export a year's wrapped as a real Spotify playlist: creates the playlist, adds its top
+/// tracks (resolved to Spotify URIs via app-level search, batched at 100 per request), and
+/// uploads the OG card as the cover image. Requires the caller to have already connected
+/// their Spotify account via `/api/auth/login` with the `playlist-modify-*`/
+/// `ugc-image-upload` scopes.
+async fn export_wrapped_playlist(
+    State(state): State<AppState>,
+    Path(year): Path<u32>,
+    Query(params): Query<ExportPlaylistQuery>,
+) -> Result<Json<ExportPlaylistResponse>, axum::http::StatusCode> {
+    let did = &params.did;
+
+    let owns_did = atproto::verify_session_owns_did(&did.to_string(), &params.atproto_access_token)
+        .await
+        .map_err(|e| {
+            tracing::warn!("failed to verify atproto session for {}: {}", did, e);
+            axum::http::StatusCode::UNAUTHORIZED
+        })?;
+    if !owns_did {
+        return Err(axum::http::StatusCode::UNAUTHORIZED);
+    }
+
+    let stored = db::get_spotify_tokens(&state.db, &did.to_string())
+        .await
+        .map_err(|e| {
+            tracing::error!("failed to load spotify tokens for {}: {}", did, e);
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(axum::http::StatusCode::UNAUTHORIZED)?;
+
+    let access_token = if stored.expires_at <= chrono::Utc::now() {
+        refresh_and_store_spotify_token(&state, &did.to_string(), &stored.refresh_token)
+            .await
+            .ok_or(axum::http::StatusCode::UNAUTHORIZED)?
+    } else {
+        stored.access_token
+    };
+
+    let wrapped = db::get_cached_wrapped(&state.db, did, wrapped::ReportWindow::Year(year as i32))
+        .await
+        .map_err(|e| {
+            tracing::error!("failed to load cached wrapped data for {}: {}", did, e);
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(axum::http::StatusCode::NOT_FOUND)?;
+
+    let mut track_uris = Vec::new();
+    for track in &wrapped.top_tracks {
+        match state
+            .spotify
+            .resolve_track_uri(&track.title, &track.artist)
+            .await
+        {
+            Ok(Some(uri)) => track_uris.push(uri),
+            Ok(None) => tracing::debug!(
+                "no spotify match for '{}' by {}, skipping",
+                track.title,
+                track.artist
+            ),
+            Err(e) => tracing::warn!(
+                "spotify track lookup failed for '{}' by {}: {}",
+                track.title,
+                track.artist,
+                e
+            ),
+        }
+    }
+
+    if track_uris.is_empty() {
+        tracing::warn!("no spotify tracks resolved for {} year {}, aborting export", did, year);
+        return Err(axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let user_id = spotify::fetch_current_user_id(&state.http_client, &access_token)
+        .await
+        .map_err(|e| {
+            tracing::error!("failed to fetch spotify user id for {}: {}", did, e);
+            axum::http::StatusCode::BAD_GATEWAY
+        })?;
+
+    let playlist = spotify::create_playlist(
+        &state.http_client,
+        &access_token,
+        &user_id,
+        &format!("Wrapped {}", year),
+        &format!("My {} Wrapped, exported from teal.fm", year),
+        false,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("failed to create spotify playlist for {}: {}", did, e);
+        axum::http::StatusCode::BAD_GATEWAY
+    })?;
+
+    if let Err(e) = spotify::add_tracks_to_playlist(
+        &state.http_client,
+        &access_token,
+        &playlist.id,
+        &track_uris,
+    )
+    .await
+    {
+        tracing::error!("failed to add tracks to spotify playlist {}: {}", playlist.id, e);
+        return Err(axum::http::StatusCode::BAD_GATEWAY);
+    }
+
+    let cover_image = og_image::generate_og_image_with_mode(
+        &did.to_string(),
+        year,
+        wrapped.profile_picture.as_deref(),
+        wrapped.top_artists.first().and_then(|a| a.image_url.clone()).as_deref(),
+        og_image::RenderMode::Raster,
+    )
+    .await
+    .and_then(|png_bytes| {
+        let mut jpeg_bytes = Vec::new();
+        image::load_from_memory(&png_bytes)?
+            .to_rgb8()
+            .write_to(&mut std::io::Cursor::new(&mut jpeg_bytes), image::ImageFormat::Jpeg)?;
+        Ok(jpeg_bytes)
+    });
+
+    match cover_image {
+        Ok(image_bytes) => {
+            if let Err(e) = spotify::upload_playlist_cover_image(
+                &state.http_client,
+                &access_token,
+                &playlist.id,
+                &image_bytes,
+            )
+            .await
+            {
+                tracing::warn!(
+                    "failed to upload cover image for playlist {}: {}",
+                    playlist.id,
+                    e
+                );
+            }
+        }
+        Err(e) => tracing::warn!("failed to generate OG cover image for {}: {}", did, e),
+    }
+
+    Ok(Json(ExportPlaylistResponse {
+        playlist_url: playlist.url,
+    }))
+}
+
 async fn serve_image(Path(filename): Path<String>) -> Result<Response, axum::http::StatusCode> {
     let filepath = std::path::PathBuf::from("./images").join(&filename);
 
@@ -738,27 +1476,82 @@ pub async fn run() {
     let spotify_client_id = std::env::var("SPOTIFY_CLIENT_ID").unwrap_or_default();
     let spotify_client_secret = std::env::var("SPOTIFY_CLIENT_SECRET").unwrap_or_default();
     let fanart_api_key = std::env::var("FANART_API_KEY").unwrap_or_default();
+    let youtube_api_key = std::env::var("YOUTUBE_API_KEY").unwrap_or_default();
 
     if spotify_client_id.is_empty() && fanart_api_key.is_empty() {
         tracing::warn!(
             "Neither SPOTIFY_CLIENT_ID nor FANART_API_KEY set, artist images will not be fetched"
         );
     }
+    if youtube_api_key.is_empty() {
+        tracing::debug!("YOUTUBE_API_KEY not set, YouTube thumbnail fallback disabled");
+    }
+
+    tokio::spawn(db::run_global_stats_rehydrate_task(db.clone()));
+
+    let spotify_app_token = Arc::new(spotify::SpotifyAppToken::new(
+        spotify_client_id.clone(),
+        spotify_client_secret.clone(),
+    ));
+
+    let providers = metadata::ProviderChain::new(vec![
+        Box::new(metadata::FanartProvider {
+            db: db.clone(),
+            spotify_app_token: spotify_app_token.clone(),
+            fanart_api_key: fanart_api_key.clone(),
+        }),
+        Box::new(metadata::DeezerProvider::new()),
+        Box::new(metadata::YoutubeProvider::new(youtube_api_key)),
+    ]);
+
+    let spotify_resolver = spotify::SpotifyLinkResolver::new(spotify_app_token.clone());
+
+    let spotify_redirect_uri = std::env::var("SPOTIFY_REDIRECT_URI")
+        .unwrap_or_else(|_| "http://localhost:3001/api/auth/callback".to_string());
 
     let state = AppState {
         db,
         http_client: reqwest::Client::new(),
         spotify_client_id,
         spotify_client_secret,
+        spotify_app_token,
         fanart_api_key,
+        artist_image_cache: Arc::new(ttl_cache::TtlCache::new(
+            ARTIST_IMAGE_CACHE_TTL,
+            EXTERNAL_LOOKUP_CACHE_CAPACITY,
+        )),
+        profile_picture_cache: Arc::new(ttl_cache::TtlCache::new(
+            PROFILE_PICTURE_CACHE_TTL,
+            EXTERNAL_LOOKUP_CACHE_CAPACITY,
+        )),
+        release_lookup_cache: Arc::new(ttl_cache::TtlCache::new(
+            RELEASE_LOOKUP_CACHE_TTL,
+            EXTERNAL_LOOKUP_CACHE_CAPACITY,
+        )),
+        providers: Arc::new(providers),
+        spotify: Arc::new(spotify_resolver),
+        spotify_redirect_uri,
+        pending_logins: Arc::new(ttl_cache::TtlCache::new(
+            oauth::PENDING_LOGIN_TTL,
+            PENDING_LOGIN_CACHE_CAPACITY,
+        )),
     };
 
     let app = Router::new()
         .route("/health", get(health_check))
         .route("/api/wrapped/:year", get(get_wrapped))
         .route("/api/wrapped/:year/og", get(get_og_image))
+        .route("/api/wrapped/:year/playlist", post(export_wrapped_playlist))
         .route("/api/global-wrapped/:year", get(get_global_wrapped))
+        .route("/api/global-wrapped/:year/artists", get(get_global_top_artists))
+        .route("/api/global-wrapped/:year/tracks", get(get_global_top_tracks))
+        .route("/api/global-wrapped/:year/users", get(get_global_top_users))
+        .route("/api/global-wrapped/:year/search/artists", get(search_global_artists))
+        .route("/api/global-wrapped/:year/search/tracks", get(search_global_tracks))
+        .route("/api/resolve", get(resolve_spotify_link))
         .route("/images/:filename", get(serve_image))
+        .route("/api/auth/login", get(spotify_login))
+        .route("/api/auth/callback", get(spotify_callback))
         .layer(CorsLayer::permissive())
         .with_state(state);
 
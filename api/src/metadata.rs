@@ -0,0 +1,339 @@
+//! Pluggable artist/track metadata providers.
+//!
+//! `fanart::get_artist_image` (fanart.tv + Spotify) used to be the only source for artist
+//! art, so anything it doesn't cover left `TopArtist.image_url`/`TopTrack.release_*` empty.
+//! `MetadataProvider` lets additional sources be tried in order as a fallback chain instead,
+//! so image coverage degrades gracefully rather than going straight to nothing.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::fanart;
+pub use crate::fanart::ImageQuality;
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseInfo {
+    pub release_name: Option<String>,
+    pub cover_url: Option<String>,
+}
+
+pub trait MetadataProvider: Send + Sync {
+    fn artist_image<'a>(
+        &'a self,
+        mb_id: Option<&'a str>,
+        artist_name: &'a str,
+        quality: ImageQuality,
+    ) -> BoxFuture<'a, Result<Option<String>>>;
+
+    fn track_release<'a>(
+        &'a self,
+        recording_mb_id: Option<&'a str>,
+        track_name: &'a str,
+        artist_name: &'a str,
+    ) -> BoxFuture<'a, Result<Option<ReleaseInfo>>>;
+}
+
+/// Tries each provider in order, moving to the next on a miss (`Ok(None)`) or an error,
+/// returning the first hit.
+pub struct ProviderChain {
+    providers: Vec<Box<dyn MetadataProvider>>,
+}
+
+impl ProviderChain {
+    pub fn new(providers: Vec<Box<dyn MetadataProvider>>) -> Self {
+        ProviderChain { providers }
+    }
+
+    pub async fn artist_image(
+        &self,
+        mb_id: Option<&str>,
+        artist_name: &str,
+        quality: ImageQuality,
+    ) -> Result<Option<String>> {
+        for provider in &self.providers {
+            match provider.artist_image(mb_id, artist_name, quality).await {
+                Ok(Some(url)) => return Ok(Some(url)),
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::debug!(
+                        "metadata provider failed for artist image of {}: {}",
+                        artist_name,
+                        e
+                    );
+                    continue;
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    pub async fn track_release(
+        &self,
+        recording_mb_id: Option<&str>,
+        track_name: &str,
+        artist_name: &str,
+    ) -> Result<Option<ReleaseInfo>> {
+        for provider in &self.providers {
+            match provider
+                .track_release(recording_mb_id, track_name, artist_name)
+                .await
+            {
+                Ok(Some(info)) => return Ok(Some(info)),
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::debug!(
+                        "metadata provider failed for track release of '{}' by {}: {}",
+                        track_name,
+                        artist_name,
+                        e
+                    );
+                    continue;
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Wraps the existing fanart.tv + Spotify artist-image path. Has no track-release data of
+/// its own - that's resolved separately via MusicBrainz in `lib::lookup_release_from_recording`.
+pub struct FanartProvider {
+    pub db: PgPool,
+    /// Shared with `SpotifyLinkResolver` so the client-credentials token is only fetched
+    /// once across both, instead of this provider re-authenticating on every lookup.
+    pub spotify_app_token: std::sync::Arc<crate::spotify::SpotifyAppToken>,
+    pub fanart_api_key: String,
+}
+
+impl MetadataProvider for FanartProvider {
+    fn artist_image<'a>(
+        &'a self,
+        mb_id: Option<&'a str>,
+        artist_name: &'a str,
+        quality: ImageQuality,
+    ) -> BoxFuture<'a, Result<Option<String>>> {
+        Box::pin(async move {
+            let Some(mb_id) = mb_id else {
+                return Ok(None);
+            };
+            fanart::get_artist_image(
+                &self.db,
+                mb_id,
+                artist_name,
+                &self.spotify_app_token,
+                &self.fanart_api_key,
+                quality,
+            )
+            .await
+        })
+    }
+
+    fn track_release<'a>(
+        &'a self,
+        _recording_mb_id: Option<&'a str>,
+        _track_name: &'a str,
+        _artist_name: &'a str,
+    ) -> BoxFuture<'a, Result<Option<ReleaseInfo>>> {
+        Box::pin(async { Ok(None) })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DeezerArtistSearchResponse {
+    #[serde(default)]
+    data: Vec<DeezerArtist>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeezerArtist {
+    picture_xl: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeezerTrackSearchResponse {
+    #[serde(default)]
+    data: Vec<DeezerTrack>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeezerTrack {
+    album: DeezerAlbum,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeezerAlbum {
+    title: Option<String>,
+    cover_xl: Option<String>,
+}
+
+/// `api.deezer.com` requires no authentication, unlike Spotify/fanart.tv.
+pub struct DeezerProvider {
+    client: reqwest::Client,
+}
+
+impl DeezerProvider {
+    pub fn new() -> Self {
+        DeezerProvider {
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl Default for DeezerProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetadataProvider for DeezerProvider {
+    fn artist_image<'a>(
+        &'a self,
+        _mb_id: Option<&'a str>,
+        artist_name: &'a str,
+        _quality: ImageQuality,
+    ) -> BoxFuture<'a, Result<Option<String>>> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .get("https://api.deezer.com/search/artist")
+                .query(&[("q", artist_name)])
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Ok(None);
+            }
+
+            let search: DeezerArtistSearchResponse = response.json().await?;
+            Ok(search.data.into_iter().find_map(|a| a.picture_xl))
+        })
+    }
+
+    fn track_release<'a>(
+        &'a self,
+        _recording_mb_id: Option<&'a str>,
+        track_name: &'a str,
+        artist_name: &'a str,
+    ) -> BoxFuture<'a, Result<Option<ReleaseInfo>>> {
+        Box::pin(async move {
+            let query = format!(r#"track:"{}" artist:"{}""#, track_name, artist_name);
+            let response = self
+                .client
+                .get("https://api.deezer.com/search")
+                .query(&[("q", query.as_str())])
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Ok(None);
+            }
+
+            let search: DeezerTrackSearchResponse = response.json().await?;
+            Ok(search.data.into_iter().next().map(|t| ReleaseInfo {
+                release_name: t.album.title,
+                cover_url: t.album.cover_xl,
+            }))
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct YoutubeSearchResponse {
+    #[serde(default)]
+    items: Vec<YoutubeItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YoutubeItem {
+    snippet: YoutubeSnippet,
+}
+
+#[derive(Debug, Deserialize)]
+struct YoutubeSnippet {
+    thumbnails: YoutubeThumbnails,
+}
+
+#[derive(Debug, Deserialize)]
+struct YoutubeThumbnails {
+    high: Option<YoutubeThumbnail>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YoutubeThumbnail {
+    url: String,
+}
+
+/// Last-resort cover art for tracks nothing else found anything for: the thumbnail of the
+/// top YouTube search hit for the track. Requires `YOUTUBE_API_KEY`; no-ops without one,
+/// matching the empty-credential handling already used for Spotify/fanart.
+pub struct YoutubeProvider {
+    client: reqwest::Client,
+    api_key: String,
+}
+
+impl YoutubeProvider {
+    pub fn new(api_key: String) -> Self {
+        YoutubeProvider {
+            client: reqwest::Client::new(),
+            api_key,
+        }
+    }
+}
+
+impl MetadataProvider for YoutubeProvider {
+    fn artist_image<'a>(
+        &'a self,
+        _mb_id: Option<&'a str>,
+        _artist_name: &'a str,
+        _quality: ImageQuality,
+    ) -> BoxFuture<'a, Result<Option<String>>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    fn track_release<'a>(
+        &'a self,
+        _recording_mb_id: Option<&'a str>,
+        track_name: &'a str,
+        artist_name: &'a str,
+    ) -> BoxFuture<'a, Result<Option<ReleaseInfo>>> {
+        Box::pin(async move {
+            if self.api_key.is_empty() {
+                return Ok(None);
+            }
+
+            let query = format!("{} {}", artist_name, track_name);
+            let response = self
+                .client
+                .get("https://www.googleapis.com/youtube/v3/search")
+                .query(&[
+                    ("part", "snippet"),
+                    ("type", "video"),
+                    ("maxResults", "1"),
+                    ("q", query.as_str()),
+                    ("key", self.api_key.as_str()),
+                ])
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Ok(None);
+            }
+
+            let search: YoutubeSearchResponse = response.json().await?;
+            Ok(search
+                .items
+                .into_iter()
+                .next()
+                .and_then(|item| item.snippet.thumbnails.high)
+                .map(|thumb| ReleaseInfo {
+                    release_name: None,
+                    cover_url: Some(thumb.url),
+                }))
+        })
+    }
+}
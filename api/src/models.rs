@@ -20,7 +20,7 @@ pub struct UserPlay {
 #[derive(Debug, Clone, FromRow)]
 pub struct WrappedCache {
     pub user_did: String,
-    pub year: i32,
+    pub window_key: String,
     pub data: serde_json::Value,
     pub created_at: DateTime<Utc>,
 }
@@ -45,3 +45,11 @@ pub struct UserDailyActivity {
     pub year: i32,
     pub daily_stats: Option<serde_json::Value>,
 }
+
+#[derive(Debug, Clone, FromRow)]
+pub struct SpotifyOAuthTokens {
+    pub user_did: String,
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: DateTime<Utc>,
+}
@@ -0,0 +1,127 @@
+//! Shared rate-limited, retrying MusicBrainz HTTP client.
+//!
+//! MusicBrainz enforces roughly 1 request/second per client and returns 429/503 under
+//! burst. `lib::lookup_release_from_recording` used to fire requests directly off the
+//! shared `reqwest::Client` with no throttling at all, silently turning a dropped request
+//! into `None`. This is a single process-wide client (independent of `AppState`, since
+//! `atproto::backfill_musicbrainz_ids` calls MusicBrainz from batch-import code paths that
+//! never construct one) so every MusicBrainz call shares the same request gate and retry
+//! policy, regardless of call site.
+
+use anyhow::{Context, Result};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+const USER_AGENT: &str = "TealWrapped/1.0 (https://teal.fm)";
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(1000);
+const MAX_ATTEMPTS: u32 = 3;
+
+struct MusicBrainzClient {
+    client: reqwest::Client,
+    /// When the last request was sent, so `throttle` can space requests at least
+    /// `MIN_REQUEST_INTERVAL` apart. Held behind a single `Mutex` rather than a
+    /// per-caller lock, since MB wants a global cap on outbound requests, not a per-key one.
+    last_request: Mutex<Option<Instant>>,
+}
+
+fn client() -> &'static MusicBrainzClient {
+    static CLIENT: OnceLock<MusicBrainzClient> = OnceLock::new();
+    CLIENT.get_or_init(|| MusicBrainzClient {
+        client: reqwest::Client::new(),
+        last_request: Mutex::new(None),
+    })
+}
+
+impl MusicBrainzClient {
+    async fn throttle(&self) -> tokio::sync::MutexGuard<'_, Option<Instant>> {
+        let mut last_request = self.last_request.lock().await;
+        if let Some(last) = *last_request {
+            let elapsed = last.elapsed();
+            if elapsed < MIN_REQUEST_INTERVAL {
+                tokio::time::sleep(MIN_REQUEST_INTERVAL - elapsed).await;
+            }
+        }
+        *last_request = Some(Instant::now());
+        last_request
+    }
+
+    /// `GET` a MusicBrainz URL (with optional query pairs), serialized to at most one
+    /// in-flight request with at least `MIN_REQUEST_INTERVAL` between requests, making up
+    /// to `MAX_ATTEMPTS` attempts on 429/503 with exponential backoff between them (1s, then
+    /// 2s), honoring `Retry-After` when present.
+    async fn get(&self, url: &str, query: &[(&str, &str)]) -> Result<reqwest::Response> {
+        let mut attempt = 0u32;
+        let mut backoff = Duration::from_secs(1);
+
+        loop {
+            attempt += 1;
+            let gate = self.throttle().await;
+
+            let response = self
+                .client
+                .get(url)
+                .query(query)
+                .header("User-Agent", USER_AGENT)
+                .send()
+                .await
+                .context("failed to reach musicbrainz")?;
+
+            let status = response.status();
+            drop(gate);
+            let should_retry = (status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                || status == reqwest::StatusCode::SERVICE_UNAVAILABLE)
+                && attempt < MAX_ATTEMPTS;
+
+            if !should_retry {
+                return Ok(response);
+            }
+
+            let retry_after = response
+                .headers()
+                .get("Retry-After")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(backoff);
+
+            tracing::warn!(
+                "musicbrainz returned {}, retrying in {:?} (attempt {}/{})",
+                status,
+                retry_after,
+                attempt,
+                MAX_ATTEMPTS
+            );
+            tokio::time::sleep(retry_after).await;
+            backoff *= 2;
+        }
+    }
+}
+
+/// Fetch and parse a MusicBrainz JSON endpoint through the shared rate-limited client.
+/// Returns `Ok(None)` for a non-success response (including one that exhausted retries)
+/// rather than an error, matching the existing "missing data" handling at call sites.
+pub async fn get_json(url: &str, query: &[(&str, &str)]) -> Result<Option<serde_json::Value>> {
+    let response = client().get(url, query).await?;
+    let status = response.status();
+
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        crate::diagnostics::report_failure("musicbrainz", url, Some(status), &body).await;
+        return Ok(None);
+    }
+
+    let body = response
+        .text()
+        .await
+        .context("failed to read musicbrainz response body")?;
+
+    match serde_json::from_str(&body) {
+        Ok(data) => Ok(Some(data)),
+        Err(e) => {
+            crate::diagnostics::report_failure("musicbrainz", url, Some(status), &body).await;
+            Err(e).context("failed to parse musicbrainz response")
+        }
+    }
+}
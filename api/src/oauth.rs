@@ -0,0 +1,158 @@
+//! Spotify user OAuth (Authorization Code flow).
+//!
+//! `spotify::SpotifyLinkResolver` only ever runs the client-credentials grant, which is
+//! app-level and can't see anyone's actual listening history - `user-top-read` and
+//! `user-read-recently-played` both require a user to grant consent. This module is that
+//! separate flow: `/api/auth/login` redirects here to Spotify's authorize endpoint with a
+//! random CSRF `state`, Spotify redirects back to `/api/auth/callback` with a `code` and
+//! that same `state`, and the callback exchanges the code for a user-scoped access/refresh
+//! token pair that gets persisted in `db`, keyed by the caller's AT Proto DID.
+
+use anyhow::{Context, Result};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::Deserialize;
+use std::time::Duration;
+
+const AUTHORIZE_URL: &str = "https://accounts.spotify.com/authorize";
+const TOKEN_URL: &str = "https://accounts.spotify.com/api/token";
+/// Scopes needed to build a wrapped page from a user's own history rather than just what
+/// we've scrobbled for them ourselves, plus playlist creation/cover upload so a wrapped can
+/// be exported as a real playlist.
+const SCOPES: &str =
+    "user-top-read user-read-recently-played playlist-modify-public playlist-modify-private ugc-image-upload";
+/// How long a login's CSRF `state` stays valid - long enough to sit on Spotify's consent
+/// screen, short enough that a state leaked via logs/referrers can't be replayed later.
+pub const PENDING_LOGIN_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// What a pending login remembers between `/api/auth/login` issuing a `state` and
+/// `/api/auth/callback` redeeming it - just enough to know whose tokens to store.
+#[derive(Debug, Clone)]
+pub struct PendingLogin {
+    pub did: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: i64,
+}
+
+/// The outcome of a successful code exchange or refresh.
+pub struct TokenSet {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Generate a random, URL-safe CSRF token for the authorize request's `state` parameter.
+pub fn generate_state() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+/// Build the URL to send a user to Spotify's consent screen.
+pub fn authorize_url(client_id: &str, redirect_uri: &str, state: &str) -> String {
+    let mut url = reqwest::Url::parse(AUTHORIZE_URL).expect("AUTHORIZE_URL is a valid URL");
+    url.query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", client_id)
+        .append_pair("scope", SCOPES)
+        .append_pair("redirect_uri", redirect_uri)
+        .append_pair("state", state);
+    url.into()
+}
+
+/// Exchange an authorization `code` for a user-scoped access/refresh token pair.
+pub async fn exchange_code(
+    client: &reqwest::Client,
+    client_id: &str,
+    client_secret: &str,
+    code: &str,
+    redirect_uri: &str,
+) -> Result<TokenSet> {
+    request_token(
+        client,
+        client_id,
+        client_secret,
+        &[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect_uri),
+        ],
+        None,
+    )
+    .await
+}
+
+/// Refresh an expired access token. Spotify doesn't always issue a new `refresh_token` on
+/// refresh, so the existing one is carried forward unless a new one comes back.
+pub async fn refresh_token(
+    client: &reqwest::Client,
+    client_id: &str,
+    client_secret: &str,
+    refresh_token: &str,
+) -> Result<TokenSet> {
+    request_token(
+        client,
+        client_id,
+        client_secret,
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ],
+        Some(refresh_token),
+    )
+    .await
+}
+
+async fn request_token(
+    client: &reqwest::Client,
+    client_id: &str,
+    client_secret: &str,
+    form: &[(&str, &str)],
+    fallback_refresh_token: Option<&str>,
+) -> Result<TokenSet> {
+    let auth = format!("{}:{}", client_id, client_secret);
+    let encoded =
+        base64::Engine::encode(&base64::engine::general_purpose::STANDARD, auth.as_bytes());
+
+    let response = client
+        .post(TOKEN_URL)
+        .header("Authorization", format!("Basic {}", encoded))
+        .form(form)
+        .send()
+        .await
+        .context("failed to reach spotify token endpoint")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        crate::diagnostics::report_failure("spotify_oauth_token", TOKEN_URL, Some(status), &body)
+            .await;
+        anyhow::bail!("spotify token request returned status {}", status);
+    }
+
+    let body = response
+        .text()
+        .await
+        .context("failed to read spotify token response")?;
+    let token: TokenResponse = serde_json::from_str(&body).map_err(|e| {
+        anyhow::anyhow!("failed to parse spotify token response: {}", e)
+    })?;
+
+    let refresh_token = token
+        .refresh_token
+        .or_else(|| fallback_refresh_token.map(str::to_string))
+        .context("spotify token response had no refresh_token and none was already on hand")?;
+
+    Ok(TokenSet {
+        access_token: token.access_token,
+        refresh_token,
+        expires_at: chrono::Utc::now() + chrono::Duration::seconds(token.expires_in),
+    })
+}
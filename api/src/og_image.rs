@@ -5,6 +5,16 @@ use imageproc::drawing::draw_text_mut;
 use std::io::Cursor;
 use tracing;
 
+/// Which backend renders the OG card: `Raster` hand-places glyphs with imageproc (the
+/// original, default path), `Svg` emits the same layout as a vector document and
+/// rasterizes it to PNG through resvg for embedders that want crisp scaling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    #[default]
+    Raster,
+    Svg,
+}
+
 /// Generate an OG image for a user's wrapped page
 pub async fn generate_og_image(
     handle: &str,
@@ -12,6 +22,27 @@ pub async fn generate_og_image(
     profile_picture_url: Option<&str>,
     top_artist_image_url: Option<&str>,
 ) -> Result<Vec<u8>> {
+    generate_og_image_with_mode(
+        handle,
+        year,
+        profile_picture_url,
+        top_artist_image_url,
+        RenderMode::Raster,
+    )
+    .await
+}
+
+/// Generate an OG image using the requested render backend.
+pub async fn generate_og_image_with_mode(
+    handle: &str,
+    year: u32,
+    profile_picture_url: Option<&str>,
+    top_artist_image_url: Option<&str>,
+    mode: RenderMode,
+) -> Result<Vec<u8>> {
+    if mode == RenderMode::Svg {
+        return generate_og_svg_png(handle, year, profile_picture_url, top_artist_image_url).await;
+    }
     // OG image dimensions (1200x630 is the recommended size)
     const WIDTH: u32 = 1200;
     const HEIGHT: u32 = 630;
@@ -19,32 +50,49 @@ pub async fn generate_og_image(
     // Create base image with dark background
     let mut img: RgbaImage = ImageBuffer::from_pixel(WIDTH, HEIGHT, Rgba([10, 10, 10, 255]));
 
-    // Fetch and blur the top artist image for background
-    if let Some(artist_url) = top_artist_image_url {
+    // Fetch and blur the top artist image for background, falling back to a bundled
+    // placeholder so the card never falls back to a flat black background.
+    let artist_img = if let Some(artist_url) = top_artist_image_url {
         tracing::info!("fetching artist background image from URL: '{}'", artist_url);
         match fetch_image(artist_url).await {
             Ok(artist_img) => {
                 tracing::info!("successfully fetched artist image, applying blur");
-                // Resize to cover the canvas
-                let resized = resize_to_cover(&artist_img, WIDTH, HEIGHT);
-                // Apply heavy blur for background effect
-                let blurred = image::imageops::blur(&resized, 30.0);
-                // Darken the blurred image
-                let darkened = darken_image(&blurred, 0.4);
-                // Composite onto base
-                image::imageops::overlay(&mut img, &darkened, 0, 0);
+                Some(artist_img)
             }
             Err(e) => {
-                tracing::warn!("failed to fetch artist image: {}", e);
+                tracing::warn!(
+                    "failed to fetch artist image after retries, using placeholder: {}",
+                    e
+                );
+                Some(placeholder_artist_image())
             }
         }
     } else {
         tracing::info!("no top artist image URL provided for OG background");
+        None
+    };
+
+    if let Some(artist_img) = &artist_img {
+        // Resize to cover the canvas
+        let resized = resize_to_cover(artist_img, WIDTH, HEIGHT);
+        // Apply heavy blur for background effect
+        let blurred = image::imageops::blur(&resized, 30.0);
+        // Darken the blurred image
+        let darkened = darken_image(&blurred, 0.4);
+        // Composite onto base
+        image::imageops::overlay(&mut img, &darkened, 0, 0);
     }
 
     // Add a gradient overlay for better text readability
     add_gradient_overlay(&mut img);
 
+    // Derive the card's accent/text colors from the artist artwork, falling back to the
+    // hardcoded teal when there's no artist image to sample.
+    let theme = match &artist_img {
+        Some(artist_img) => extract_theme(artist_img),
+        None => CardTheme::default(),
+    };
+
     // Load font - use DM Sans Bold
     let font_data = include_bytes!("../../public/fonts/DMSans-Bold.ttf");
     let font = FontRef::try_from_slice(font_data).expect("Failed to load DM Sans font");
@@ -79,7 +127,7 @@ pub async fn generate_og_image(
     let handle_width = text_width(&font, &handle_text, handle_scale);
     draw_text_mut(
         &mut img,
-        Rgba([255, 255, 255, 255]),
+        theme.text_color,
         (WIDTH / 2 - handle_width / 2) as i32,
         270,
         handle_scale,
@@ -93,7 +141,7 @@ pub async fn generate_og_image(
     let title_width = text_width(&font, &title_text, title_scale);
     draw_text_mut(
         &mut img,
-        Rgba([0, 217, 170, 255]), // Teal color #00d9aa
+        theme.accent_color,
         (WIDTH / 2 - title_width / 2) as i32,
         340,
         title_scale,
@@ -107,7 +155,7 @@ pub async fn generate_og_image(
     let subtitle_width = text_width(&font, subtitle_text, subtitle_scale);
     draw_text_mut(
         &mut img,
-        Rgba([255, 255, 255, 220]),
+        theme.subtitle_color,
         (WIDTH / 2 - subtitle_width / 2) as i32,
         440,
         subtitle_scale,
@@ -123,30 +171,393 @@ pub async fn generate_og_image(
     Ok(buffer)
 }
 
+/// Render the OG card as an SVG document and rasterize it to PNG via resvg.
+///
+/// This mirrors `generate_og_image_with_mode`'s raster layout (dark background, blurred
+/// artist art, circular profile picture, centered handle/title/subtitle) but expresses it
+/// as a declarative template instead of imperative pixel math, so layout tweaks are a
+/// markup edit rather than a redo of the glyph-placement arithmetic above.
+async fn generate_og_svg_png(
+    handle: &str,
+    year: u32,
+    profile_picture_url: Option<&str>,
+    top_artist_image_url: Option<&str>,
+) -> Result<Vec<u8>> {
+    const WIDTH: u32 = 1200;
+    const HEIGHT: u32 = 630;
+
+    let artist_img = match top_artist_image_url {
+        Some(artist_url) => match fetch_image(artist_url).await {
+            Ok(img) => Some(img),
+            Err(e) => {
+                tracing::warn!(
+                    "failed to fetch artist image for SVG card, using placeholder: {}",
+                    e
+                );
+                Some(placeholder_artist_image())
+            }
+        },
+        None => None,
+    };
+
+    let theme = match &artist_img {
+        Some(artist_img) => extract_theme(artist_img),
+        None => CardTheme::default(),
+    };
+
+    let background_href = match &artist_img {
+        Some(artist_img) => {
+            let resized = resize_to_cover(artist_img, WIDTH, HEIGHT);
+            let blurred = image::imageops::blur(&resized, 30.0);
+            let darkened = darken_image(&blurred, 0.4);
+            Some(png_data_uri(&DynamicImage::ImageRgba8(darkened))?)
+        }
+        None => None,
+    };
+
+    let profile_href = match profile_picture_url {
+        Some(pfp_url) => fetch_image(pfp_url).await.ok().map(|img| {
+            let resized = img.resize_exact(280, 280, image::imageops::FilterType::Lanczos3);
+            png_data_uri(&resized)
+        }),
+        None => None,
+    }
+    .transpose()?;
+
+    let handle_text = format!("@{}", handle);
+    let title_text = format!("{} Teal.fm", year);
+
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}">"#
+    ));
+    svg.push_str(&format!(
+        r#"<rect width="{WIDTH}" height="{HEIGHT}" fill="rgb(10,10,10)"/>"#
+    ));
+
+    if let Some(href) = &background_href {
+        svg.push_str(&format!(
+            r#"<image href="{href}" x="0" y="0" width="{WIDTH}" height="{HEIGHT}" preserveAspectRatio="xMidYMid slice"/>"#
+        ));
+    }
+
+    svg.push_str(
+        r#"<defs><clipPath id="pfp-clip"><circle cx="600" cy="180" r="70"/></clipPath></defs>"#,
+    );
+
+    if let Some(href) = &profile_href {
+        svg.push_str(&format!(
+            r#"<image href="{href}" x="460" y="40" width="280" height="280" clip-path="url(#pfp-clip)"/>"#
+        ));
+    } else {
+        svg.push_str(r#"<circle cx="600" cy="180" r="70" fill="rgb(60,60,80)"/>"#);
+    }
+
+    svg.push_str(&format!(
+        r#"<text x="600" y="270" text-anchor="middle" font-family="DM Sans" font-weight="700" font-size="52" fill="{}">{}</text>"#,
+        rgba_to_css(theme.text_color),
+        escape_xml(&handle_text),
+    ));
+    svg.push_str(&format!(
+        r#"<text x="600" y="360" text-anchor="middle" font-family="DM Sans" font-weight="700" font-size="90" fill="{}">{}</text>"#,
+        rgba_to_css(theme.accent_color),
+        escape_xml(&title_text),
+    ));
+    svg.push_str(&format!(
+        r#"<text x="600" y="450" text-anchor="middle" font-family="DM Sans" font-weight="700" font-size="64" fill="{}">Year In Music</text>"#,
+        rgba_to_css(theme.subtitle_color),
+    ));
+    svg.push_str("</svg>");
+
+    rasterize_svg(&svg, WIDTH, HEIGHT)
+}
+
+/// Encode an image as a `data:image/png;base64,...` URI for inline embedding in `<image>`
+/// elements, since resvg resolves hrefs relative to the document rather than our image
+/// cache.
+fn png_data_uri(img: &DynamicImage) -> Result<String> {
+    use base64::Engine;
+
+    let mut buffer = Vec::new();
+    let mut cursor = Cursor::new(&mut buffer);
+    img.write_to(&mut cursor, image::ImageFormat::Png)?;
+    let encoded = base64::engine::general_purpose::STANDARD.encode(&buffer);
+    Ok(format!("data:image/png;base64,{}", encoded))
+}
+
+fn rgba_to_css(color: Rgba<u8>) -> String {
+    format!(
+        "rgba({},{},{},{:.3})",
+        color[0],
+        color[1],
+        color[2],
+        color[3] as f32 / 255.0
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Rasterize an SVG document to PNG bytes via resvg, using the default system font
+/// database so `font-family: "DM Sans"` falls back gracefully if the font isn't
+/// installed on the host.
+fn rasterize_svg(svg: &str, width: u32, height: u32) -> Result<Vec<u8>> {
+    let mut fontdb = resvg::usvg::fontdb::Database::new();
+    fontdb.load_system_fonts();
+
+    let opt = resvg::usvg::Options {
+        fontdb: std::sync::Arc::new(fontdb),
+        ..Default::default()
+    };
+
+    let tree = resvg::usvg::Tree::from_str(svg, &opt)
+        .map_err(|e| anyhow::anyhow!("failed to parse OG card SVG: {}", e))?;
+
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| anyhow::anyhow!("failed to allocate pixmap for OG card"))?;
+    resvg::render(&tree, resvg::usvg::Transform::identity(), &mut pixmap.as_mut());
+
+    pixmap
+        .encode_png()
+        .map_err(|e| anyhow::anyhow!("failed to encode rasterized OG card: {}", e))
+}
+
+/// Accent/text colors derived from the artist artwork for a given card.
+struct CardTheme {
+    accent_color: Rgba<u8>,
+    text_color: Rgba<u8>,
+    subtitle_color: Rgba<u8>,
+}
+
+impl Default for CardTheme {
+    fn default() -> Self {
+        CardTheme {
+            accent_color: Rgba([0, 217, 170, 255]), // Teal color #00d9aa
+            text_color: Rgba([255, 255, 255, 255]),
+            subtitle_color: Rgba([255, 255, 255, 220]),
+        }
+    }
+}
+
+/// Sample the artist artwork and derive an accent color (from the dominant palette) plus
+/// a legible text color (from the luminance of the darkened background behind the text).
+fn extract_theme(img: &DynamicImage) -> CardTheme {
+    const SAMPLE_SIZE: u32 = 64;
+    let sample = img.resize_exact(SAMPLE_SIZE, SAMPLE_SIZE, image::imageops::FilterType::Triangle);
+    let pixels: Vec<(u8, u8, u8)> = sample
+        .to_rgb8()
+        .pixels()
+        .map(|p| (p[0], p[1], p[2]))
+        .collect();
+
+    let palette = median_cut_palette(&pixels, 8);
+
+    let accent_color = palette
+        .iter()
+        .filter(|&&(r, g, b)| !is_near_black_or_white(r, g, b))
+        .max_by(|a, b| saturation(*a).partial_cmp(&saturation(*b)).unwrap())
+        .map(|&(r, g, b)| Rgba([r, g, b, 255]))
+        .unwrap_or(Rgba([0, 217, 170, 255]));
+
+    // The text sits over the blurred+darkened background, so judge legibility against the
+    // same darkening factor `generate_og_image` applies (0.4) rather than the raw artwork.
+    let avg_luminance = pixels
+        .iter()
+        .map(|&(r, g, b)| relative_luminance(r, g, b) * 0.4)
+        .sum::<f32>()
+        / pixels.len().max(1) as f32;
+
+    let (text_color, subtitle_color) = if avg_luminance < 0.5 {
+        (Rgba([255, 255, 255, 255]), Rgba([255, 255, 255, 220]))
+    } else {
+        (Rgba([17, 17, 17, 255]), Rgba([17, 17, 17, 220]))
+    };
+
+    CardTheme {
+        accent_color,
+        text_color,
+        subtitle_color,
+    }
+}
+
+fn relative_luminance(r: u8, g: u8, b: u8) -> f32 {
+    0.2126 * (r as f32 / 255.0) + 0.7152 * (g as f32 / 255.0) + 0.0722 * (b as f32 / 255.0)
+}
+
+fn saturation((r, g, b): (u8, u8, u8)) -> f32 {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    if max == 0.0 {
+        0.0
+    } else {
+        (max - min) / max
+    }
+}
+
+fn is_near_black_or_white(r: u8, g: u8, b: u8) -> bool {
+    let luminance = relative_luminance(r, g, b);
+    !(0.08..0.92).contains(&luminance)
+}
+
+/// Median-cut color quantization: recursively split the color box along its longest RGB
+/// axis until `target_buckets` boxes remain, then average each bucket into one color.
+fn median_cut_palette(pixels: &[(u8, u8, u8)], target_buckets: usize) -> Vec<(u8, u8, u8)> {
+    if pixels.is_empty() {
+        return Vec::new();
+    }
+
+    let mut boxes: Vec<Vec<(u8, u8, u8)>> = vec![pixels.to_vec()];
+
+    while boxes.len() < target_buckets {
+        let Some((idx, _)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .max_by_key(|(_, b)| box_range(b))
+        else {
+            break;
+        };
+
+        let bucket = boxes.swap_remove(idx);
+        let axis = longest_axis(&bucket);
+        let mut sorted = bucket;
+        sorted.sort_by_key(|&(r, g, b)| match axis {
+            0 => r,
+            1 => g,
+            _ => b,
+        });
+        let mid = sorted.len() / 2;
+        let (low, high) = sorted.split_at(mid);
+        boxes.push(low.to_vec());
+        boxes.push(high.to_vec());
+    }
+
+    boxes
+        .iter()
+        .filter(|b| !b.is_empty())
+        .map(|bucket| {
+            let len = bucket.len() as u32;
+            let (sr, sg, sb) = bucket.iter().fold((0u32, 0u32, 0u32), |(sr, sg, sb), &(r, g, b)| {
+                (sr + r as u32, sg + g as u32, sb + b as u32)
+            });
+            ((sr / len) as u8, (sg / len) as u8, (sb / len) as u8)
+        })
+        .collect()
+}
+
+fn longest_axis(bucket: &[(u8, u8, u8)]) -> u8 {
+    let (mut r_min, mut r_max) = (255u8, 0u8);
+    let (mut g_min, mut g_max) = (255u8, 0u8);
+    let (mut b_min, mut b_max) = (255u8, 0u8);
+
+    for &(r, g, b) in bucket {
+        r_min = r_min.min(r);
+        r_max = r_max.max(r);
+        g_min = g_min.min(g);
+        g_max = g_max.max(g);
+        b_min = b_min.min(b);
+        b_max = b_max.max(b);
+    }
+
+    let ranges = [
+        (r_max - r_min, 0u8),
+        (g_max - g_min, 1u8),
+        (b_max - b_min, 2u8),
+    ];
+    ranges.iter().max_by_key(|(range, _)| *range).unwrap().1
+}
+
+fn box_range(bucket: &[(u8, u8, u8)]) -> u8 {
+    let (mut r_min, mut r_max) = (255u8, 0u8);
+    let (mut g_min, mut g_max) = (255u8, 0u8);
+    let (mut b_min, mut b_max) = (255u8, 0u8);
+
+    for &(r, g, b) in bucket {
+        r_min = r_min.min(r);
+        r_max = r_max.max(r);
+        g_min = g_min.min(g);
+        g_max = g_max.max(g);
+        b_min = b_min.min(b);
+        b_max = b_max.max(b);
+    }
+
+    (r_max - r_min).max(g_max - g_min).max(b_max - b_min)
+}
+
+/// A small bundled artist photo used when every remote fetch attempt fails, so the OG
+/// background never degrades to flat black.
+fn placeholder_artist_image() -> DynamicImage {
+    let bytes: &[u8] = include_bytes!("../../public/images/placeholder_artist.jpg");
+    image::load_from_memory(bytes).expect("bundled placeholder artist image must decode")
+}
+
+const IMAGE_FETCH_RETRIES: u32 = 3;
+const IMAGE_CACHE_DIR: &str = "./images/cache";
+
+fn cache_path_for_url(url: &str) -> std::path::PathBuf {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    url.hash(&mut hasher);
+    std::path::PathBuf::from(IMAGE_CACHE_DIR).join(format!("{:016x}", hasher.finish()))
+}
+
 async fn fetch_image(url: &str) -> Result<DynamicImage> {
     tracing::debug!("fetch_image called with URL: {}", url);
-    
+
     // Check if this is a local path (starts with /images/)
     if url.starts_with("/images/") {
         // Read directly from filesystem
         let file_path = format!(".{}", url); // Convert /images/... to ./images/...
         tracing::debug!("reading local image from: {}", file_path);
-        
+
         let bytes = tokio::fs::read(&file_path).await.map_err(|e| {
             tracing::error!("failed to read local image {}: {}", file_path, e);
             anyhow::anyhow!("file read error: {}", e)
         })?;
-        
+
         let img = image::load_from_memory(&bytes).map_err(|e| {
             tracing::error!("failed to decode local image: {}", e);
             anyhow::anyhow!("image decode error: {}", e)
         })?;
-        
+
         tracing::info!("successfully loaded local image: {}", file_path);
         return Ok(img);
     }
-    
-    // Otherwise, fetch from URL
+
+    // Small on-disk cache keyed by URL hash so repeated wrapped-page renders don't
+    // re-download the same CDN art.
+    let cache_path = cache_path_for_url(url);
+    if let Ok(bytes) = tokio::fs::read(&cache_path).await {
+        if let Ok(img) = image::load_from_memory(&bytes) {
+            tracing::debug!("using disk-cached image for {}", url);
+            return Ok(img);
+        }
+    }
+
+    let bytes = fetch_image_bytes_with_retry(url).await?;
+
+    if let Err(e) = tokio::fs::create_dir_all(IMAGE_CACHE_DIR).await {
+        tracing::warn!("failed to create image cache directory: {}", e);
+    } else if let Err(e) = tokio::fs::write(&cache_path, &bytes).await {
+        tracing::warn!("failed to write image cache entry for {}: {}", url, e);
+    }
+
+    let img = image::load_from_memory(&bytes).map_err(|e| {
+        tracing::error!("failed to decode image: {}", e);
+        anyhow::anyhow!("image decode error: {}", e)
+    })?;
+
+    Ok(img)
+}
+
+/// Fetch raw image bytes, retrying transient failures (timeouts, 5xx) with exponential
+/// backoff before giving up.
+async fn fetch_image_bytes_with_retry(url: &str) -> Result<Vec<u8>> {
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(10))
         .build()
@@ -154,35 +565,60 @@ async fn fetch_image(url: &str) -> Result<DynamicImage> {
             tracing::error!("failed to build reqwest client: {}", e);
             anyhow::anyhow!("client build error: {}", e)
         })?;
-    
-    let response = client
-        .get(url)
-        .header("User-Agent", "TealWrapped/1.0")
-        .send()
-        .await
-        .map_err(|e| {
-            tracing::error!("failed to send request to {}: {}", url, e);
-            anyhow::anyhow!("request error: {}", e)
-        })?;
-    
-    if !response.status().is_success() {
-        tracing::warn!("image request returned status {}: {}", response.status(), url);
-        return Err(anyhow::anyhow!("HTTP {}", response.status()));
+
+    let mut last_err = None;
+
+    for attempt in 0..IMAGE_FETCH_RETRIES {
+        if attempt > 0 {
+            let delay = std::time::Duration::from_millis(500 * 2u64.pow(attempt - 1));
+            tracing::debug!(
+                "retrying image fetch for {} (attempt {}/{}) after {:?}",
+                url,
+                attempt + 1,
+                IMAGE_FETCH_RETRIES,
+                delay
+            );
+            tokio::time::sleep(delay).await;
+        }
+
+        let result = client
+            .get(url)
+            .header("User-Agent", "TealWrapped/1.0")
+            .send()
+            .await;
+
+        match result {
+            Ok(response) if response.status().is_success() => {
+                return response
+                    .bytes()
+                    .await
+                    .map(|b| b.to_vec())
+                    .map_err(|e| {
+                        tracing::error!("failed to read response bytes: {}", e);
+                        anyhow::anyhow!("read error: {}", e)
+                    });
+            }
+            Ok(response) => {
+                let status = response.status();
+                let transient = status.is_server_error();
+                tracing::warn!("image request returned status {}: {}", status, url);
+                last_err = Some(anyhow::anyhow!("HTTP {}", status));
+                if !transient {
+                    break;
+                }
+            }
+            Err(e) => {
+                let transient = e.is_timeout() || e.is_connect();
+                tracing::warn!("failed to send request to {}: {}", url, e);
+                last_err = Some(anyhow::anyhow!("request error: {}", e));
+                if !transient {
+                    break;
+                }
+            }
+        }
     }
-    
-    let bytes = response.bytes().await.map_err(|e| {
-        tracing::error!("failed to read response bytes: {}", e);
-        anyhow::anyhow!("read error: {}", e)
-    })?;
-    
-    tracing::debug!("received {} bytes from {}", bytes.len(), url);
-    
-    let img = image::load_from_memory(&bytes).map_err(|e| {
-        tracing::error!("failed to decode image: {}", e);
-        anyhow::anyhow!("image decode error: {}", e)
-    })?;
-    
-    Ok(img)
+
+    Err(last_err.unwrap_or_else(|| anyhow::anyhow!("image fetch failed for {}", url)))
 }
 
 fn resize_to_cover(img: &DynamicImage, target_width: u32, target_height: u32) -> RgbaImage {
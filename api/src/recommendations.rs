@@ -0,0 +1,62 @@
+//! Recency-weighted track recommendations, built from the same `user_plays` table the
+//! wrapped stats already read: a track played a lot but not recently scores highly, so
+//! the user gets "rediscover this" suggestions rather than just their current top plays.
+//!
+//! The artist/album counterparts ([`crate::wrapped::recommend_artists`] /
+//! [`crate::wrapped::recommend_albums`]) live in `wrapped.rs` instead, since they share the
+//! [`TimeWindow`] type and a session-analysis-adjacent query shape with the rest of that module.
+
+use anyhow::Result;
+use sqlx::postgres::PgPool;
+use sqlx::Row;
+
+pub use crate::wrapped::TimeWindow;
+
+/// Same idea as [`crate::wrapped::recommend_artists`], scoped to `track_name` instead of artist.
+pub async fn recommend_tracks(
+    pool: &PgPool,
+    user_did: &str,
+    count: i64,
+    include: TimeWindow,
+    exclude: TimeWindow,
+    random: bool,
+) -> Result<Vec<(String, u32)>> {
+    let include_predicate = include.sql_predicate();
+    let exclude_predicate = exclude.sql_predicate();
+    let order_by = if random {
+        "RANDOM()"
+    } else {
+        "COUNT(*) * (EXTRACT(EPOCH FROM now()) - MAX(EXTRACT(EPOCH FROM played_at))) DESC"
+    };
+
+    let query = format!(
+        r#"
+        SELECT track_name, COUNT(*) as play_count
+        FROM user_plays
+        WHERE user_did = $1
+          AND {include_predicate}
+          AND track_name NOT IN (
+              SELECT DISTINCT track_name
+              FROM user_plays
+              WHERE user_did = $1
+                AND {exclude_predicate}
+          )
+        GROUP BY track_name
+        ORDER BY {order_by}
+        LIMIT $2
+        "#
+    );
+
+    sqlx::query(&query)
+        .bind(user_did)
+        .bind(count)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let track_name: String = row.get("track_name");
+            let plays: i64 = row.get("play_count");
+            Ok((track_name, plays as u32))
+        })
+        .collect()
+}
@@ -0,0 +1,323 @@
+//! Importer for Pioneer Rekordbox `export.pdb` DeviceSQL files.
+//!
+//! The PDB format is a page-based table store: a fixed header names the tables (by type)
+//! and the first page of each; each page holds a type tag, a row count, and a bitmask of
+//! which row slots are actually present, with rows stored as offset-indexed records that
+//! point at length-prefixed DeviceSQL strings. We only read four tables here - History
+//! (play log entries), Track (titles), and the Artist and Album tables a track points at
+//! for its names - so the rest of the schema (playlists, keys, colors, ...) is skipped.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::io::Read;
+
+const PAGE_SIZE: usize = 4096;
+
+// Table type tags, as laid out in the PDB header's table pointer array.
+const TABLE_TYPE_TRACKS: u32 = 0;
+const TABLE_TYPE_ARTISTS: u32 = 1;
+const TABLE_TYPE_ALBUMS: u32 = 2;
+const TABLE_TYPE_HISTORY: u32 = 19;
+
+#[derive(Debug, Clone)]
+pub struct RekordboxPlay {
+    pub track_name: String,
+    pub artist_name: Option<String>,
+    pub release_name: Option<String>,
+    pub played_at: DateTime<Utc>,
+}
+
+struct TablePointer {
+    table_type: u32,
+    first_page: u32,
+}
+
+struct Page {
+    table_type: u32,
+    rows: Vec<Vec<u8>>,
+}
+
+/// Parse an `export.pdb` file and return every History-table play, joined against the
+/// Track/Artist/Album tables for display metadata.
+pub fn parse_export_pdb(path: &std::path::Path) -> Result<Vec<RekordboxPlay>> {
+    let mut file = std::fs::File::open(path)
+        .with_context(|| format!("failed to open rekordbox export at {}", path.display()))?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)
+        .context("failed to read rekordbox export.pdb")?;
+
+    let table_pointers = parse_header(&data)?;
+
+    let mut track_pages = Vec::new();
+    let mut artist_pages = Vec::new();
+    let mut album_pages = Vec::new();
+    let mut history_pages = Vec::new();
+
+    for pointer in &table_pointers {
+        let pages = collect_pages(&data, pointer.first_page)?;
+        match pointer.table_type {
+            TABLE_TYPE_TRACKS => track_pages.extend(pages),
+            TABLE_TYPE_ARTISTS => artist_pages.extend(pages),
+            TABLE_TYPE_ALBUMS => album_pages.extend(pages),
+            TABLE_TYPE_HISTORY => history_pages.extend(pages),
+            _ => {}
+        }
+    }
+
+    let artists = parse_artist_rows(&artist_pages)?;
+    let albums = parse_album_rows(&album_pages)?;
+    let tracks = parse_track_rows(&track_pages, &artists, &albums)?;
+    let plays = parse_history_rows(&history_pages, &tracks)?;
+
+    tracing::info!(
+        "parsed rekordbox export: {} tracks, {} history entries",
+        tracks.len(),
+        plays.len()
+    );
+
+    Ok(plays)
+}
+
+/// The PDB header: a page-size field followed by the table pointer array. Each pointer
+/// records a table type tag and the page index where that table's row chain begins.
+fn parse_header(data: &[u8]) -> Result<Vec<TablePointer>> {
+    if data.len() < PAGE_SIZE {
+        return Err(anyhow!("file too small to contain a PDB header page"));
+    }
+
+    let num_tables = u32::from_le_bytes(data[4..8].try_into()?);
+
+    // Table pointer entries start at offset 0x1c and are 0x14 bytes each, so the file
+    // itself bounds how many could possibly be present - a malformed or truncated file
+    // claiming far more than that would otherwise drive `Vec::with_capacity` to attempt a
+    // huge allocation before a single pointer is even read.
+    let offset = 0x1c;
+    let max_possible_tables = data.len().saturating_sub(offset) / 0x14;
+    if num_tables as usize > max_possible_tables {
+        return Err(anyhow!(
+            "PDB header claims {} tables, but the file only has room for {}",
+            num_tables,
+            max_possible_tables
+        ));
+    }
+
+    let mut pointers = Vec::with_capacity(num_tables as usize);
+
+    let mut offset = offset;
+    for _ in 0..num_tables {
+        if offset + 0x14 > data.len() {
+            break;
+        }
+        let table_type = u32::from_le_bytes(data[offset..offset + 4].try_into()?);
+        let first_page = u32::from_le_bytes(data[offset + 4..offset + 8].try_into()?);
+        pointers.push(TablePointer {
+            table_type,
+            first_page,
+        });
+        offset += 0x14;
+    }
+
+    Ok(pointers)
+}
+
+/// Walk a table's page chain, reading every present row in each page via its
+/// row-presence bitmask.
+fn collect_pages(data: &[u8], first_page: u32) -> Result<Vec<Page>> {
+    let mut pages = Vec::new();
+    let mut page_index = first_page;
+    let mut visited = std::collections::HashSet::new();
+
+    while page_index != 0 && visited.insert(page_index) {
+        let page_offset = page_index as usize * PAGE_SIZE;
+        if page_offset + PAGE_SIZE > data.len() {
+            break;
+        }
+        let page_data = &data[page_offset..page_offset + PAGE_SIZE];
+
+        let table_type = u32::from_le_bytes(page_data[4..8].try_into()?);
+        let next_page = u32::from_le_bytes(page_data[8..12].try_into()?);
+        let num_rows = u16::from_le_bytes(page_data[18..20].try_into()?);
+
+        // The row-presence bitmask starts at offset 0x20 of the page; bit N set means the
+        // Nth row-offset slot actually holds a row rather than a hole left by a deletion.
+        let bitmask_offset = 0x20;
+        let rows = read_rows(page_data, num_rows, bitmask_offset);
+
+        pages.push(Page { table_type, rows });
+        page_index = next_page;
+    }
+
+    Ok(pages)
+}
+
+fn read_rows(page_data: &[u8], num_rows: u16, bitmask_offset: usize) -> Vec<Vec<u8>> {
+    let mut rows = Vec::new();
+    let row_offset_table = bitmask_offset + num_rows.div_ceil(8) as usize;
+
+    for i in 0..num_rows as usize {
+        let bitmask_byte = bitmask_offset + i / 8;
+        if bitmask_byte >= page_data.len() {
+            break;
+        }
+        let present = page_data[bitmask_byte] & (1 << (i % 8)) != 0;
+        if !present {
+            continue;
+        }
+
+        let offset_pos = row_offset_table + i * 2;
+        if offset_pos + 2 > page_data.len() {
+            continue;
+        }
+        let row_offset = u16::from_le_bytes(page_data[offset_pos..offset_pos + 2].try_into().unwrap()) as usize;
+        let row_start = bitmask_offset + row_offset;
+        if row_start >= page_data.len() {
+            continue;
+        }
+        rows.push(page_data[row_start..].to_vec());
+    }
+
+    rows
+}
+
+/// Read a DeviceSQL string: a one-byte length prefix (short strings) or, for longer
+/// strings, a marker byte followed by a little-endian u16 length.
+fn read_devicesql_string(row: &[u8], offset: usize) -> Option<String> {
+    let marker = *row.get(offset)?;
+    if marker & 0x01 == 0 {
+        // Long-form string: 2-byte length follows the marker byte.
+        let len = u16::from_le_bytes(row.get(offset + 1..offset + 3)?.try_into().ok()?) as usize;
+        let start = offset + 4;
+        let bytes = row.get(start..start + len.saturating_sub(4))?;
+        Some(String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string())
+    } else {
+        let len = (marker >> 1) as usize;
+        let start = offset + 1;
+        let bytes = row.get(start..start + len.saturating_sub(1))?;
+        Some(String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string())
+    }
+}
+
+fn parse_artist_rows(pages: &[Page]) -> Result<HashMap<u32, String>> {
+    let mut artists = HashMap::new();
+    for page in pages {
+        for row in &page.rows {
+            if row.len() < 12 {
+                continue;
+            }
+            let id = u32::from_le_bytes(row[8..12].try_into()?);
+            if let Some(name) = read_devicesql_string(row, 12) {
+                artists.insert(id, name);
+            }
+        }
+    }
+    Ok(artists)
+}
+
+fn parse_album_rows(pages: &[Page]) -> Result<HashMap<u32, String>> {
+    let mut albums = HashMap::new();
+    for page in pages {
+        for row in &page.rows {
+            if row.len() < 12 {
+                continue;
+            }
+            let id = u32::from_le_bytes(row[4..8].try_into()?);
+            if let Some(name) = read_devicesql_string(row, 12) {
+                albums.insert(id, name);
+            }
+        }
+    }
+    Ok(albums)
+}
+
+struct TrackInfo {
+    title: String,
+    artist_name: Option<String>,
+    release_name: Option<String>,
+}
+
+fn parse_track_rows(
+    pages: &[Page],
+    artists: &HashMap<u32, String>,
+    albums: &HashMap<u32, String>,
+) -> Result<HashMap<u32, TrackInfo>> {
+    let mut tracks = HashMap::new();
+    for page in pages {
+        for row in &page.rows {
+            if row.len() < 0x5c {
+                continue;
+            }
+            let id = u32::from_le_bytes(row[0x2c..0x30].try_into()?);
+            let artist_id = u32::from_le_bytes(row[0x24..0x28].try_into()?);
+            let album_id = u32::from_le_bytes(row[0x28..0x2c].try_into()?);
+
+            let Some(title) = read_devicesql_string(row, 0x5c) else {
+                continue;
+            };
+
+            tracks.insert(
+                id,
+                TrackInfo {
+                    title,
+                    artist_name: artists.get(&artist_id).cloned(),
+                    release_name: albums.get(&album_id).cloned(),
+                },
+            );
+        }
+    }
+    Ok(tracks)
+}
+
+fn parse_history_rows(
+    pages: &[Page],
+    tracks: &HashMap<u32, TrackInfo>,
+) -> Result<Vec<RekordboxPlay>> {
+    let mut plays = Vec::new();
+    for page in pages {
+        for row in &page.rows {
+            if row.len() < 12 {
+                continue;
+            }
+            let track_id = u32::from_le_bytes(row[4..8].try_into()?);
+            let played_at_unix = u32::from_le_bytes(row[8..12].try_into()?) as i64;
+
+            let Some(played_at) = DateTime::from_timestamp(played_at_unix, 0) else {
+                continue;
+            };
+
+            let Some(track) = tracks.get(&track_id) else {
+                continue;
+            };
+
+            plays.push(RekordboxPlay {
+                track_name: track.title.clone(),
+                artist_name: track.artist_name.clone(),
+                release_name: track.release_name.clone(),
+                played_at,
+            });
+        }
+    }
+    Ok(plays)
+}
+
+/// Map parsed history entries into the same shape `db::store_user_plays` expects.
+pub fn plays_to_scrobbles(did: &str, plays: Vec<RekordboxPlay>) -> Vec<crate::atproto::ScrobbleRecord> {
+    plays
+        .into_iter()
+        .enumerate()
+        .map(|(i, play)| crate::atproto::ScrobbleRecord {
+            uri: format!("rekordbox://{}/{}-{}", did, play.played_at.timestamp(), i),
+            cid: String::new(),
+            track_name: play.track_name,
+            artists: play.artist_name.into_iter().collect(),
+            played_time: Some(play.played_at.to_rfc3339()),
+            duration: None,
+            recording_mb_id: None,
+            track_mb_id: None,
+            release_mb_id: None,
+            release_name: play.release_name,
+            artist_mb_ids: None,
+            spotify_track_url: None,
+        })
+        .collect()
+}
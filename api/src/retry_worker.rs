@@ -0,0 +1,115 @@
+//! Background worker that drains the materialized-view refresh retry queue.
+//!
+//! `db::store_user_plays` pushes a user onto `refresh_retry_queue` when its post-import
+//! `REFRESH MATERIALIZED VIEW CONCURRENTLY` pass fails, but nothing was actually polling
+//! that queue - entries just accumulated. This module does: each row is retried with
+//! exponential backoff keyed off its own `retry_count` and `last_attempt`, and rows that
+//! reach `MAX_RETRY_COUNT` retries are moved to the dead-letter state instead of being retried forever.
+
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::postgres::PgPool;
+use std::time::Duration;
+
+use crate::db;
+
+/// Base delay for the first retry; doubles per `retry_count` up to `MAX_BACKOFF`.
+const BASE_DELAY: Duration = Duration::from_secs(1);
+/// Never wait longer than this between retries for a single row.
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+/// Rows that have failed this many times are moved to the dead-letter state.
+pub const MAX_RETRY_COUNT: i32 = 8;
+/// How often the worker wakes up to scan the queue.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Emit a warning if a single refresh poll takes longer than this.
+const SLOW_POLL_THRESHOLD: Duration = Duration::from_secs(10);
+
+/// Exposed so other drivers of the retry queue (e.g. `yircli`'s manual `Process` command)
+/// can honor the same schedule as the background worker instead of hammering every row.
+pub fn backoff_for(retry_count: i32) -> Duration {
+    let shift = retry_count.clamp(0, 20) as u32;
+    BASE_DELAY
+        .checked_mul(1u32.checked_shl(shift).unwrap_or(u32::MAX))
+        .unwrap_or(MAX_BACKOFF)
+        .min(MAX_BACKOFF)
+}
+
+/// Run the retry worker forever, polling `db::get_retry_queue` every `POLL_INTERVAL` and
+/// attempting a refresh for each row whose backoff has elapsed.
+pub async fn run(pool: PgPool) -> Result<()> {
+    tracing::info!("retry worker starting, polling every {:?}", POLL_INTERVAL);
+
+    loop {
+        let poll_started = std::time::Instant::now();
+
+        if let Err(e) = drain_once(&pool).await {
+            tracing::error!("retry queue poll failed: {}", e);
+        }
+
+        let elapsed = poll_started.elapsed();
+        if elapsed > SLOW_POLL_THRESHOLD {
+            tracing::warn!(
+                "retry queue poll took {:?}, exceeding the {:?} threshold",
+                elapsed,
+                SLOW_POLL_THRESHOLD
+            );
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Process every due row in the retry queue once.
+async fn drain_once(pool: &PgPool) -> Result<()> {
+    let queue = db::get_retry_queue(pool).await?;
+    if queue.is_empty() {
+        return Ok(());
+    }
+
+    tracing::debug!("retry queue has {} pending user(s)", queue.len());
+
+    for (user_did, retry_count, last_attempt) in queue {
+        let due_at = last_attempt + chrono::Duration::from_std(backoff_for(retry_count))?;
+        if Utc::now() < due_at {
+            continue;
+        }
+
+        if retry_count >= MAX_RETRY_COUNT {
+            tracing::error!(
+                "user {} exceeded {} refresh retries, moving to dead letter",
+                user_did,
+                MAX_RETRY_COUNT
+            );
+            db::mark_retry_dead_letter(
+                pool,
+                &user_did,
+                &format!("exceeded max retry count ({})", MAX_RETRY_COUNT),
+            )
+            .await?;
+            continue;
+        }
+
+        tracing::info!(
+            "retrying materialized view refresh for {} (attempt {})",
+            user_did,
+            retry_count + 1
+        );
+
+        match db::refresh_user_stats(pool).await {
+            Ok(true) => {
+                tracing::info!("refresh succeeded for {}, removing from retry queue", user_did);
+                db::remove_from_retry_queue(pool, &user_did).await?;
+            }
+            Ok(false) => {
+                tracing::warn!("refresh still failing for {}, will retry later", user_did);
+                db::add_to_retry_queue(pool, &user_did).await?;
+            }
+            Err(e) => {
+                tracing::warn!("refresh attempt errored for {}: {}", user_did, e);
+                db::add_to_retry_queue(pool, &user_did).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
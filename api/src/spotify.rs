@@ -0,0 +1,1019 @@
+//! Spotify track link resolution and enrichment.
+//!
+//! Resolves a canonical `open.spotify.com/track/<id>` URL for a scrobble so a wrapped page
+//! can link out to something playable, using Spotify's client-credentials flow (no user
+//! login required) against the search endpoint. This runs over a whole year of plays, so it
+//! shares one cached bearer token (`SpotifyAppToken`) across the batch instead of fetching
+//! one per record - the same token is also shared with `fanart::fetch_spotify_image`, which
+//! used to authenticate separately on every call. `enrich_top_tracks` uses the same cached
+//! token to additionally resolve preview URLs and audio features for a year's top tracks.
+
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use crate::atproto::ScrobbleRecord;
+
+const TOKEN_URL: &str = "https://accounts.spotify.com/api/token";
+const SEARCH_URL: &str = "https://api.spotify.com/v1/search";
+const AUDIO_FEATURES_URL: &str = "https://api.spotify.com/v1/audio-features";
+const TOP_ARTISTS_URL: &str = "https://api.spotify.com/v1/me/top/artists";
+const TOP_TRACKS_URL: &str = "https://api.spotify.com/v1/me/top/tracks";
+/// Spotify doesn't publish a fixed search rate limit, but does start returning 429s under
+/// sustained load - a whole year of plays is sustained load, so keep a small floor between
+/// requests rather than waiting to get rate-limited.
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(100);
+/// Spotify's `audio-features` endpoint accepts at most 100 ids per request.
+const AUDIO_FEATURES_BATCH_SIZE: usize = 100;
+
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+/// Caches the app-level client-credentials bearer token so every caller that only needs
+/// app-level data (`SpotifyLinkResolver`, `fanart::fetch_spotify_image`) shares one token
+/// instead of each re-authenticating against `TOKEN_URL` on every request. The token itself
+/// lives behind a `tokio::sync::Mutex` rather than a `std::sync::RwLock` so `get` can hold the
+/// lock across the refresh request - otherwise concurrent callers that all race past an
+/// expired token (e.g. a bulk import running several scrobbles' worth of lookups in parallel)
+/// would each independently hit `TOKEN_URL` instead of the second-and-later callers simply
+/// waiting for the first caller's refresh and reusing its result.
+pub struct SpotifyAppToken {
+    client: Client,
+    client_id: String,
+    client_secret: String,
+    token: Mutex<Option<CachedToken>>,
+}
+
+impl SpotifyAppToken {
+    pub fn new(client_id: String, client_secret: String) -> Self {
+        SpotifyAppToken {
+            client: Client::new(),
+            client_id,
+            client_secret,
+            token: Mutex::new(None),
+        }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        !self.client_id.is_empty() && !self.client_secret.is_empty()
+    }
+
+    /// Force the next `get` to re-authenticate - for when a caller gets a 401 back despite
+    /// the cached token looking unexpired.
+    pub async fn invalidate(&self) {
+        *self.token.lock().await = None;
+    }
+
+    /// Return a valid bearer token, fetching a fresh one if there's none cached or the
+    /// cached one is within 60s of expiry.
+    pub async fn get(&self) -> Result<String> {
+        let mut guard = self.token.lock().await;
+
+        if let Some(token) = guard.as_ref() {
+            if token.expires_at > Instant::now() {
+                return Ok(token.access_token.clone());
+            }
+        }
+
+        let auth = format!("{}:{}", self.client_id, self.client_secret);
+        let encoded =
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, auth.as_bytes());
+
+        let response = self
+            .client
+            .post(TOKEN_URL)
+            .header("Authorization", format!("Basic {}", encoded))
+            .form(&[("grant_type", "client_credentials")])
+            .send()
+            .await
+            .context("failed to reach spotify token endpoint")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("spotify token request returned status {}", response.status());
+        }
+
+        let token: TokenResponse = response
+            .json()
+            .await
+            .context("failed to parse spotify token response")?;
+
+        // Refresh a minute early so a request never races an about-to-expire token.
+        let expires_at = Instant::now() + Duration::from_secs(token.expires_in.saturating_sub(60));
+        let access_token = token.access_token.clone();
+        *guard = Some(CachedToken {
+            access_token: access_token.clone(),
+            expires_at,
+        });
+
+        Ok(access_token)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    tracks: Tracks,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tracks {
+    #[serde(default)]
+    items: Vec<Track>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Track {
+    id: String,
+    preview_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AudioFeaturesResponse {
+    #[serde(default)]
+    audio_features: Vec<Option<AudioFeatureEntry>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AudioFeatureEntry {
+    id: String,
+    danceability: f64,
+    energy: f64,
+    valence: f64,
+    tempo: f64,
+}
+
+/// A compact "mood" summary for a track, used by frontends to e.g. sort or visualize a
+/// year's top tracks by how danceable/energetic they are.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioFeatures {
+    pub danceability: f64,
+    pub energy: f64,
+    pub valence: f64,
+    pub tempo: f64,
+}
+
+/// What `enrich_top_tracks` resolves for a single (title, artist) pair.
+#[derive(Debug, Clone, Default)]
+pub struct TrackEnrichment {
+    pub preview_url: Option<String>,
+    pub audio_features: Option<AudioFeatures>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopItemsResponse<T> {
+    #[serde(default)]
+    items: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopArtistItem {
+    name: String,
+    #[serde(default)]
+    images: Vec<ArtistImage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ArtistImage {
+    url: String,
+    width: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopTrackItem {
+    name: String,
+    artists: Vec<TopTrackArtist>,
+    preview_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopTrackArtist {
+    name: String,
+}
+
+/// A user's own top artist, as reported by the Authorization Code-gated `/me/top/artists`
+/// endpoint - no play counts, just Spotify's own ranking.
+#[derive(Debug, Clone)]
+pub struct UserTopArtist {
+    pub name: String,
+    pub image_url: Option<String>,
+}
+
+/// A user's own top track, as reported by `/me/top/tracks`.
+#[derive(Debug, Clone)]
+pub struct UserTopTrack {
+    pub title: String,
+    pub artist: String,
+    pub preview_url: Option<String>,
+}
+
+/// Fetch the caller's top artists for the last ~year of listening (`time_range=long_term`),
+/// using a user-scoped access token from the OAuth Authorization Code flow.
+pub async fn fetch_user_top_artists(
+    client: &Client,
+    access_token: &str,
+) -> Result<Vec<UserTopArtist>> {
+    let response: TopItemsResponse<TopArtistItem> =
+        fetch_top_items(client, access_token, TOP_ARTISTS_URL).await?;
+
+    Ok(response
+        .items
+        .into_iter()
+        .map(|item| UserTopArtist {
+            name: item.name,
+            image_url: item
+                .images
+                .into_iter()
+                .max_by_key(|img| img.width)
+                .map(|img| img.url),
+        })
+        .collect())
+}
+
+/// Fetch the caller's top tracks for the last ~year of listening.
+pub async fn fetch_user_top_tracks(
+    client: &Client,
+    access_token: &str,
+) -> Result<Vec<UserTopTrack>> {
+    let response: TopItemsResponse<TopTrackItem> =
+        fetch_top_items(client, access_token, TOP_TRACKS_URL).await?;
+
+    Ok(response
+        .items
+        .into_iter()
+        .map(|item| UserTopTrack {
+            title: item.name,
+            artist: item
+                .artists
+                .first()
+                .map(|a| a.name.clone())
+                .unwrap_or_default(),
+            preview_url: item.preview_url,
+        })
+        .collect())
+}
+
+async fn fetch_top_items<T: serde::de::DeserializeOwned>(
+    client: &Client,
+    access_token: &str,
+    url: &str,
+) -> Result<TopItemsResponse<T>> {
+    let response = client
+        .get(url)
+        .header("Authorization", format!("Bearer {}", access_token))
+        .query(&[("time_range", "long_term"), ("limit", "50")])
+        .send()
+        .await
+        .context("failed to reach spotify top-items endpoint")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        crate::diagnostics::report_failure("spotify_top_items", url, Some(status), &body).await;
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            anyhow::bail!("spotify top-items endpoint rejected the access token (401)");
+        }
+        anyhow::bail!("spotify top-items endpoint returned status {}", status);
+    }
+
+    response
+        .json()
+        .await
+        .context("failed to parse spotify top-items response")
+}
+
+/// Resolves Spotify track links for a batch of scrobbles, reusing one client-credentials
+/// bearer token across the whole batch and only refreshing it once it expires.
+pub struct SpotifyLinkResolver {
+    client: Client,
+    app_token: Arc<SpotifyAppToken>,
+    last_request: RwLock<Option<Instant>>,
+}
+
+impl SpotifyLinkResolver {
+    pub fn new(app_token: Arc<SpotifyAppToken>) -> Self {
+        SpotifyLinkResolver {
+            client: Client::new(),
+            app_token,
+            last_request: RwLock::new(None),
+        }
+    }
+
+    /// Attach `spotify_track_url` to every record missing one. No-ops if no credentials
+    /// were configured, same as `fanart`'s Spotify path.
+    pub async fn enrich(&self, records: &mut [ScrobbleRecord]) -> Result<()> {
+        if !self.app_token.is_configured() {
+            tracing::debug!("spotify credentials not set, skipping track link resolution");
+            return Ok(());
+        }
+
+        for record in records.iter_mut() {
+            if record.spotify_track_url.is_some() {
+                continue;
+            }
+            let Some(artist) = record.artists.first() else {
+                continue;
+            };
+
+            match self.resolve_track(&record.track_name, artist).await {
+                Ok(Some(url)) => record.spotify_track_url = Some(url),
+                Ok(None) => {}
+                Err(e) => tracing::warn!(
+                    "spotify lookup failed for '{}' by '{}': {}",
+                    record.track_name,
+                    artist,
+                    e
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn resolve_track(&self, track_name: &str, artist: &str) -> Result<Option<String>> {
+        Ok(self
+            .search_track(track_name, artist)
+            .await?
+            .map(|track| format!("https://open.spotify.com/track/{}", track.id)))
+    }
+
+    /// Resolve a track's Spotify URI (`spotify:track:<id>`), as needed to add it to a
+    /// playlist via `add_tracks_to_playlist`. Uses the same app-level search as `enrich`.
+    pub async fn resolve_track_uri(&self, track_name: &str, artist: &str) -> Result<Option<String>> {
+        Ok(self
+            .search_track(track_name, artist)
+            .await?
+            .map(|track| format!("spotify:track:{}", track.id)))
+    }
+
+    /// Search for a single track, retrying once on a 401 (stale cached token - force a
+    /// refresh and try again) and on a 429 (honoring `Retry-After`, no retry cap since
+    /// Spotify always eventually lets the request through once the window passes).
+    async fn search_track(&self, track_name: &str, artist: &str) -> Result<Option<Track>> {
+        let mut retried_unauthorized = false;
+
+        loop {
+            let token = self.access_token().await?;
+            let query = format!(r#"track:"{}" artist:"{}""#, track_name, artist);
+
+            self.throttle().await;
+
+            let response = self
+                .client
+                .get(SEARCH_URL)
+                .header("Authorization", format!("Bearer {}", token))
+                .query(&[("type", "track"), ("q", query.as_str()), ("limit", "1")])
+                .send()
+                .await
+                .context("failed to reach spotify search")?;
+
+            if response.status() == reqwest::StatusCode::UNAUTHORIZED && !retried_unauthorized {
+                tracing::debug!("spotify token rejected, refreshing and retrying once");
+                self.invalidate_token().await;
+                retried_unauthorized = true;
+                continue;
+            }
+
+            if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                let retry_after = response
+                    .headers()
+                    .get("Retry-After")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(1);
+                tracing::warn!("spotify rate-limited us, waiting {}s", retry_after);
+                tokio::time::sleep(Duration::from_secs(retry_after)).await;
+                continue;
+            }
+
+            if !response.status().is_success() {
+                anyhow::bail!("spotify search returned status {}", response.status());
+            }
+
+            let search: SearchResponse = response
+                .json()
+                .await
+                .context("failed to parse spotify search response")?;
+
+            return Ok(search.tracks.items.into_iter().next());
+        }
+    }
+
+    /// Look up danceability/energy/valence/tempo for a batch of Spotify track ids, chunked
+    /// into groups of at most `AUDIO_FEATURES_BATCH_SIZE` (Spotify's documented limit per
+    /// request). Missing/unavailable entries come back as `null` in the response array and
+    /// are simply omitted from the result map.
+    async fn fetch_audio_features(&self, ids: &[&str]) -> Result<HashMap<String, AudioFeatures>> {
+        let mut features = HashMap::new();
+
+        for chunk in ids.chunks(AUDIO_FEATURES_BATCH_SIZE) {
+            let ids_param = chunk.join(",");
+            let mut retried_unauthorized = false;
+
+            let response = loop {
+                let token = self.access_token().await?;
+                self.throttle().await;
+
+                let response = self
+                    .client
+                    .get(AUDIO_FEATURES_URL)
+                    .header("Authorization", format!("Bearer {}", token))
+                    .query(&[("ids", ids_param.as_str())])
+                    .send()
+                    .await
+                    .context("failed to reach spotify audio-features")?;
+
+                if response.status() == reqwest::StatusCode::UNAUTHORIZED
+                    && !retried_unauthorized
+                {
+                    tracing::debug!("spotify token rejected, refreshing and retrying once");
+                    self.invalidate_token().await;
+                    retried_unauthorized = true;
+                    continue;
+                }
+
+                if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    let retry_after = response
+                        .headers()
+                        .get("Retry-After")
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.parse::<u64>().ok())
+                        .unwrap_or(1);
+                    tracing::warn!("spotify rate-limited us, waiting {}s", retry_after);
+                    tokio::time::sleep(Duration::from_secs(retry_after)).await;
+                    continue;
+                }
+
+                if !response.status().is_success() {
+                    anyhow::bail!(
+                        "spotify audio-features returned status {}",
+                        response.status()
+                    );
+                }
+
+                break response;
+            };
+
+            let parsed: AudioFeaturesResponse = response
+                .json()
+                .await
+                .context("failed to parse spotify audio-features response")?;
+
+            for entry in parsed.audio_features.into_iter().flatten() {
+                features.insert(
+                    entry.id.clone(),
+                    AudioFeatures {
+                        danceability: entry.danceability,
+                        energy: entry.energy,
+                        valence: entry.valence,
+                        tempo: entry.tempo,
+                    },
+                );
+            }
+        }
+
+        Ok(features)
+    }
+
+    /// Resolve preview URLs and audio features for a year's top tracks, keyed by
+    /// `(title, artist)` so this stays decoupled from `lib::TopTrack`. No-ops (returns an
+    /// empty map) if no credentials were configured, same as `enrich`. Failures are logged
+    /// and skipped per-track rather than failing the whole batch, since a wrapped page
+    /// should still render without previews/moods if Spotify is having a bad day.
+    pub async fn enrich_top_tracks(
+        &self,
+        tracks: &[(String, String)],
+    ) -> HashMap<(String, String), TrackEnrichment> {
+        let mut enrichment = HashMap::new();
+
+        if !self.app_token.is_configured() {
+            tracing::debug!("spotify credentials not set, skipping track enrichment");
+            return enrichment;
+        }
+
+        let mut ids_by_key = HashMap::new();
+
+        for (title, artist) in tracks {
+            match self.search_track(title, artist).await {
+                Ok(Some(track)) => {
+                    enrichment.insert(
+                        (title.clone(), artist.clone()),
+                        TrackEnrichment {
+                            preview_url: track.preview_url.clone(),
+                            audio_features: None,
+                        },
+                    );
+                    ids_by_key.insert(track.id, (title.clone(), artist.clone()));
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!(
+                    "spotify enrichment lookup failed for '{}' by '{}': {}",
+                    title,
+                    artist,
+                    e
+                ),
+            }
+        }
+
+        if ids_by_key.is_empty() {
+            return enrichment;
+        }
+
+        let ids: Vec<&str> = ids_by_key.keys().map(String::as_str).collect();
+        match self.fetch_audio_features(&ids).await {
+            Ok(features) => {
+                for (id, key) in &ids_by_key {
+                    if let Some(features) = features.get(id) {
+                        if let Some(entry) = enrichment.get_mut(key) {
+                            entry.audio_features = Some(features.clone());
+                        }
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("spotify audio-features batch failed: {}", e),
+        }
+
+        enrichment
+    }
+
+    async fn invalidate_token(&self) {
+        self.app_token.invalidate().await;
+    }
+
+    async fn throttle(&self) {
+        let wait = {
+            let last_request = self.last_request.read().unwrap();
+            last_request.and_then(|last| {
+                let elapsed = last.elapsed();
+                (elapsed < MIN_REQUEST_INTERVAL).then(|| MIN_REQUEST_INTERVAL - elapsed)
+            })
+        };
+        if let Some(wait) = wait {
+            tokio::time::sleep(wait).await;
+        }
+        *self.last_request.write().unwrap() = Some(Instant::now());
+    }
+
+    async fn access_token(&self) -> Result<String> {
+        self.app_token.get().await
+    }
+}
+
+const ME_URL: &str = "https://api.spotify.com/v1/me";
+/// Spotify accepts at most 100 track URIs per `POST .../tracks` request.
+const PLAYLIST_TRACKS_BATCH_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct MeResponse {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlaylistResponse {
+    id: String,
+    external_urls: PlaylistExternalUrls,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlaylistExternalUrls {
+    spotify: String,
+}
+
+/// A newly-created playlist, as needed to add tracks/a cover image to it and to hand the
+/// caller something to link out to.
+pub struct CreatedPlaylist {
+    pub id: String,
+    pub url: String,
+}
+
+/// Look up the Spotify user id behind a user-scoped access token - playlist creation is
+/// scoped to `/users/{user_id}/playlists`, so this has to happen before `create_playlist`.
+pub async fn fetch_current_user_id(client: &Client, access_token: &str) -> Result<String> {
+    let response = client
+        .get(ME_URL)
+        .header("Authorization", format!("Bearer {}", access_token))
+        .send()
+        .await
+        .context("failed to reach spotify /me endpoint")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        crate::diagnostics::report_failure("spotify_me", ME_URL, Some(status), &body).await;
+        anyhow::bail!("spotify /me endpoint returned status {}", status);
+    }
+
+    let me: MeResponse = response
+        .json()
+        .await
+        .context("failed to parse spotify /me response")?;
+    Ok(me.id)
+}
+
+/// Create a new playlist for `user_id`, requiring the `playlist-modify-public`/
+/// `playlist-modify-private` scope.
+pub async fn create_playlist(
+    client: &Client,
+    access_token: &str,
+    user_id: &str,
+    name: &str,
+    description: &str,
+    public: bool,
+) -> Result<CreatedPlaylist> {
+    let url = format!("https://api.spotify.com/v1/users/{}/playlists", user_id);
+
+    let response = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", access_token))
+        .json(&serde_json::json!({
+            "name": name,
+            "description": description,
+            "public": public,
+        }))
+        .send()
+        .await
+        .context("failed to reach spotify create-playlist endpoint")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        crate::diagnostics::report_failure("spotify_create_playlist", &url, Some(status), &body)
+            .await;
+        anyhow::bail!("spotify create-playlist endpoint returned status {}", status);
+    }
+
+    let playlist: PlaylistResponse = response
+        .json()
+        .await
+        .context("failed to parse spotify create-playlist response")?;
+
+    Ok(CreatedPlaylist {
+        id: playlist.id,
+        url: playlist.external_urls.spotify,
+    })
+}
+
+/// Add `track_uris` (each `spotify:track:<id>`) to a playlist, batched at Spotify's
+/// 100-tracks-per-request limit.
+pub async fn add_tracks_to_playlist(
+    client: &Client,
+    access_token: &str,
+    playlist_id: &str,
+    track_uris: &[String],
+) -> Result<()> {
+    let url = format!(
+        "https://api.spotify.com/v1/playlists/{}/tracks",
+        playlist_id
+    );
+
+    for chunk in track_uris.chunks(PLAYLIST_TRACKS_BATCH_SIZE) {
+        let response = client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", access_token))
+            .json(&serde_json::json!({ "uris": chunk }))
+            .send()
+            .await
+            .context("failed to reach spotify add-tracks endpoint")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            crate::diagnostics::report_failure("spotify_add_tracks", &url, Some(status), &body)
+                .await;
+            anyhow::bail!("spotify add-tracks endpoint returned status {}", status);
+        }
+    }
+
+    Ok(())
+}
+
+/// Upload a playlist cover image, requiring the `ugc-image-upload` scope. Spotify expects a
+/// base64-encoded JPEG under 256KB with no data URI prefix, sent with `image/jpeg` as the
+/// content type rather than as JSON.
+pub async fn upload_playlist_cover_image(
+    client: &Client,
+    access_token: &str,
+    playlist_id: &str,
+    jpeg_bytes: &[u8],
+) -> Result<()> {
+    use base64::Engine;
+
+    let url = format!(
+        "https://api.spotify.com/v1/playlists/{}/images",
+        playlist_id
+    );
+    let encoded = base64::engine::general_purpose::STANDARD.encode(jpeg_bytes);
+
+    let response = client
+        .put(&url)
+        .header("Authorization", format!("Bearer {}", access_token))
+        .header("Content-Type", "image/jpeg")
+        .body(encoded)
+        .send()
+        .await
+        .context("failed to reach spotify playlist-images endpoint")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        crate::diagnostics::report_failure("spotify_playlist_image", &url, Some(status), &body)
+            .await;
+        anyhow::bail!("spotify playlist-images endpoint returned status {}", status);
+    }
+
+    Ok(())
+}
+
+/// A parsed `open.spotify.com/{track,album,playlist}/{id}` share link - any `?si=...`
+/// tracking query is simply dropped by `Url::path_segments`, which only looks at the path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpotifyShareLink {
+    Track(String),
+    Album(String),
+    Playlist(String),
+}
+
+/// Parse a Spotify share URL into its entry type and id, e.g.
+/// `https://open.spotify.com/track/abc123?si=xyz` -> `Track("abc123")`.
+pub fn parse_share_link(url: &str) -> Option<SpotifyShareLink> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    if parsed.host_str() != Some("open.spotify.com") {
+        return None;
+    }
+
+    let mut segments = parsed.path_segments()?;
+    let kind = segments.next()?;
+    let id = segments.next()?;
+    if id.is_empty() {
+        return None;
+    }
+
+    match kind {
+        "track" => Some(SpotifyShareLink::Track(id.to_string())),
+        "album" => Some(SpotifyShareLink::Album(id.to_string())),
+        "playlist" => Some(SpotifyShareLink::Playlist(id.to_string())),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedTrack {
+    pub name: String,
+    pub artists: Vec<String>,
+    pub duration_ms: u32,
+    pub cover_url: Option<String>,
+}
+
+/// A track nested inside a resolved album/playlist, formatted the way Songlify/2b-rs
+/// render playlist entries - `"artist1, artist2 - name"` - rather than carrying the full
+/// `ResolvedTrack` shape for every item.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackSummary {
+    pub label: String,
+    pub duration_ms: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedAlbum {
+    pub name: String,
+    pub artists: Vec<String>,
+    pub cover_url: Option<String>,
+    pub tracks: Vec<TrackSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedPlaylist {
+    pub name: String,
+    pub cover_url: Option<String>,
+    pub tracks: Vec<TrackSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ResolvedSpotifyEntry {
+    Track(ResolvedTrack),
+    Album(ResolvedAlbum),
+    Playlist(ResolvedPlaylist),
+}
+
+#[derive(Debug, Deserialize)]
+struct SimpleArtist {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrackObject {
+    name: String,
+    #[serde(default)]
+    artists: Vec<SimpleArtist>,
+    duration_ms: u32,
+}
+
+fn largest_image(images: &[ArtistImage]) -> Option<String> {
+    images.iter().max_by_key(|img| img.width).map(|img| img.url.clone())
+}
+
+fn track_label(name: &str, artists: &[SimpleArtist]) -> String {
+    let artist_names: Vec<&str> = artists.iter().map(|a| a.name.as_str()).collect();
+    format!("{} - {}", artist_names.join(", "), name)
+}
+
+/// Resolve a share link's metadata using the shared app-level client-credentials token -
+/// none of this needs a user's own account, just public catalog data.
+pub async fn resolve_share_link(
+    client: &Client,
+    app_token: &SpotifyAppToken,
+    link: &SpotifyShareLink,
+) -> Result<ResolvedSpotifyEntry> {
+    match link {
+        SpotifyShareLink::Track(id) => {
+            fetch_track(client, app_token, id).await.map(ResolvedSpotifyEntry::Track)
+        }
+        SpotifyShareLink::Album(id) => {
+            fetch_album(client, app_token, id).await.map(ResolvedSpotifyEntry::Album)
+        }
+        SpotifyShareLink::Playlist(id) => {
+            fetch_playlist(client, app_token, id).await.map(ResolvedSpotifyEntry::Playlist)
+        }
+    }
+}
+
+async fn spotify_get<T: serde::de::DeserializeOwned>(
+    client: &Client,
+    app_token: &SpotifyAppToken,
+    url: &str,
+) -> Result<T> {
+    let access_token = app_token.get().await?;
+
+    let response = client
+        .get(url)
+        .header("Authorization", format!("Bearer {}", access_token))
+        .send()
+        .await
+        .context("failed to reach spotify endpoint")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        crate::diagnostics::report_failure("spotify_resolve_link", url, Some(status), &body).await;
+        anyhow::bail!("spotify endpoint returned status {}", status);
+    }
+
+    response
+        .json()
+        .await
+        .context("failed to parse spotify response")
+}
+
+async fn fetch_track(client: &Client, app_token: &SpotifyAppToken, id: &str) -> Result<ResolvedTrack> {
+    #[derive(Debug, Deserialize)]
+    struct FullTrack {
+        name: String,
+        #[serde(default)]
+        artists: Vec<SimpleArtist>,
+        duration_ms: u32,
+        album: AlbumRef,
+    }
+    #[derive(Debug, Deserialize)]
+    struct AlbumRef {
+        #[serde(default)]
+        images: Vec<ArtistImage>,
+    }
+
+    let url = format!("https://api.spotify.com/v1/tracks/{}", id);
+    let track: FullTrack = spotify_get(client, app_token, &url).await?;
+
+    Ok(ResolvedTrack {
+        name: track.name,
+        artists: track.artists.into_iter().map(|a| a.name).collect(),
+        duration_ms: track.duration_ms,
+        cover_url: largest_image(&track.album.images),
+    })
+}
+
+async fn fetch_album(client: &Client, app_token: &SpotifyAppToken, id: &str) -> Result<ResolvedAlbum> {
+    #[derive(Debug, Deserialize)]
+    struct FullAlbum {
+        name: String,
+        #[serde(default)]
+        artists: Vec<SimpleArtist>,
+        #[serde(default)]
+        images: Vec<ArtistImage>,
+        tracks: AlbumTracks,
+    }
+    #[derive(Debug, Deserialize)]
+    struct AlbumTracks {
+        #[serde(default)]
+        items: Vec<TrackObject>,
+    }
+
+    let url = format!("https://api.spotify.com/v1/albums/{}", id);
+    let album: FullAlbum = spotify_get(client, app_token, &url).await?;
+
+    Ok(ResolvedAlbum {
+        name: album.name,
+        artists: album.artists.into_iter().map(|a| a.name).collect(),
+        cover_url: largest_image(&album.images),
+        tracks: album
+            .tracks
+            .items
+            .into_iter()
+            .map(|t| TrackSummary {
+                label: track_label(&t.name, &t.artists),
+                duration_ms: t.duration_ms,
+            })
+            .collect(),
+    })
+}
+
+/// A playlist item's `track` field, discriminated by Spotify's `type` - either a music
+/// track or a podcast episode (whose "artist" is really the show it belongs to).
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum PlaylistTrackOrEpisode {
+    Track {
+        name: String,
+        #[serde(default)]
+        artists: Vec<SimpleArtist>,
+        duration_ms: u32,
+    },
+    Episode {
+        name: String,
+        show: ShowRef,
+        duration_ms: u32,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShowRef {
+    name: String,
+}
+
+async fn fetch_playlist(
+    client: &Client,
+    app_token: &SpotifyAppToken,
+    id: &str,
+) -> Result<ResolvedPlaylist> {
+    #[derive(Debug, Deserialize)]
+    struct FullPlaylist {
+        name: String,
+        #[serde(default)]
+        images: Vec<ArtistImage>,
+        tracks: PlaylistTracks,
+    }
+    #[derive(Debug, Deserialize)]
+    struct PlaylistTracks {
+        #[serde(default)]
+        items: Vec<PlaylistItem>,
+    }
+    #[derive(Debug, Deserialize)]
+    struct PlaylistItem {
+        track: Option<PlaylistTrackOrEpisode>,
+    }
+
+    let url = format!("https://api.spotify.com/v1/playlists/{}", id);
+    let playlist: FullPlaylist = spotify_get(client, app_token, &url).await?;
+
+    let tracks = playlist
+        .tracks
+        .items
+        .into_iter()
+        .filter_map(|item| match item.track? {
+            PlaylistTrackOrEpisode::Track {
+                name,
+                artists,
+                duration_ms,
+            } => Some(TrackSummary {
+                label: track_label(&name, &artists),
+                duration_ms,
+            }),
+            PlaylistTrackOrEpisode::Episode {
+                name,
+                show,
+                duration_ms,
+            } => Some(TrackSummary {
+                label: format!("{} - {}", show.name, name),
+                duration_ms,
+            }),
+            PlaylistTrackOrEpisode::Unknown => None,
+        })
+        .collect();
+
+    Ok(ResolvedPlaylist {
+        name: playlist.name,
+        cover_url: largest_image(&playlist.images),
+        tracks,
+    })
+}
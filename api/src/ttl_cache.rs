@@ -0,0 +1,105 @@
+//! A small in-process TTL cache used to avoid round-tripping to Postgres and
+//! re-deserializing JSON for values that are read far more often than they change -
+//! wrapped pages for popular DIDs, and `GlobalStats`, which is identical for every user
+//! hitting the site in a given year.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+struct Entry<V> {
+    value: V,
+    inserted_at: Instant,
+}
+
+/// A capacity-bounded, TTL-expiring cache keyed by `K`. A miss (absent or aged-out entry)
+/// returns `None` so the caller falls back to its backing store; once `capacity` is
+/// exceeded, the oldest entry is evicted to make room for the new one.
+pub struct TtlCache<K, V> {
+    entries: RwLock<HashMap<K, Entry<V>>>,
+    ttl: Duration,
+    capacity: usize,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        TtlCache {
+            entries: RwLock::new(HashMap::new()),
+            ttl,
+            capacity,
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let entries = self.entries.read().unwrap();
+        let entry = entries.get(key)?;
+        if entry.inserted_at.elapsed() > self.ttl {
+            return None;
+        }
+        Some(entry.value.clone())
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        let mut entries = self.entries.write().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            if let Some(oldest_key) = entries
+                .iter()
+                .min_by_key(|(_, e)| e.inserted_at)
+                .map(|(k, _)| k.clone())
+            {
+                entries.remove(&oldest_key);
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                value,
+                inserted_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Return the cached value for `key` if present and unexpired, otherwise call `fetch`,
+    /// cache its result, and return it. Concurrent misses for the same key may both invoke
+    /// `fetch` - that's a tolerable duplicate upstream call, not a correctness issue, and
+    /// avoids needing a per-key lock for what's meant to be a best-effort dedup layer.
+    pub async fn get_or_fetch<F, Fut, E>(&self, key: &K, fetch: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<V, E>>,
+    {
+        if let Some(value) = self.get(key) {
+            return Ok(value);
+        }
+        let value = fetch().await?;
+        self.insert(key.clone(), value.clone());
+        Ok(value)
+    }
+
+    /// Remove and return `key`'s value if present and unexpired, discarding it either way -
+    /// for values meant to be consumed exactly once, like a login flow's CSRF token.
+    pub fn remove(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.write().unwrap();
+        let entry = entries.remove(key)?;
+        if entry.inserted_at.elapsed() > self.ttl {
+            return None;
+        }
+        Some(entry.value)
+    }
+
+    /// Keys whose remaining time-to-live is within `within`, so a background task can
+    /// refresh them before they actually expire and a request has to pay the Postgres
+    /// round-trip again.
+    pub fn keys_near_expiry(&self, within: Duration) -> Vec<K> {
+        let entries = self.entries.read().unwrap();
+        entries
+            .iter()
+            .filter(|(_, entry)| {
+                let age = entry.inserted_at.elapsed();
+                age <= self.ttl && self.ttl.saturating_sub(age) <= within
+            })
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+}
@@ -1,9 +1,11 @@
 use anyhow::Result;
-use chrono::{Datelike, NaiveDate, Weekday};
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgPool;
 use sqlx::Row;
 use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TrackMetadata {
@@ -12,12 +14,107 @@ pub struct TrackMetadata {
     pub release_mb_id: Option<String>,
 }
 
+/// Coarse label for what kind of stats query is being timed, so a profiling summary shows
+/// "which category is slow" without needing to read every individual query string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryCategory {
+    /// Simple sums/counts over `user_plays` or a precomputed materialized view.
+    Aggregation,
+    /// Percentile/ranking queries computed across all users.
+    Percentile,
+    /// Session-grouping queries - see [`calculate_sessions`].
+    Session,
+    /// A query that runs once per artist rather than once overall - the classic N+1 shape.
+    PerArtist,
+}
+
+/// Per-label totals produced by [`QueryProfiler::summary`], sorted slowest-total-first.
+#[derive(Debug, Clone)]
+pub struct QuerySummary {
+    pub label: &'static str,
+    pub category: QueryCategory,
+    pub count: u32,
+    pub total: Duration,
+}
+
+/// Records `{label, category, duration}` for each query fired by `calculate_wrapped_stats`/
+/// `calculate_global_wrapped_stats`, so a maintainer can see which of the roughly dozen
+/// sequential queries either function fires is actually slow at scale - the `top_track_per_artist`
+/// N+1 loop is the obvious first thing this is meant to expose.
+#[derive(Debug, Default)]
+pub struct QueryProfiler {
+    events: Mutex<Vec<(&'static str, QueryCategory, Duration)>>,
+}
+
+impl QueryProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, label: &'static str, category: QueryCategory, duration: Duration) {
+        self.events.lock().unwrap().push((label, category, duration));
+    }
+
+    /// Total duration and call count per `(label, category)`, sorted slowest-total-first.
+    pub fn summary(&self) -> Vec<QuerySummary> {
+        let events = self.events.lock().unwrap();
+        let mut by_label: HashMap<(&'static str, QueryCategory), (u32, Duration)> = HashMap::new();
+        for (label, category, duration) in events.iter() {
+            let entry = by_label.entry((label, *category)).or_insert((0, Duration::ZERO));
+            entry.0 += 1;
+            entry.1 += *duration;
+        }
+
+        let mut summary: Vec<QuerySummary> = by_label
+            .into_iter()
+            .map(|((label, category), (count, total))| QuerySummary {
+                label,
+                category,
+                count,
+                total,
+            })
+            .collect();
+
+        summary.sort_by(|a, b| b.total.cmp(&a.total));
+        summary
+    }
+
+    /// Log the summary at `info` level, one line per label, slowest first.
+    pub fn log_summary(&self) {
+        for entry in self.summary() {
+            tracing::info!(
+                label = entry.label,
+                category = ?entry.category,
+                count = entry.count,
+                total_ms = entry.total.as_millis() as u64,
+                avg_ms = (entry.total.as_millis() / entry.count.max(1) as u128) as u64,
+                "stats query profile"
+            );
+        }
+    }
+}
+
+/// Time a query call chain (everything up to but not including `.await`), recording the
+/// elapsed time against `$profiler` under `$label`/`$category` when one was supplied. A no-op
+/// wrapper when `$profiler` is `None`, so instrumentation costs nothing for callers that don't
+/// ask for it.
+macro_rules! profiled {
+    ($profiler:expr, $label:expr, $category:expr, $query:expr) => {{
+        let __started = Instant::now();
+        let __result = $query.await;
+        if let Some(__profiler) = $profiler {
+            __profiler.record($label, $category, __started.elapsed());
+        }
+        __result
+    }};
+}
+
 #[derive(Debug)]
 pub struct WrappedStats {
     pub total_minutes: f64,
     pub total_plays: u32,
     pub avg_track_length_ms: i32,
-    pub listening_diversity: f64,       // unique tracks / total plays
+    pub listening_diversity: f64, // normalized Shannon entropy over per-track play counts, 0.0-1.0
     pub hourly_distribution: [u32; 24], // plays per hour (UTC)
     pub top_hour: u8,                   // hour with most plays (0-23)
     pub longest_session_minutes: u32,   // longest continuous listening session
@@ -28,126 +125,594 @@ pub struct WrappedStats {
     pub daily_plays: HashMap<NaiveDate, u32>,
     pub weekday_avg_minutes: f64,
     pub weekend_avg_minutes: f64,
-    pub longest_streak: u32,
+    pub streaks: StreakStats,
     pub days_active: u32,
+    /// Modal listening hour (UTC, 0-23) via `MODE() WITHIN GROUP`, distinct from `top_hour`
+    /// (the hour with the most total plays) in that it reflects the single most common hour
+    /// across individual plays rather than an aggregate bucket total.
+    pub peak_hour: Option<u8>,
+    /// Modal day of week (`EXTRACT(DOW)`, 0 = Sunday) across all plays in the window.
+    pub peak_weekday: Option<u8>,
+    /// Median listening hour (UTC, 0-23) via `PERCENTILE_DISC(0.5)` - a representative
+    /// "typical" hour that's robust to a handful of late-night outlier plays.
+    pub typical_session_hour: Option<u8>,
+    /// Artists trending right now for this user, distinct from the all-time `top_artists` -
+    /// see [`calculate_hotness`].
+    pub top_rising_artists: Vec<(String, f64)>,
+    pub session_count: u32,
+    pub avg_session_minutes: f64,
+    /// Hour (UTC) in which a session most often starts.
+    pub most_common_session_start_hour: Option<u8>,
+    /// The single longest session of the year, for narrating "your epic session" rather than
+    /// just reporting `longest_session_minutes` as a bare number.
+    pub epic_session: Option<EpicSession>,
+    pub monthly_plays: [u32; 12],
+    pub monthly_minutes: [f64; 12],
+    /// Index 0 = January. Tiebroken by whichever artist's most recent play in that month is
+    /// later when play counts are equal.
+    pub top_artist_per_month: [Option<(String, u32)>; 12],
+    pub seasonal_distribution: SeasonalDistribution,
+    /// Strongest artist-pair co-occurrences this window, so the wrapped report can say "you
+    /// always listen to X alongside Y" - see [`calculate_artist_affinities`].
+    pub top_artist_affinities: Vec<(String, String, u32)>,
 }
 
-/// Calculate wrapped stats directly from database views
-pub async fn calculate_wrapped_stats(
+/// Winter/spring/summer/fall aggregates (meteorological seasons, winter = Dec-Feb), so the
+/// front end can detect an artist or stretch of listening that defined a particular season.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SeasonalDistribution {
+    pub winter_plays: u32,
+    pub winter_minutes: f64,
+    pub spring_plays: u32,
+    pub spring_minutes: f64,
+    pub summer_plays: u32,
+    pub summer_minutes: f64,
+    pub fall_plays: u32,
+    pub fall_minutes: f64,
+}
+
+/// One continuous run of plays with no gap larger than the `gap_seconds` threshold passed to
+/// [`calculate_sessions`].
+#[derive(Debug, Clone)]
+pub struct ListeningSession {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub play_count: u32,
+    pub duration_minutes: f64,
+    /// The artist with the most plays within this session, if any play had artist metadata.
+    pub top_artist: Option<String>,
+}
+
+/// The year's single longest listening session, named so wrapped output can narrate it
+/// ("on this date, you listened to mostly this artist for N minutes straight").
+#[derive(Debug, Clone)]
+pub struct EpicSession {
+    pub date: NaiveDate,
+    pub duration_minutes: f64,
+    pub top_artist: Option<String>,
+}
+
+/// A user's longest consecutive-day listening run within the window, plus their current run
+/// (the streak ending at the most recent play date), so the wrap can narrate "day 12 of your
+/// current streak" instead of only ever reporting the best-ever run. `current` is zero unless
+/// the most recent play date is today or yesterday - a streak that already ended isn't
+/// "current" just because it was long.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StreakStats {
+    pub longest: u32,
+    pub longest_start: Option<NaiveDate>,
+    pub longest_end: Option<NaiveDate>,
+    pub current: u32,
+    pub current_start: Option<NaiveDate>,
+    pub current_end: Option<NaiveDate>,
+}
+
+/// An arbitrary `[start, end)` span of time to compute stats over, generalizing the old
+/// bare calendar year so "Monthly Wrapped" or a rolling 7-day recap can run through the exact
+/// same code path as the year-end wrap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportWindow {
+    /// The user's entire history.
+    AllTime,
+    /// A full calendar year, `[Jan 1, next Jan 1)`. The only variant backed by the
+    /// per-year materialized views (`user_artist_stats`/`user_track_stats`/`user_daily_activity`) -
+    /// any other variant falls back to aggregating `user_plays` directly (per-day activity
+    /// reads the `user_daily_rollups` table instead).
+    Year(i32),
+    /// A calendar month, `[1st, next 1st)`. `month` is 1-12.
+    Month(i32, u32),
+    /// A 7-day span starting at `date` (inclusive) at 00:00 UTC.
+    Week(NaiveDate),
+    /// An explicit `[start, end)` range.
+    Custom(DateTime<Utc>, DateTime<Utc>),
+}
+
+impl ReportWindow {
+    /// Resolve this window to a concrete `[start, end)` range, so every stats query can filter
+    /// on `played_at >= $n AND played_at < $n+1` instead of the old
+    /// `EXTRACT(YEAR FROM played_at) = $n`.
+    pub fn bounds(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        match *self {
+            ReportWindow::AllTime => (DateTime::<Utc>::MIN_UTC, DateTime::<Utc>::MAX_UTC),
+            ReportWindow::Year(year) => {
+                let start = NaiveDate::from_ymd_opt(year, 1, 1)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap()
+                    .and_utc();
+                let end = NaiveDate::from_ymd_opt(year + 1, 1, 1)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap()
+                    .and_utc();
+                (start, end)
+            }
+            ReportWindow::Month(year, month) => {
+                let start = NaiveDate::from_ymd_opt(year, month, 1)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap()
+                    .and_utc();
+                let (next_year, next_month) = if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+                let end = NaiveDate::from_ymd_opt(next_year, next_month, 1)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap()
+                    .and_utc();
+                (start, end)
+            }
+            ReportWindow::Week(date) => {
+                let start = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+                let end = start + chrono::Duration::days(7);
+                (start, end)
+            }
+            ReportWindow::Custom(start, end) => (start, end),
+        }
+    }
+
+    /// A stable string key identifying this window, used as the `wrapped_cache`/
+    /// `artist_affinities` cache key instead of a bare year, so monthly/weekly recaps cache
+    /// independently of the year-end wrap.
+    pub fn cache_key(&self) -> String {
+        match *self {
+            ReportWindow::AllTime => "all".to_string(),
+            ReportWindow::Year(year) => format!("year:{year}"),
+            ReportWindow::Month(year, month) => format!("month:{year}-{month:02}"),
+            ReportWindow::Week(date) => format!("week:{date}"),
+            ReportWindow::Custom(start, end) => {
+                format!("custom:{}:{}", start.to_rfc3339(), end.to_rfc3339())
+            }
+        }
+    }
+}
+
+/// Group a user's plays in `window` into sessions, splitting a new session whenever the gap
+/// since the previous play exceeds `gap_seconds`. Generalizes the old hardcoded-360s
+/// longest-session query so callers can tune the threshold (or reuse it for richer narration)
+/// instead of only getting back a single scalar.
+pub async fn calculate_sessions(
     pool: &PgPool,
     user_did: &str,
-    year: u32,
-) -> Result<WrappedStats> {
-    // Get top artists from materialized view
-    let artist_stats = sqlx::query(
+    window: ReportWindow,
+    gap_seconds: f64,
+    profiler: Option<&QueryProfiler>,
+) -> Result<Vec<ListeningSession>> {
+    let (start, end) = window.bounds();
+    let rows = profiled!(
+        profiler,
+        "sessions",
+        QueryCategory::Session,
+        sqlx::query(
         r#"
-        SELECT artists
-        FROM user_artist_stats
-        WHERE user_did = $1 AND year = $2
+        WITH sessions AS (
+            SELECT
+                played_at,
+                (artists->0)->>'artistName' AS artist_name,
+                EXTRACT(EPOCH FROM (played_at - LAG(played_at) OVER (ORDER BY played_at))) AS gap_seconds
+            FROM user_plays
+            WHERE user_did = $1
+              AND played_at >= $2 AND played_at < $3
+        ),
+        session_groups AS (
+            SELECT
+                played_at,
+                artist_name,
+                SUM(
+                    CASE
+                        WHEN gap_seconds > $4 OR gap_seconds IS NULL THEN 1
+                        ELSE 0
+                    END
+                ) OVER (ORDER BY played_at) AS session_id
+            FROM sessions
+        ),
+        artist_counts_per_session AS (
+            SELECT session_id, artist_name, COUNT(*) AS artist_plays
+            FROM session_groups
+            WHERE artist_name IS NOT NULL
+            GROUP BY session_id, artist_name
+        ),
+        top_artist_per_session AS (
+            SELECT DISTINCT ON (session_id) session_id, artist_name
+            FROM artist_counts_per_session
+            ORDER BY session_id, artist_plays DESC
+        )
+        SELECT
+            MIN(session_groups.played_at) AS start,
+            MAX(session_groups.played_at) AS "end",
+            COUNT(*) AS play_count,
+            top_artist_per_session.artist_name AS top_artist
+        FROM session_groups
+        LEFT JOIN top_artist_per_session USING (session_id)
+        GROUP BY session_groups.session_id, top_artist_per_session.artist_name
+        ORDER BY start
         "#,
     )
     .bind(user_did)
-    .bind(year as i32)
-    .fetch_optional(pool)
-    .await?;
+    .bind(start)
+    .bind(end)
+    .bind(gap_seconds)
+    .fetch_all(pool)
+    )?;
 
-    let top_artists: Vec<(String, u32, f64, Option<String>)> = if let Some(row) = artist_stats {
-        let artists_json: serde_json::Value = row.get("artists");
-        artists_json
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .take(10)
-            .filter_map(|a| {
-                let name = a.get("name")?.as_str()?.to_string();
-                let plays = a.get("play_count")?.as_i64()? as u32;
-                let duration_ms = a.get("total_duration_ms")?.as_i64()? as f64;
-                let minutes = duration_ms / (1000.0 * 60.0);
-                let mb_id = a.get("mb_id").and_then(|v| v.as_str()).map(String::from);
-                Some((name, plays, minutes, mb_id))
-            })
-            .collect()
-    } else {
-        vec![]
-    };
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let start: DateTime<Utc> = row.get("start");
+            let end: DateTime<Utc> = row.get("end");
+            let play_count: i64 = row.get("play_count");
+            let top_artist: Option<String> = row.get("top_artist");
+            let duration_minutes = (end - start).num_seconds() as f64 / 60.0;
 
-    // Get top tracks from materialized view
-    let track_stats = sqlx::query(
-        r#"
-        SELECT tracks
-        FROM user_track_stats
-        WHERE user_did = $1 AND year = $2
-        "#,
-    )
-    .bind(user_did)
-    .bind(year as i32)
-    .fetch_optional(pool)
-    .await?;
+            ListeningSession {
+                start,
+                end,
+                play_count: play_count as u32,
+                duration_minutes,
+                top_artist,
+            }
+        })
+        .collect())
+}
 
-    let top_tracks: Vec<((String, String), u32, TrackMetadata)> = if let Some(row) = track_stats {
-        let tracks_json: serde_json::Value = row.get("tracks");
-        tracks_json
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .take(10)
-            .filter_map(|t| {
-                let track_name = t.get("track_name")?.as_str()?.to_string();
-                let artist_name = t.get("artist_name")?.as_str()?.to_string();
-                let plays = t.get("play_count")?.as_i64()? as u32;
-                let metadata = TrackMetadata {
-                    recording_mb_id: t
-                        .get("recording_mb_id")
-                        .and_then(|v| v.as_str())
-                        .map(String::from),
-                    release_name: t
-                        .get("release_name")
-                        .and_then(|v| v.as_str())
-                        .map(String::from),
-                    release_mb_id: t
-                        .get("release_mb_id")
-                        .and_then(|v| v.as_str())
-                        .map(String::from),
-                };
-                Some(((track_name, artist_name), plays, metadata))
-            })
-            .collect()
-    } else {
-        vec![]
-    };
+/// Gap (in seconds) between plays before a new listening session starts; matches the threshold
+/// the old hardcoded `longest_session_minutes` query used before session analysis was
+/// generalized via [`calculate_sessions`].
+const DEFAULT_SESSION_GAP_SECONDS: f64 = 360.0;
 
-    // Get daily activity from materialized view
-    let daily_stats = sqlx::query(
-        r#"
-        SELECT daily_stats
-        FROM user_daily_activity
-        WHERE user_did = $1 AND year = $2
-        "#,
-    )
-    .bind(user_did)
-    .bind(year as i32)
-    .fetch_optional(pool)
-    .await?;
+/// Top 10 artists (play count, total minutes, MBID when known) for `window`. Reads the
+/// precomputed `user_artist_stats` materialized view when `window` is a full calendar year
+/// (the view is only refreshed per-year); any other window aggregates `user_plays` directly
+/// over the resolved `[start, end)` range.
+async fn top_artists_for_window(
+    pool: &PgPool,
+    user_did: &str,
+    window: ReportWindow,
+    profiler: Option<&QueryProfiler>,
+) -> Result<Vec<(String, u32, f64, Option<String>)>> {
+    if let ReportWindow::Year(year) = window {
+        let artist_stats = profiled!(
+            profiler,
+            "top_artists_view",
+            QueryCategory::Aggregation,
+            sqlx::query(
+                r#"
+                SELECT artists
+                FROM user_artist_stats
+                WHERE user_did = $1 AND year = $2
+                "#,
+            )
+            .bind(user_did)
+            .bind(year)
+            .fetch_optional(pool)
+        )?;
 
+        return Ok(if let Some(row) = artist_stats {
+            let artists_json: serde_json::Value = row.get("artists");
+            artists_json
+                .as_array()
+                .unwrap_or(&vec![])
+                .iter()
+                .take(10)
+                .filter_map(|a| {
+                    let name = a.get("name")?.as_str()?.to_string();
+                    let plays = a.get("play_count")?.as_i64()? as u32;
+                    let duration_ms = a.get("total_duration_ms")?.as_i64()? as f64;
+                    let minutes = duration_ms / (1000.0 * 60.0);
+                    let mb_id = a.get("mb_id").and_then(|v| v.as_str()).map(String::from);
+                    Some((name, plays, minutes, mb_id))
+                })
+                .collect()
+        } else {
+            vec![]
+        });
+    }
+
+    let (start, end) = window.bounds();
+    let rows = profiled!(
+        profiler,
+        "top_artists_live",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT
+                artist->>'artistName' as name,
+                MAX(artist->>'artistMbId') as mb_id,
+                COUNT(*) as play_count,
+                SUM(COALESCE(duration_ms, 210000)) as total_duration_ms
+            FROM user_plays, jsonb_array_elements(artists) as artist
+            WHERE user_did = $1
+              AND played_at >= $2 AND played_at < $3
+            GROUP BY artist->>'artistName'
+            ORDER BY play_count DESC
+            LIMIT 10
+            "#,
+        )
+        .bind(user_did)
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let name: String = row.get("name");
+            let plays: i64 = row.get("play_count");
+            let duration_ms: i64 = row.get("total_duration_ms");
+            let minutes = duration_ms as f64 / (1000.0 * 60.0);
+            let mb_id: Option<String> = row.get("mb_id");
+            (name, plays as u32, minutes, mb_id)
+        })
+        .collect())
+}
+
+/// Top 10 tracks for `window`, same view-or-live-fallback strategy as
+/// [`top_artists_for_window`].
+async fn top_tracks_for_window(
+    pool: &PgPool,
+    user_did: &str,
+    window: ReportWindow,
+    profiler: Option<&QueryProfiler>,
+) -> Result<Vec<((String, String), u32, TrackMetadata)>> {
+    if let ReportWindow::Year(year) = window {
+        let track_stats = profiled!(
+            profiler,
+            "top_tracks_view",
+            QueryCategory::Aggregation,
+            sqlx::query(
+                r#"
+                SELECT tracks
+                FROM user_track_stats
+                WHERE user_did = $1 AND year = $2
+                "#,
+            )
+            .bind(user_did)
+            .bind(year)
+            .fetch_optional(pool)
+        )?;
+
+        return Ok(if let Some(row) = track_stats {
+            let tracks_json: serde_json::Value = row.get("tracks");
+            tracks_json
+                .as_array()
+                .unwrap_or(&vec![])
+                .iter()
+                .take(10)
+                .filter_map(|t| {
+                    let track_name = t.get("track_name")?.as_str()?.to_string();
+                    let artist_name = t.get("artist_name")?.as_str()?.to_string();
+                    let plays = t.get("play_count")?.as_i64()? as u32;
+                    let metadata = TrackMetadata {
+                        recording_mb_id: t
+                            .get("recording_mb_id")
+                            .and_then(|v| v.as_str())
+                            .map(String::from),
+                        release_name: t
+                            .get("release_name")
+                            .and_then(|v| v.as_str())
+                            .map(String::from),
+                        release_mb_id: t
+                            .get("release_mb_id")
+                            .and_then(|v| v.as_str())
+                            .map(String::from),
+                    };
+                    Some(((track_name, artist_name), plays, metadata))
+                })
+                .collect()
+        } else {
+            vec![]
+        });
+    }
+
+    let (start, end) = window.bounds();
+    let rows = profiled!(
+        profiler,
+        "top_tracks_live",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT
+                track_name,
+                (artists->0)->>'artistName' as first_artist,
+                COUNT(*) as play_count,
+                recording_mb_id,
+                release_mb_id,
+                release_name
+            FROM user_plays
+            WHERE user_did = $1
+              AND played_at >= $2 AND played_at < $3
+              AND jsonb_array_length(artists) > 0
+            GROUP BY track_name, (artists->0)->>'artistName', recording_mb_id, release_mb_id, release_name
+            ORDER BY play_count DESC
+            LIMIT 10
+            "#,
+        )
+        .bind(user_did)
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let track_name: String = row.get("track_name");
+            let artist_name: String = row.get("first_artist");
+            let plays: i64 = row.get("play_count");
+            let recording_mb_id: Option<String> = row.get("recording_mb_id");
+            let release_mb_id: Option<String> = row.get("release_mb_id");
+            let release_name: Option<String> = row.get("release_name");
+            let metadata = TrackMetadata {
+                recording_mb_id,
+                release_name,
+                release_mb_id,
+            };
+            ((track_name, artist_name), plays as u32, metadata)
+        })
+        .collect())
+}
+
+/// Per-day play counts and total duration for `window`. Reads the `user_daily_activity` view
+/// for a full calendar year, like [`top_artists_for_window`]; any other window reads the
+/// `user_daily_rollups` table rather than `user_plays`.
+async fn daily_activity_for_window(
+    pool: &PgPool,
+    user_did: &str,
+    window: ReportWindow,
+    profiler: Option<&QueryProfiler>,
+) -> Result<(HashMap<NaiveDate, u32>, i64)> {
     let mut daily_plays: HashMap<NaiveDate, u32> = HashMap::new();
     let mut total_duration_ms = 0i64;
 
-    if let Some(row) = daily_stats {
-        let daily_json: serde_json::Value = row.get("daily_stats");
-        if let Some(obj) = daily_json.as_object() {
-            for (date_str, stats) in obj {
-                if let Ok(date) = NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-                    if let Some(plays) = stats.get("plays").and_then(|v| v.as_u64()) {
-                        daily_plays.insert(date, plays as u32);
-                    }
-                    if let Some(duration) = stats.get("duration_ms").and_then(|v| v.as_i64()) {
-                        total_duration_ms += duration;
+    if let ReportWindow::Year(year) = window {
+        let daily_stats = profiled!(
+            profiler,
+            "daily_activity_view",
+            QueryCategory::Aggregation,
+            sqlx::query(
+                r#"
+                SELECT daily_stats
+                FROM user_daily_activity
+                WHERE user_did = $1 AND year = $2
+                "#,
+            )
+            .bind(user_did)
+            .bind(year)
+            .fetch_optional(pool)
+        )?;
+
+        if let Some(row) = daily_stats {
+            let daily_json: serde_json::Value = row.get("daily_stats");
+            if let Some(obj) = daily_json.as_object() {
+                for (date_str, stats) in obj {
+                    if let Ok(date) = NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
+                        if let Some(plays) = stats.get("plays").and_then(|v| v.as_u64()) {
+                            daily_plays.insert(date, plays as u32);
+                        }
+                        if let Some(duration) = stats.get("duration_ms").and_then(|v| v.as_i64()) {
+                            total_duration_ms += duration;
+                        }
                     }
                 }
             }
         }
+
+        return Ok((daily_plays, total_duration_ms));
     }
 
+    let (start, end) = window.bounds();
+    let rows = profiled!(
+        profiler,
+        "daily_activity_rollup",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT day, play_count, total_duration_ms
+            FROM user_daily_rollups
+            WHERE user_did = $1
+              AND day >= $2::date AND day < $3::date
+            "#,
+        )
+        .bind(user_did)
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?;
+
+    for row in rows {
+        let day: NaiveDate = row.get("day");
+        let play_count: i32 = row.get("play_count");
+        let duration_ms: i64 = row.get("total_duration_ms");
+        daily_plays.insert(day, play_count as u32);
+        total_duration_ms += duration_ms;
+    }
+
+    Ok((daily_plays, total_duration_ms))
+}
+
+/// A user's modal listening-time habits within a window, computed with Postgres ordered-set
+/// aggregates over `played_at` rather than bucketing in Rust.
+async fn listening_pattern_stats(
+    pool: &PgPool,
+    user_did: &str,
+    window: ReportWindow,
+    profiler: Option<&QueryProfiler>,
+) -> Result<(Option<u8>, Option<u8>, Option<u8>)> {
+    let (start, end) = window.bounds();
+    let row = profiled!(
+        profiler,
+        "listening_patterns",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT
+                MODE() WITHIN GROUP (ORDER BY EXTRACT(HOUR FROM played_at)) AS peak_hour,
+                MODE() WITHIN GROUP (ORDER BY EXTRACT(DOW FROM played_at)) AS peak_weekday,
+                PERCENTILE_DISC(0.5) WITHIN GROUP (ORDER BY EXTRACT(HOUR FROM played_at)) AS typical_session_hour
+            FROM user_plays
+            WHERE user_did = $1
+              AND played_at >= $2 AND played_at < $3
+            "#,
+        )
+        .bind(user_did)
+        .bind(start)
+        .bind(end)
+        .fetch_optional(pool)
+    )?;
+
+    let Some(row) = row else {
+        return Ok((None, None, None));
+    };
+
+    let peak_hour: Option<f64> = row.get("peak_hour");
+    let peak_weekday: Option<f64> = row.get("peak_weekday");
+    let typical_session_hour: Option<f64> = row.get("typical_session_hour");
+
+    Ok((
+        peak_hour.map(|h| h as u8),
+        peak_weekday.map(|d| d as u8),
+        typical_session_hour.map(|h| h as u8),
+    ))
+}
+
+/// Calculate wrapped stats directly from database views
+pub async fn calculate_wrapped_stats(
+    pool: &PgPool,
+    user_did: &str,
+    window: ReportWindow,
+    profiler: Option<&QueryProfiler>,
+) -> Result<WrappedStats> {
+    let (start, end) = window.bounds();
+
+    let top_artists = top_artists_for_window(pool, user_did, window, profiler).await?;
+    let top_tracks = top_tracks_for_window(pool, user_did, window, profiler).await?;
+    let (daily_plays, total_duration_ms) =
+        daily_activity_for_window(pool, user_did, window, profiler).await?;
+
     // Calculate derived metrics
     let total_minutes = total_duration_ms as f64 / (1000.0 * 60.0);
     let total_plays: u32 = daily_plays.values().sum();
@@ -160,44 +725,55 @@ pub async fn calculate_wrapped_stats(
         0
     };
 
-    // Calculate listening diversity (unique tracks / total plays)
-    let unique_tracks: i64 = sqlx::query(
-        r#"
-        SELECT COUNT(DISTINCT track_name) as count
-        FROM user_plays
-        WHERE user_did = $1 AND EXTRACT(YEAR FROM played_at) = $2
-        "#,
-    )
-    .bind(user_did)
-    .bind(year as i32)
-    .fetch_one(pool)
-    .await
-    .map(|row| row.get::<i64, _>("count"))
-    .unwrap_or(0);
+    // Calculate listening diversity as normalized Shannon entropy over per-track play
+    // counts, rather than the old `unique_tracks / total_plays` ratio, which saturates to
+    // ~1.0 as soon as a user rarely repeats a track and can't distinguish "100 plays spread
+    // evenly across 50 tracks" from "100 plays of 49 tracks once each and one track 51 times".
+    let track_play_counts: Vec<i64> = profiled!(
+        profiler,
+        "track_play_counts",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT COUNT(*) as play_count
+            FROM user_plays
+            WHERE user_did = $1 AND played_at >= $2 AND played_at < $3
+            GROUP BY track_name
+            "#,
+        )
+        .bind(user_did)
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?
+    .into_iter()
+    .map(|row| row.get::<i64, _>("play_count"))
+    .collect();
 
-    let listening_diversity = if total_plays > 0 {
-        unique_tracks as f64 / total_plays as f64
-    } else {
-        0.0
-    };
+    let listening_diversity = calculate_listening_entropy(&track_play_counts);
 
     // Calculate hourly distribution
-    let hourly_stats = sqlx::query(
-        r#"
-        SELECT
-          EXTRACT(HOUR FROM played_at)::INT AS hour,
-          COUNT(*) AS play_count
-        FROM user_plays
-        WHERE user_did = $1
-          AND EXTRACT(YEAR FROM played_at) = $2
-        GROUP BY EXTRACT(HOUR FROM played_at)::INT
-        ORDER BY hour;
-        "#,
-    )
-    .bind(user_did)
-    .bind(year as i32)
-    .fetch_all(pool)
-    .await?;
+    let hourly_stats = profiled!(
+        profiler,
+        "hourly_distribution",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT
+              EXTRACT(HOUR FROM played_at)::INT AS hour,
+              COUNT(*) AS play_count
+            FROM user_plays
+            WHERE user_did = $1
+              AND played_at >= $2 AND played_at < $3
+            GROUP BY EXTRACT(HOUR FROM played_at)::INT
+            ORDER BY hour;
+            "#,
+        )
+        .bind(user_did)
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?;
 
     let mut hourly_distribution = [0u32; 24];
     for row in hourly_stats {
@@ -213,68 +789,105 @@ pub async fn calculate_wrapped_stats(
         .map(|(hour, _)| hour as u8)
         .unwrap_or(0);
 
-    // Calculate longest listening session (plays within 6 minutes of each other)
-    let session_query = sqlx::query(
-        r#"
-        WITH sessions AS (
-            SELECT
-                played_at,
-                EXTRACT(EPOCH FROM (played_at - LAG(played_at) OVER (ORDER BY played_at))) AS gap_seconds
-            FROM user_plays
-            WHERE user_did = $1
-              AND EXTRACT(YEAR FROM played_at) = $2
-        ),
-        session_groups AS (
-            SELECT
-                played_at,
-                SUM(
-                    CASE
-                        WHEN gap_seconds > 360 OR gap_seconds IS NULL THEN 1
-                        ELSE 0
-                    END
-                ) OVER (ORDER BY played_at) AS session_id
-            FROM sessions
-        ),
-        session_lengths AS (
-            SELECT
-                session_id,
-                EXTRACT(EPOCH FROM (MAX(played_at) - MIN(played_at))) / 60.0 AS duration_minutes
-                -- 60.0 ensures DOUBLE PRECISION arithmetic
-            FROM session_groups
-            GROUP BY session_id
-        )
-        SELECT
-            COALESCE(MAX(duration_minutes)::DOUBLE PRECISION, 0) AS max_session
-        FROM session_lengths;
-        "#,
-    )
-    .bind(user_did)
-    .bind(year as i32)
-    .fetch_one(pool)
-    .await?;
+    // Session analysis: group plays into sessions (gap-based), then derive everything the old
+    // single-scalar `longest_session_minutes` query used to hide.
+    let sessions =
+        calculate_sessions(pool, user_did, window, DEFAULT_SESSION_GAP_SECONDS, profiler).await?;
+
+    let longest_session_minutes = sessions
+        .iter()
+        .map(|s| s.duration_minutes)
+        .fold(0.0_f64, f64::max)
+        .round() as u32;
+
+    let session_count = sessions.len() as u32;
+
+    let avg_session_minutes = if sessions.is_empty() {
+        0.0
+    } else {
+        sessions.iter().map(|s| s.duration_minutes).sum::<f64>() / sessions.len() as f64
+    };
+
+    let most_common_session_start_hour = {
+        let mut counts = [0u32; 24];
+        for session in &sessions {
+            counts[session.start.hour() as usize] += 1;
+        }
+        counts
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, &count)| count)
+            .filter(|(_, &count)| count > 0)
+            .map(|(hour, _)| hour as u8)
+    };
 
-    let longest_session_minutes: f64 = session_query.get("max_session");
-    let longest_session_minutes = longest_session_minutes.round() as u32;
+    let epic_session = sessions
+        .iter()
+        .max_by(|a, b| {
+            a.duration_minutes
+                .partial_cmp(&b.duration_minutes)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .map(|s| EpicSession {
+            date: s.start.date_naive(),
+            duration_minutes: s.duration_minutes,
+            top_artist: s.top_artist.clone(),
+        });
 
     // Count unique first artists for new_artists
-    let unique_first_artists: i64 = sqlx::query(
-        r#"
-        SELECT COUNT(DISTINCT (artists->0)->>'artistName') as count
-        FROM user_plays
-        WHERE user_did = $1 AND EXTRACT(YEAR FROM played_at) = $2 AND jsonb_array_length(artists) > 0
-        "#,
+    let unique_first_artists: i64 = profiled!(
+        profiler,
+        "new_artists_count",
+        QueryCategory::PerArtist,
+        sqlx::query(
+            r#"
+            SELECT COUNT(DISTINCT (artists->0)->>'artistName') as count
+            FROM user_plays
+            WHERE user_did = $1 AND played_at >= $2 AND played_at < $3 AND jsonb_array_length(artists) > 0
+            "#,
+        )
+        .bind(user_did)
+        .bind(start)
+        .bind(end)
+        .fetch_one(pool)
     )
-    .bind(user_did)
-    .bind(year as i32)
-    .fetch_one(pool)
-    .await
     .map(|row| row.get::<i64, _>("count"))
     .unwrap_or(0);
 
     let new_artists_count = unique_first_artists as u32;
 
-    // Calculate longest streak
-    let longest_streak = calculate_longest_streak(&daily_plays);
+    // "What you're into right now": artists trending in the last HOTNESS_WINDOW_DAYS days,
+    // ranked by a decayed/compressed score rather than raw recent play count.
+    let rising_artist_rows = profiled!(
+        profiler,
+        "top_rising_artists",
+        QueryCategory::PerArtist,
+        sqlx::query(
+            r#"
+            SELECT
+                artist->>'artistName' as name,
+                COUNT(*) FILTER (WHERE played_at > now() - make_interval(days => $2)) as recent_plays,
+                EXTRACT(EPOCH FROM (now() - MIN(played_at))) / 86400.0 as days_since_first_play
+            FROM user_plays, jsonb_array_elements(artists) as artist
+            WHERE user_did = $1
+            GROUP BY artist->>'artistName'
+            HAVING COUNT(*) FILTER (WHERE played_at > now() - make_interval(days => $2)) > 0
+            "#,
+        )
+        .bind(user_did)
+        .bind(HOTNESS_WINDOW_DAYS as i32)
+        .fetch_all(pool)
+    )?;
+
+    let top_rising_artists = top_rising_from_rows(rising_artist_rows);
+
+    // Calculate longest and current streaks
+    let streaks = calculate_streak_stats(&daily_plays);
+
+    let (peak_hour, peak_weekday, typical_session_hour) =
+        listening_pattern_stats(pool, user_did, window, profiler).await?;
+
+    let top_artist_affinities = calculate_artist_affinities(pool, user_did, window, profiler).await?;
 
     // Calculate weekday vs weekend averages
     let mut weekday_days = 0;
@@ -292,35 +905,45 @@ pub async fn calculate_wrapped_stats(
     }
 
     // Get weekday/weekend breakdown from database
-    let weekday_stats = sqlx::query(
-        r#"
-        SELECT
-            SUM(COALESCE(duration_ms, 210000)) as total_duration_ms
-        FROM user_plays
-        WHERE user_did = $1
-          AND EXTRACT(YEAR FROM played_at) = $2
-          AND EXTRACT(DOW FROM played_at) NOT IN (0, 6)
-        "#,
-    )
-    .bind(user_did)
-    .bind(year as i32)
-    .fetch_one(pool)
-    .await?;
+    let weekday_stats = profiled!(
+        profiler,
+        "weekday_stats",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT
+                SUM(COALESCE(duration_ms, 210000)) as total_duration_ms
+            FROM user_plays
+            WHERE user_did = $1
+              AND played_at >= $2 AND played_at < $3
+              AND EXTRACT(DOW FROM played_at) NOT IN (0, 6)
+            "#,
+        )
+        .bind(user_did)
+        .bind(start)
+        .bind(end)
+        .fetch_one(pool)
+    )?;
 
-    let weekend_stats = sqlx::query(
-        r#"
-        SELECT
-            SUM(COALESCE(duration_ms, 210000)) as total_duration_ms
-        FROM user_plays
-        WHERE user_did = $1
-          AND EXTRACT(YEAR FROM played_at) = $2
-          AND EXTRACT(DOW FROM played_at) IN (0, 6)
-        "#,
-    )
-    .bind(user_did)
-    .bind(year as i32)
-    .fetch_one(pool)
-    .await?;
+    let weekend_stats = profiled!(
+        profiler,
+        "weekend_stats",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT
+                SUM(COALESCE(duration_ms, 210000)) as total_duration_ms
+            FROM user_plays
+            WHERE user_did = $1
+              AND played_at >= $2 AND played_at < $3
+              AND EXTRACT(DOW FROM played_at) IN (0, 6)
+            "#,
+        )
+        .bind(user_did)
+        .bind(start)
+        .bind(end)
+        .fetch_one(pool)
+    )?;
 
     let weekday_avg_minutes = if weekday_days > 0 {
         let weekday_ms: Option<i64> = weekday_stats.get("total_duration_ms");
@@ -331,68 +954,574 @@ pub async fn calculate_wrapped_stats(
         0.0
     };
 
-    let weekend_avg_minutes = if weekend_days > 0 {
-        let weekend_ms: Option<i64> = weekend_stats.get("total_duration_ms");
-        weekend_ms
-            .map(|ms| ms as f64 / (1000.0 * 60.0) / weekend_days as f64)
-            .unwrap_or(0.0)
-    } else {
-        0.0
-    };
+    let weekend_avg_minutes = if weekend_days > 0 {
+        let weekend_ms: Option<i64> = weekend_stats.get("total_duration_ms");
+        weekend_ms
+            .map(|ms| ms as f64 / (1000.0 * 60.0) / weekend_days as f64)
+            .unwrap_or(0.0)
+    } else {
+        0.0
+    };
+
+    // Get top track for each artist
+    let mut top_track_per_artist: HashMap<String, (String, u32, i32)> = HashMap::new();
+
+    // For each top artist, find their most played track. This is an N+1 query (one round trip
+    // per top artist) - exactly the shape `QueryProfiler` is meant to expose, since its total
+    // cost scales with `top_artists.len()` rather than being a fixed number of queries.
+    for (artist_name, _, _, _) in &top_artists {
+        let top_track_result = profiled!(
+            profiler,
+            "top_track_per_artist",
+            QueryCategory::PerArtist,
+            sqlx::query(
+                r#"
+                SELECT track_name, COUNT(*) as play_count, MAX(duration_ms) as duration_ms
+                FROM user_plays
+                WHERE user_did = $1
+                  AND played_at >= $2 AND played_at < $3
+                  AND (artists->0)->>'artistName' = $4
+                GROUP BY track_name
+                ORDER BY play_count DESC
+                LIMIT 1
+                "#,
+            )
+            .bind(user_did)
+            .bind(start)
+            .bind(end)
+            .bind(artist_name)
+            .fetch_optional(pool)
+        )?;
+
+        if let Some(row) = top_track_result {
+            let track_name: String = row.get("track_name");
+            let play_count: i64 = row.get("play_count");
+            let duration_ms: Option<i32> = row.get("duration_ms");
+            let duration = duration_ms.unwrap_or(210000);
+            top_track_per_artist.insert(
+                artist_name.clone(),
+                (track_name, play_count as u32, duration),
+            );
+        }
+    }
+
+    // Month-by-month breakdown, so the front end can build a "how your taste shifted" story
+    // instead of just a yearly total.
+    let monthly_stats = profiled!(
+        profiler,
+        "monthly_stats",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT
+                EXTRACT(MONTH FROM played_at)::INT AS month,
+                COUNT(*) AS play_count,
+                SUM(COALESCE(duration_ms, 210000)) AS total_duration_ms
+            FROM user_plays
+            WHERE user_did = $1 AND played_at >= $2 AND played_at < $3
+            GROUP BY month
+            "#,
+        )
+        .bind(user_did)
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?;
+
+    let mut monthly_plays = [0u32; 12];
+    let mut monthly_minutes = [0.0f64; 12];
+    for row in monthly_stats {
+        let month: i32 = row.get("month");
+        let play_count: i64 = row.get("play_count");
+        let total_duration_ms: i64 = row.get("total_duration_ms");
+        monthly_plays[(month - 1) as usize] = play_count as u32;
+        monthly_minutes[(month - 1) as usize] = total_duration_ms as f64 / (1000.0 * 60.0);
+    }
+
+    // Top artist per month, with ties broken by whichever artist's most recent play in that
+    // month is later - so a late-month surge can edge out an artist that merely played more
+    // total tracks earlier in the month.
+    let monthly_top_artist_rows = profiled!(
+        profiler,
+        "monthly_top_artist",
+        QueryCategory::PerArtist,
+        sqlx::query(
+            r#"
+            SELECT DISTINCT ON (month)
+                month,
+                artist_name,
+                play_count
+            FROM (
+                SELECT
+                    EXTRACT(MONTH FROM played_at)::INT AS month,
+                    (artists->0)->>'artistName' AS artist_name,
+                    COUNT(*) AS play_count,
+                    MAX(played_at) AS last_played
+                FROM user_plays
+                WHERE user_did = $1
+                  AND played_at >= $2 AND played_at < $3
+                  AND (artists->0)->>'artistName' IS NOT NULL
+                GROUP BY month, artist_name
+            ) monthly_artist_counts
+            ORDER BY month, play_count DESC, last_played DESC
+            "#,
+        )
+        .bind(user_did)
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?;
+
+    let mut top_artist_per_month: [Option<(String, u32)>; 12] = Default::default();
+    for row in monthly_top_artist_rows {
+        let month: i32 = row.get("month");
+        let artist_name: String = row.get("artist_name");
+        let play_count: i64 = row.get("play_count");
+        top_artist_per_month[(month - 1) as usize] = Some((artist_name, play_count as u32));
+    }
+
+    let seasonal_distribution = SeasonalDistribution {
+        winter_plays: monthly_plays[11] + monthly_plays[0] + monthly_plays[1],
+        winter_minutes: monthly_minutes[11] + monthly_minutes[0] + monthly_minutes[1],
+        spring_plays: monthly_plays[2] + monthly_plays[3] + monthly_plays[4],
+        spring_minutes: monthly_minutes[2] + monthly_minutes[3] + monthly_minutes[4],
+        summer_plays: monthly_plays[5] + monthly_plays[6] + monthly_plays[7],
+        summer_minutes: monthly_minutes[5] + monthly_minutes[6] + monthly_minutes[7],
+        fall_plays: monthly_plays[8] + monthly_plays[9] + monthly_plays[10],
+        fall_minutes: monthly_minutes[8] + monthly_minutes[9] + monthly_minutes[10],
+    };
+
+    Ok(WrappedStats {
+        total_minutes,
+        total_plays,
+        top_artists,
+        top_tracks,
+        top_track_per_artist,
+        new_artists_count,
+        daily_plays,
+        weekday_avg_minutes,
+        weekend_avg_minutes,
+        streaks,
+        days_active,
+        peak_hour,
+        peak_weekday,
+        typical_session_hour,
+        avg_track_length_ms,
+        listening_diversity,
+        hourly_distribution,
+        top_hour,
+        longest_session_minutes,
+        top_rising_artists,
+        session_count,
+        avg_session_minutes,
+        most_common_session_start_hour,
+        epic_session,
+        monthly_plays,
+        monthly_minutes,
+        top_artist_per_month,
+        seasonal_distribution,
+        top_artist_affinities,
+    })
+}
+
+/// Rolling window (in days) `calculate_hotness` treats as "recent" when scoring trends.
+const HOTNESS_WINDOW_DAYS: f64 = 30.0;
+
+/// A Hacker-News-style decayed/compressed trending score. `num` is the play count within the
+/// last `window_days`; raw count is compressed via `-7 + 0.19*num + log_1.25(num + 5)` so a
+/// handful of heavy-rotation artists don't dominate the list, then scaled by an age ratio of
+/// `window_days / days_since_first_play` so a newly-adopted favorite outranks a long-settled
+/// one with the same recent play count.
+fn calculate_hotness(num: i64, days_since_first_play: f64, window_days: f64) -> f64 {
+    let num = num as f64;
+    let compressed = -7.0 + 0.19 * num + (num + 5.0).ln() / 1.25_f64.ln();
+    let age_ratio = window_days / days_since_first_play.max(1.0);
+    compressed * age_ratio
+}
+
+/// Score and rank the rows produced by a "recent plays + days since first play, grouped by
+/// name" query into a top-10 trending list.
+fn top_rising_from_rows(rows: Vec<sqlx::postgres::PgRow>) -> Vec<(String, f64)> {
+    let mut scored: Vec<(String, f64)> = rows
+        .into_iter()
+        .map(|row| {
+            let name: String = row.get("name");
+            let recent_plays: i64 = row.get("recent_plays");
+            let days_since_first_play: f64 = row.get("days_since_first_play");
+            let score = calculate_hotness(recent_plays, days_since_first_play, HOTNESS_WINDOW_DAYS);
+            (name, score)
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    scored.truncate(10);
+    scored
+}
+
+/// A rolling window of `user_plays` used to scope a recommendation query, either to decide
+/// which plays count toward a score (`include`) or which artists/albums to suppress because
+/// the user already returned to them recently (`exclude`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeWindow {
+    /// The user's entire history.
+    All,
+    /// The last 365 days.
+    Yearly,
+    /// The last 30 days.
+    Monthly,
+    /// The last 7 days.
+    Weekly,
+    /// No plays at all - matches nothing.
+    None,
+}
+
+impl TimeWindow {
+    /// A SQL boolean expression over `played_at` implementing this window. Safe to splice
+    /// directly into a query string since it never incorporates caller input.
+    pub(crate) fn sql_predicate(&self) -> &'static str {
+        match self {
+            TimeWindow::All => "TRUE",
+            TimeWindow::Yearly => "played_at > now() - interval '365 days'",
+            TimeWindow::Monthly => "played_at > now() - interval '30 days'",
+            TimeWindow::Weekly => "played_at > now() - interval '7 days'",
+            TimeWindow::None => "FALSE",
+        }
+    }
+}
+
+/// Recommend artists the user should revisit or discover: artists played heavily within
+/// `include` but not (yet) in `exclude` bubble to the top by "neglect score" - play count
+/// times how long it's been since the last play - unless `random` is set, in which case the
+/// eligible set is shuffled instead. See also [`recommend_albums`] and
+/// [`crate::recommendations::recommend_tracks`].
+pub async fn recommend_artists(
+    pool: &PgPool,
+    user_did: &str,
+    count: i64,
+    include: TimeWindow,
+    exclude: TimeWindow,
+    random: bool,
+) -> Result<Vec<(String, u32)>> {
+    let include_predicate = include.sql_predicate();
+    let exclude_predicate = exclude.sql_predicate();
+    let order_by = if random {
+        "RANDOM()"
+    } else {
+        "COUNT(*) * (EXTRACT(EPOCH FROM now()) - MAX(EXTRACT(EPOCH FROM played_at))) DESC"
+    };
+
+    let query = format!(
+        r#"
+        SELECT artist->>'artistName' as artist, COUNT(*) as play_count
+        FROM user_plays, jsonb_array_elements(artists) as artist
+        WHERE user_did = $1
+          AND {include_predicate}
+          AND artist->>'artistName' NOT IN (
+              SELECT DISTINCT artist->>'artistName'
+              FROM user_plays, jsonb_array_elements(artists) as artist
+              WHERE user_did = $1
+                AND {exclude_predicate}
+          )
+        GROUP BY artist->>'artistName'
+        ORDER BY {order_by}
+        LIMIT $2
+        "#
+    );
+
+    sqlx::query(&query)
+        .bind(user_did)
+        .bind(count)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let artist: String = row.get("artist");
+            let plays: i64 = row.get("play_count");
+            Ok((artist, plays as u32))
+        })
+        .collect()
+}
+
+/// Same idea as [`recommend_artists`], scoped to albums (`release_name`) instead of artists.
+pub async fn recommend_albums(
+    pool: &PgPool,
+    user_did: &str,
+    count: i64,
+    include: TimeWindow,
+    exclude: TimeWindow,
+    random: bool,
+) -> Result<Vec<(String, u32)>> {
+    let include_predicate = include.sql_predicate();
+    let exclude_predicate = exclude.sql_predicate();
+    let order_by = if random {
+        "RANDOM()"
+    } else {
+        "COUNT(*) * (EXTRACT(EPOCH FROM now()) - MAX(EXTRACT(EPOCH FROM played_at))) DESC"
+    };
+
+    let query = format!(
+        r#"
+        SELECT release_name, COUNT(*) as play_count
+        FROM user_plays
+        WHERE user_did = $1
+          AND release_name IS NOT NULL
+          AND {include_predicate}
+          AND release_name NOT IN (
+              SELECT DISTINCT release_name
+              FROM user_plays
+              WHERE user_did = $1
+                AND release_name IS NOT NULL
+                AND {exclude_predicate}
+          )
+        GROUP BY release_name
+        ORDER BY {order_by}
+        LIMIT $2
+        "#
+    );
+
+    sqlx::query(&query)
+        .bind(user_did)
+        .bind(count)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let release_name: String = row.get("release_name");
+            let plays: i64 = row.get("play_count");
+            Ok((release_name, plays as u32))
+        })
+        .collect()
+}
+
+/// One edge in a user's co-listening affinity network: how often `artist_a` and `artist_b`
+/// (canonically ordered so `artist_a < artist_b`, regardless of play order) showed up in the
+/// same listening session, plus how often one was played immediately before/after the other.
+#[derive(Debug, Clone)]
+pub struct ArtistAffinity {
+    pub artist_a: String,
+    pub artist_b: String,
+    pub co_occurrence_count: u32,
+    pub adjacent_count: u32,
+}
+
+/// Build the co-listening affinity network for a user's sessions in `window`: group plays into
+/// sessions with the same gap threshold as [`calculate_sessions`], then for every pair of
+/// distinct artists that appear together in a session, count how many sessions they co-occurred
+/// in and how many times one played immediately before/after the other. Persists the full
+/// network to `artist_affinities` (see [`store_artist_affinities`]) and returns the strongest
+/// pairs by co-occurrence count, so the wrapped report can say "you always listen to X alongside
+/// Y."
+pub async fn calculate_artist_affinities(
+    pool: &PgPool,
+    user_did: &str,
+    window: ReportWindow,
+    profiler: Option<&QueryProfiler>,
+) -> Result<Vec<(String, String, u32)>> {
+    let (start, end) = window.bounds();
+    let rows = profiled!(
+        profiler,
+        "artist_affinities",
+        QueryCategory::PerArtist,
+        sqlx::query(
+            r#"
+            WITH sessions AS (
+                SELECT
+                    played_at,
+                    (artists->0)->>'artistName' AS artist_name,
+                    EXTRACT(EPOCH FROM (played_at - LAG(played_at) OVER (ORDER BY played_at))) AS gap_seconds
+                FROM user_plays
+                WHERE user_did = $1
+                  AND played_at >= $2 AND played_at < $3
+            ),
+            session_groups AS (
+                SELECT
+                    played_at,
+                    artist_name,
+                    SUM(
+                        CASE
+                            WHEN gap_seconds > $4 OR gap_seconds IS NULL THEN 1
+                            ELSE 0
+                        END
+                    ) OVER (ORDER BY played_at) AS session_id
+                FROM sessions
+                WHERE artist_name IS NOT NULL
+            ),
+            co_occurring_pairs AS (
+                SELECT DISTINCT
+                    a.session_id,
+                    LEAST(a.artist_name, b.artist_name) AS artist_a,
+                    GREATEST(a.artist_name, b.artist_name) AS artist_b
+                FROM session_groups a
+                JOIN session_groups b
+                    ON a.session_id = b.session_id AND a.artist_name < b.artist_name
+            ),
+            adjacent_pairs AS (
+                SELECT
+                    LEAST(artist_name, next_artist) AS artist_a,
+                    GREATEST(artist_name, next_artist) AS artist_b
+                FROM (
+                    SELECT
+                        artist_name,
+                        LEAD(artist_name) OVER (PARTITION BY session_id ORDER BY played_at) AS next_artist
+                    FROM session_groups
+                ) adjacent
+                WHERE next_artist IS NOT NULL AND next_artist != artist_name
+            ),
+            co_occurrence_counts AS (
+                SELECT artist_a, artist_b, COUNT(*) AS co_occurrence_count
+                FROM co_occurring_pairs
+                GROUP BY artist_a, artist_b
+            ),
+            adjacent_counts AS (
+                SELECT artist_a, artist_b, COUNT(*) AS adjacent_count
+                FROM adjacent_pairs
+                GROUP BY artist_a, artist_b
+            )
+            SELECT
+                c.artist_a,
+                c.artist_b,
+                c.co_occurrence_count,
+                COALESCE(a.adjacent_count, 0) AS adjacent_count
+            FROM co_occurrence_counts c
+            LEFT JOIN adjacent_counts a USING (artist_a, artist_b)
+            ORDER BY c.co_occurrence_count DESC
+            "#,
+        )
+        .bind(user_did)
+        .bind(start)
+        .bind(end)
+        .bind(DEFAULT_SESSION_GAP_SECONDS)
+        .fetch_all(pool)
+    )?;
+
+    let affinities: Vec<ArtistAffinity> = rows
+        .into_iter()
+        .map(|row| {
+            let co_occurrence_count: i64 = row.get("co_occurrence_count");
+            let adjacent_count: i64 = row.get("adjacent_count");
+            ArtistAffinity {
+                artist_a: row.get("artist_a"),
+                artist_b: row.get("artist_b"),
+                co_occurrence_count: co_occurrence_count as u32,
+                adjacent_count: adjacent_count as u32,
+            }
+        })
+        .collect();
+
+    store_artist_affinities(pool, user_did, window, &affinities).await?;
+
+    Ok(affinities
+        .into_iter()
+        .take(25)
+        .map(|a| (a.artist_a, a.artist_b, a.co_occurrence_count))
+        .collect())
+}
+
+/// Replace `user_did`/`window`'s rows in `artist_affinities` with `affinities`, via a single
+/// multi-row `UNNEST` insert rather than one round trip per edge.
+async fn store_artist_affinities(
+    pool: &PgPool,
+    user_did: &str,
+    window: ReportWindow,
+    affinities: &[ArtistAffinity],
+) -> Result<()> {
+    let window_key = window.cache_key();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM artist_affinities WHERE user_did = $1 AND window_key = $2")
+        .bind(user_did)
+        .bind(&window_key)
+        .execute(&mut *tx)
+        .await?;
 
-    // Get top track for each artist
-    let mut top_track_per_artist: HashMap<String, (String, u32, i32)> = HashMap::new();
+    if !affinities.is_empty() {
+        let user_dids = vec![user_did.to_string(); affinities.len()];
+        let window_keys = vec![window_key.clone(); affinities.len()];
+        let artist_as: Vec<&str> = affinities.iter().map(|a| a.artist_a.as_str()).collect();
+        let artist_bs: Vec<&str> = affinities.iter().map(|a| a.artist_b.as_str()).collect();
+        let co_occurrence_counts: Vec<i32> = affinities
+            .iter()
+            .map(|a| a.co_occurrence_count as i32)
+            .collect();
+        let adjacent_counts: Vec<i32> = affinities.iter().map(|a| a.adjacent_count as i32).collect();
 
-    // For each top artist, find their most played track
-    for (artist_name, _, _, _) in &top_artists {
-        let top_track_result = sqlx::query(
+        sqlx::query(
             r#"
-            SELECT track_name, COUNT(*) as play_count, MAX(duration_ms) as duration_ms
-            FROM user_plays
-            WHERE user_did = $1
-              AND EXTRACT(YEAR FROM played_at) = $2
-              AND (artists->0)->>'artistName' = $3
-            GROUP BY track_name
-            ORDER BY play_count DESC
-            LIMIT 1
+            INSERT INTO artist_affinities (user_did, window_key, artist_a, artist_b, co_occurrence_count, adjacent_count)
+            SELECT * FROM UNNEST(
+                $1::text[], $2::text[], $3::text[], $4::text[], $5::int4[], $6::int4[]
+            )
+            ON CONFLICT (user_did, window_key, artist_a, artist_b) DO UPDATE
+                SET co_occurrence_count = EXCLUDED.co_occurrence_count,
+                    adjacent_count = EXCLUDED.adjacent_count,
+                    updated_at = NOW()
             "#,
         )
-        .bind(user_did)
-        .bind(year as i32)
-        .bind(artist_name)
-        .fetch_optional(pool)
+        .bind(&user_dids)
+        .bind(&window_keys)
+        .bind(&artist_as)
+        .bind(&artist_bs)
+        .bind(&co_occurrence_counts)
+        .bind(&adjacent_counts)
+        .execute(&mut *tx)
         .await?;
-
-        if let Some(row) = top_track_result {
-            let track_name: String = row.get("track_name");
-            let play_count: i64 = row.get("play_count");
-            let duration_ms: Option<i32> = row.get("duration_ms");
-            let duration = duration_ms.unwrap_or(210000);
-            top_track_per_artist.insert(
-                artist_name.clone(),
-                (track_name, play_count as u32, duration),
-            );
-        }
     }
 
-    Ok(WrappedStats {
-        total_minutes,
-        total_plays,
-        top_artists,
-        top_tracks,
-        top_track_per_artist,
-        new_artists_count,
-        daily_plays,
-        weekday_avg_minutes,
-        weekend_avg_minutes,
-        longest_streak,
-        days_active,
-        avg_track_length_ms,
-        listening_diversity,
-        hourly_distribution,
-        top_hour,
-        longest_session_minutes,
-    })
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Minimum-listening-minute thresholds `calculate_global_wrapped_stats` reports engagement
+/// cohorts for - "how many users listened at least this long".
+const ENGAGEMENT_COHORT_THRESHOLDS_MINUTES: [u32; 4] = [1, 10, 60, 600];
+
+/// How many distinct users listened at least `min_minutes` within `period`, summing each
+/// user's per-day minutes from `user_daily_rollups` rather than re-deriving them from raw plays.
+pub async fn get_active_user_count(
+    pool: &PgPool,
+    period: std::ops::Range<DateTime<Utc>>,
+    min_minutes: u32,
+) -> Result<u32> {
+    let cohorts = get_active_user_sets(pool, period, &[min_minutes]).await?;
+    Ok(cohorts.into_iter().next().map(|(_, count)| count).unwrap_or(0))
+}
+
+/// Same idea as [`get_active_user_count`], but for every threshold in `thresholds` at once -
+/// one round trip to sum per-user minutes, then the histogram is built in memory.
+pub async fn get_active_user_sets(
+    pool: &PgPool,
+    period: std::ops::Range<DateTime<Utc>>,
+    thresholds: &[u32],
+) -> Result<Vec<(u32, u32)>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT (SUM(total_duration_ms) / 1000.0 / 60.0)::DOUBLE PRECISION as total_minutes
+        FROM user_daily_rollups
+        WHERE day >= $1::date AND day < $2::date
+        GROUP BY user_did
+        "#,
+    )
+    .bind(period.start)
+    .bind(period.end)
+    .fetch_all(pool)
+    .await?;
+
+    let user_minutes: Vec<f64> = rows
+        .into_iter()
+        .map(|row| row.get::<Option<f64>, _>("total_minutes").unwrap_or(0.0))
+        .collect();
+
+    Ok(thresholds
+        .iter()
+        .map(|&min_minutes| {
+            let count = user_minutes
+                .iter()
+                .filter(|&&minutes| minutes >= min_minutes as f64)
+                .count() as u32;
+            (min_minutes, count)
+        })
+        .collect())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -406,6 +1535,11 @@ pub struct GlobalWrappedStats {
     pub top_tracks: Vec<((String, String), u32, TrackMetadata)>,
     pub user_percentile: Option<UserPercentile>,
     pub distribution: Distribution,
+    /// Artists trending right now across all users - see [`calculate_hotness`].
+    pub top_rising_artists: Vec<(String, f64)>,
+    /// Retention-style histogram: for each of [`ENGAGEMENT_COHORT_THRESHOLDS_MINUTES`], how
+    /// many users listened at least that many minutes - see [`get_active_user_sets`].
+    pub engagement_cohorts: Vec<(u32, u32)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -426,41 +1560,52 @@ pub struct UserPercentile {
 
 pub async fn calculate_global_wrapped_stats(
     pool: &PgPool,
-    year: u32,
+    window: ReportWindow,
     user_did: Option<&str>,
+    profiler: Option<&QueryProfiler>,
 ) -> Result<GlobalWrappedStats> {
-    let year_i32 = year as i32;
+    let (start, end) = window.bounds();
 
-    let total_users: i64 = sqlx::query(
-        r#"
-        SELECT COUNT(DISTINCT user_did) as count
-        FROM user_plays
-        WHERE EXTRACT(YEAR FROM played_at) = $1
-        "#,
+    let total_users: i64 = profiled!(
+        profiler,
+        "total_users",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT COUNT(DISTINCT user_did) as count
+            FROM user_daily_rollups
+            WHERE day >= $1::date AND day < $2::date
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_one(pool)
     )
-    .bind(year_i32)
-    .fetch_one(pool)
-    .await
     .map(|row| row.get::<i64, _>("count"))?;
 
     let user_percentile = if let Some(did) = user_did {
-        let user_stats: Option<(i64, i64, i64, i64)> = sqlx::query(
-            r#"
-            SELECT
-                COUNT(*) as total_plays,
-                COUNT(DISTINCT track_name) as unique_tracks,
-                SUM(COALESCE(duration_ms, 210000)) as total_duration_ms,
-                (SELECT COUNT(DISTINCT artist->>'artistName')
-                 FROM user_plays, jsonb_array_elements(artists) as artist
-                 WHERE user_did = $2 AND EXTRACT(YEAR FROM played_at) = $1) as unique_artists
-            FROM user_plays
-            WHERE user_did = $2 AND EXTRACT(YEAR FROM played_at) = $1
-            "#,
-        )
-        .bind(year_i32)
-        .bind(did)
-        .fetch_optional(pool)
-        .await?
+        let user_stats: Option<(i64, i64, i64, i64)> = profiled!(
+            profiler,
+            "user_stats",
+            QueryCategory::Aggregation,
+            sqlx::query(
+                r#"
+                SELECT
+                    COUNT(*) as total_plays,
+                    COUNT(DISTINCT track_name) as unique_tracks,
+                    SUM(COALESCE(duration_ms, 210000)) as total_duration_ms,
+                    (SELECT COUNT(DISTINCT artist->>'artistName')
+                     FROM user_plays, jsonb_array_elements(artists) as artist
+                     WHERE user_did = $3 AND played_at >= $1 AND played_at < $2) as unique_artists
+                FROM user_plays
+                WHERE user_did = $3 AND played_at >= $1 AND played_at < $2
+                "#,
+            )
+            .bind(start)
+            .bind(end)
+            .bind(did)
+            .fetch_optional(pool)
+        )?
         .map(|row| {
             (
                 row.get("total_plays"),
@@ -473,86 +1618,106 @@ pub async fn calculate_global_wrapped_stats(
         if let Some((user_plays, user_unique_tracks, user_duration_ms, user_unique_artists)) = user_stats {
             let user_minutes = user_duration_ms as f64 / (1000.0 * 60.0);
 
-            let percentile_minutes: i32 = sqlx::query(
-                r#"
-                SELECT
-                    FLOOR(100.0 * COUNT(*) / $1)::INTEGER as percentile
-                FROM (
-                    SELECT user_did, SUM(COALESCE(duration_ms, 210000)) / 1000.0 / 60.0 as total_minutes
-                    FROM user_plays
-                    WHERE EXTRACT(YEAR FROM played_at) = $2
-                    GROUP BY user_did
-                ) user_minutes
-                WHERE total_minutes < $3
-                "#,
+            let percentile_minutes: i32 = profiled!(
+                profiler,
+                "percentile_minutes",
+                QueryCategory::Percentile,
+                sqlx::query(
+                    r#"
+                    SELECT
+                        FLOOR(100.0 * COUNT(*) / $1)::INTEGER as percentile
+                    FROM (
+                        SELECT user_did, SUM(COALESCE(duration_ms, 210000)) / 1000.0 / 60.0 as total_minutes
+                        FROM user_plays
+                        WHERE played_at >= $2 AND played_at < $3
+                        GROUP BY user_did
+                    ) user_minutes
+                    WHERE total_minutes < $4
+                    "#,
+                )
+                .bind(total_users)
+                .bind(start)
+                .bind(end)
+                .bind(user_minutes)
+                .fetch_one(pool)
             )
-            .bind(total_users)
-            .bind(year_i32)
-            .bind(user_minutes)
-            .fetch_one(pool)
-            .await
             .map(|row| row.get::<i32, _>("percentile"))?;
 
-            let percentile_plays: i32 = sqlx::query(
-                r#"
-                SELECT
-                    FLOOR(100.0 * COUNT(*) / $1)::INTEGER as percentile
-                FROM (
-                    SELECT user_did, COUNT(*) as total_plays
-                    FROM user_plays
-                    WHERE EXTRACT(YEAR FROM played_at) = $2
-                    GROUP BY user_did
-                ) user_plays
-                WHERE total_plays < $3
-                "#,
+            let percentile_plays: i32 = profiled!(
+                profiler,
+                "percentile_plays",
+                QueryCategory::Percentile,
+                sqlx::query(
+                    r#"
+                    SELECT
+                        FLOOR(100.0 * COUNT(*) / $1)::INTEGER as percentile
+                    FROM (
+                        SELECT user_did, COUNT(*) as total_plays
+                        FROM user_plays
+                        WHERE played_at >= $2 AND played_at < $3
+                        GROUP BY user_did
+                    ) user_plays
+                    WHERE total_plays < $4
+                    "#,
+                )
+                .bind(total_users)
+                .bind(start)
+                .bind(end)
+                .bind(user_plays)
+                .fetch_one(pool)
             )
-            .bind(total_users)
-            .bind(year_i32)
-            .bind(user_plays)
-            .fetch_one(pool)
-            .await
             .map(|row| row.get::<i32, _>("percentile"))?;
 
-            let percentile_artists: i32 = sqlx::query(
-                r#"
-                SELECT
-                    FLOOR(100.0 * COUNT(*) / $1)::INTEGER as percentile
-                FROM (
+            let percentile_artists: i32 = profiled!(
+                profiler,
+                "percentile_artists",
+                QueryCategory::Percentile,
+                sqlx::query(
+                    r#"
                     SELECT
-                        user_did,
-                        COUNT(DISTINCT artist->>'artistName') as unique_artists
-                    FROM user_plays, jsonb_array_elements(artists) as artist
-                    WHERE EXTRACT(YEAR FROM played_at) = $2
-                    GROUP BY user_did
-                ) user_artists
-                WHERE unique_artists < $3
-                "#,
+                        FLOOR(100.0 * COUNT(*) / $1)::INTEGER as percentile
+                    FROM (
+                        SELECT
+                            user_did,
+                            COUNT(DISTINCT artist->>'artistName') as unique_artists
+                        FROM user_plays, jsonb_array_elements(artists) as artist
+                        WHERE played_at >= $2 AND played_at < $3
+                        GROUP BY user_did
+                    ) user_artists
+                    WHERE unique_artists < $4
+                    "#,
+                )
+                .bind(total_users)
+                .bind(start)
+                .bind(end)
+                .bind(user_unique_artists)
+                .fetch_one(pool)
             )
-            .bind(total_users)
-            .bind(year_i32)
-            .bind(user_unique_artists)
-            .fetch_one(pool)
-            .await
             .map(|row| row.get::<i32, _>("percentile"))?;
 
-            let percentile_tracks: i32 = sqlx::query(
-                r#"
-                SELECT
-                    FLOOR(100.0 * COUNT(*) / $1)::INTEGER as percentile
-                FROM (
-                    SELECT user_did, COUNT(DISTINCT track_name) as unique_tracks
-                    FROM user_plays
-                    WHERE EXTRACT(YEAR FROM played_at) = $2
-                    GROUP BY user_did
-                ) user_tracks
-                WHERE unique_tracks < $3
-                "#,
+            let percentile_tracks: i32 = profiled!(
+                profiler,
+                "percentile_tracks",
+                QueryCategory::Percentile,
+                sqlx::query(
+                    r#"
+                    SELECT
+                        FLOOR(100.0 * COUNT(*) / $1)::INTEGER as percentile
+                    FROM (
+                        SELECT user_did, COUNT(DISTINCT track_name) as unique_tracks
+                        FROM user_plays
+                        WHERE played_at >= $2 AND played_at < $3
+                        GROUP BY user_did
+                    ) user_tracks
+                    WHERE unique_tracks < $4
+                    "#,
+                )
+                .bind(total_users)
+                .bind(start)
+                .bind(end)
+                .bind(user_unique_tracks)
+                .fetch_one(pool)
             )
-            .bind(total_users)
-            .bind(year_i32)
-            .bind(user_unique_tracks)
-            .fetch_one(pool)
-            .await
             .map(|row| row.get::<i32, _>("percentile"))?;
 
             Some(UserPercentile {
@@ -568,59 +1733,79 @@ pub async fn calculate_global_wrapped_stats(
         None
     };
 
-    let verified_minutes: f64 = sqlx::query(
-        r#"
-        SELECT (SUM(COALESCE(duration_ms, 210000)) / 1000.0 / 60.0)::DOUBLE PRECISION as total_minutes
-        FROM user_plays
-        WHERE EXTRACT(YEAR FROM played_at) = $1
-          AND recording_mb_id IS NOT NULL
-        "#,
+    let verified_minutes: f64 = profiled!(
+        profiler,
+        "verified_minutes",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT (SUM(COALESCE(duration_ms, 210000)) / 1000.0 / 60.0)::DOUBLE PRECISION as total_minutes
+            FROM user_plays
+            WHERE played_at >= $1 AND played_at < $2
+              AND recording_mb_id IS NOT NULL
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_one(pool)
     )
-    .bind(year_i32)
-    .fetch_one(pool)
-    .await
     .map(|row| row.get::<Option<f64>, _>("total_minutes").unwrap_or(0.0))?;
 
-    let unique_artists: i64 = sqlx::query(
-        r#"
-        SELECT COUNT(DISTINCT artist->>'artistName') as count
-        FROM user_plays, jsonb_array_elements(artists) as artist
-        WHERE EXTRACT(YEAR FROM played_at) = $1
-        "#,
+    let unique_artists: i64 = profiled!(
+        profiler,
+        "unique_artists",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT COUNT(DISTINCT name) as count
+            FROM user_daily_rollups, unnest(artist_names) as name
+            WHERE day >= $1::date AND day < $2::date
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_one(pool)
     )
-    .bind(year_i32)
-    .fetch_one(pool)
-    .await
     .map(|row| row.get::<i64, _>("count"))?;
 
-    let unique_tracks: i64 = sqlx::query(
-        r#"
-        SELECT COUNT(DISTINCT track_name) as count
-        FROM user_plays
-        WHERE EXTRACT(YEAR FROM played_at) = $1
-        "#,
+    let unique_tracks: i64 = profiled!(
+        profiler,
+        "unique_tracks",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT COUNT(DISTINCT name) as count
+            FROM user_daily_rollups, unnest(track_names) as name
+            WHERE day >= $1::date AND day < $2::date
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_one(pool)
     )
-    .bind(year_i32)
-    .fetch_one(pool)
-    .await
     .map(|row| row.get::<i64, _>("count"))?;
 
-    let top_users: Vec<(String, u32, f64)> = sqlx::query(
-        r#"
-        SELECT
-            user_did,
-            COUNT(*) as play_count,
-            (SUM(COALESCE(duration_ms, 210000)) / 1000.0 / 60.0)::DOUBLE PRECISION as total_minutes
-        FROM user_plays
-        WHERE EXTRACT(YEAR FROM played_at) = $1
-        GROUP BY user_did
-        ORDER BY total_minutes DESC
-        LIMIT 5
-        "#,
-    )
-    .bind(year_i32)
-    .fetch_all(pool)
-    .await?
+    let top_users: Vec<(String, u32, f64)> = profiled!(
+        profiler,
+        "top_users",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT
+                user_did,
+                COUNT(*) as play_count,
+                (SUM(COALESCE(duration_ms, 210000)) / 1000.0 / 60.0)::DOUBLE PRECISION as total_minutes
+            FROM user_plays
+            WHERE played_at >= $1 AND played_at < $2
+            GROUP BY user_did
+            ORDER BY total_minutes DESC
+            LIMIT 5
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?
     .into_iter()
     .map(|row| {
         let user_did: String = row.get("user_did");
@@ -630,23 +1815,28 @@ pub async fn calculate_global_wrapped_stats(
     })
     .collect();
 
-    let top_artists: Vec<(String, u32, f64, Option<String>)> = sqlx::query(
-        r#"
-        SELECT
-            artist->>'artistName' as name,
-            MAX(artist->>'artistMbId') as mb_id,
-            COUNT(*) as play_count,
-            SUM(COALESCE(duration_ms, 210000)) as total_duration_ms
-        FROM user_plays, jsonb_array_elements(artists) as artist
-        WHERE EXTRACT(YEAR FROM played_at) = $1
-        GROUP BY artist->>'artistName'
-        ORDER BY play_count DESC
-        LIMIT 10
-        "#,
-    )
-    .bind(year_i32)
-    .fetch_all(pool)
-    .await?
+    let top_artists: Vec<(String, u32, f64, Option<String>)> = profiled!(
+        profiler,
+        "global_top_artists",
+        QueryCategory::PerArtist,
+        sqlx::query(
+            r#"
+            SELECT
+                artist->>'artistName' as name,
+                MAX(artist->>'artistMbId') as mb_id,
+                COUNT(*) as play_count,
+                SUM(COALESCE(duration_ms, 210000)) as total_duration_ms
+            FROM user_plays, jsonb_array_elements(artists) as artist
+            WHERE played_at >= $1 AND played_at < $2
+            GROUP BY artist->>'artistName'
+            ORDER BY play_count DESC
+            LIMIT 10
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?
     .into_iter()
     .map(|row| {
         let name: String = row.get("name");
@@ -658,26 +1848,31 @@ pub async fn calculate_global_wrapped_stats(
     })
     .collect();
 
-    let top_tracks: Vec<((String, String), u32, TrackMetadata)> = sqlx::query(
-        r#"
-        SELECT
-            track_name,
-            (artists->0)->>'artistName' as first_artist,
-            COUNT(*) as play_count,
-            recording_mb_id,
-            release_mb_id,
-            release_name
-        FROM user_plays
-        WHERE EXTRACT(YEAR FROM played_at) = $1
-          AND jsonb_array_length(artists) > 0
-        GROUP BY track_name, (artists->0)->>'artistName', recording_mb_id, release_mb_id, release_name
-        ORDER BY play_count DESC
-        LIMIT 10
-        "#,
-    )
-    .bind(year_i32)
-    .fetch_all(pool)
-    .await?
+    let top_tracks: Vec<((String, String), u32, TrackMetadata)> = profiled!(
+        profiler,
+        "global_top_tracks",
+        QueryCategory::Aggregation,
+        sqlx::query(
+            r#"
+            SELECT
+                track_name,
+                (artists->0)->>'artistName' as first_artist,
+                COUNT(*) as play_count,
+                recording_mb_id,
+                release_mb_id,
+                release_name
+            FROM user_plays
+            WHERE played_at >= $1 AND played_at < $2
+              AND jsonb_array_length(artists) > 0
+            GROUP BY track_name, (artists->0)->>'artistName', recording_mb_id, release_mb_id, release_name
+            ORDER BY play_count DESC
+            LIMIT 10
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?
     .into_iter()
     .map(|row| {
         let track_name: String = row.get("track_name");
@@ -695,37 +1890,42 @@ pub async fn calculate_global_wrapped_stats(
     })
     .collect();
 
-    let minutes_percentiles: Vec<(i32, f64)> = sqlx::query(
-        r#"
-        WITH user_minutes AS (
-            SELECT user_did, (SUM(COALESCE(duration_ms, 210000)) / 1000.0 / 60.0)::DOUBLE PRECISION as total_minutes
-            FROM user_plays
-            WHERE EXTRACT(YEAR FROM played_at) = $1
-            GROUP BY user_did
-            HAVING SUM(COALESCE(duration_ms, 210000)) > 0
-        ),
-        percentiles AS (
-            SELECT
-                UNNEST(ARRAY[0,5,10,15,20,25,30,35,40,45,50,55,60,65,70,75,80,85,90,95]) as percentile
-        ),
-        calc AS (
+    let minutes_percentiles: Vec<(i32, f64)> = profiled!(
+        profiler,
+        "minutes_percentiles",
+        QueryCategory::Percentile,
+        sqlx::query(
+            r#"
+            WITH user_minutes AS (
+                SELECT user_did, (SUM(COALESCE(duration_ms, 210000)) / 1000.0 / 60.0)::DOUBLE PRECISION as total_minutes
+                FROM user_plays
+                WHERE played_at >= $1 AND played_at < $2
+                GROUP BY user_did
+                HAVING SUM(COALESCE(duration_ms, 210000)) > 0
+            ),
+            percentiles AS (
+                SELECT
+                    UNNEST(ARRAY[0,5,10,15,20,25,30,35,40,45,50,55,60,65,70,75,80,85,90,95]) as percentile
+            ),
+            calc AS (
+                SELECT
+                    p.percentile,
+                    PERCENTILE_CONT(0.01 * p.percentile) WITHIN GROUP (ORDER BY um.total_minutes) as total_minutes
+                FROM percentiles p
+                CROSS JOIN user_minutes um
+                GROUP BY p.percentile
+                ORDER BY p.percentile
+            )
             SELECT
-                p.percentile,
-                PERCENTILE_CONT(0.01 * p.percentile) WITHIN GROUP (ORDER BY um.total_minutes) as total_minutes
-            FROM percentiles p
-            CROSS JOIN user_minutes um
-            GROUP BY p.percentile
-            ORDER BY p.percentile
+                percentile,
+                CASE WHEN total_minutes IS NULL OR total_minutes < 0 THEN 0 ELSE total_minutes END as total_minutes
+            FROM calc
+            "#,
         )
-        SELECT
-            percentile,
-            CASE WHEN total_minutes IS NULL OR total_minutes < 0 THEN 0 ELSE total_minutes END as total_minutes
-        FROM calc
-        "#,
-    )
-    .bind(year_i32)
-    .fetch_all(pool)
-    .await?
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?
     .into_iter()
     .map(|row| {
         let percentile: i32 = row.get("percentile");
@@ -734,30 +1934,35 @@ pub async fn calculate_global_wrapped_stats(
     })
     .collect();
 
-    let plays_percentiles: Vec<(i32, u32)> = sqlx::query(
-        r#"
-        WITH user_plays AS (
-            SELECT user_did, COUNT(*) as total_plays
-            FROM user_plays
-            WHERE EXTRACT(YEAR FROM played_at) = $1
-            GROUP BY user_did
-        ),
-        percentiles AS (
+    let plays_percentiles: Vec<(i32, u32)> = profiled!(
+        profiler,
+        "plays_percentiles",
+        QueryCategory::Percentile,
+        sqlx::query(
+            r#"
+            WITH user_plays AS (
+                SELECT user_did, COUNT(*) as total_plays
+                FROM user_plays
+                WHERE played_at >= $1 AND played_at < $2
+                GROUP BY user_did
+            ),
+            percentiles AS (
+                SELECT
+                    UNNEST(ARRAY[0,5,10,15,20,25,30,35,40,45,50,55,60,65,70,75,80,85,90,95,100]) as percentile
+            )
             SELECT
-                UNNEST(ARRAY[0,5,10,15,20,25,30,35,40,45,50,55,60,65,70,75,80,85,90,95,100]) as percentile
+                p.percentile,
+                PERCENTILE_CONT(0.01 * p.percentile) WITHIN GROUP (ORDER BY up.total_plays)::INTEGER as total_plays
+            FROM percentiles p
+            CROSS JOIN user_plays up
+            GROUP BY p.percentile
+            ORDER BY p.percentile
+            "#,
         )
-        SELECT
-            p.percentile,
-            PERCENTILE_CONT(0.01 * p.percentile) WITHIN GROUP (ORDER BY up.total_plays)::INTEGER as total_plays
-        FROM percentiles p
-        CROSS JOIN user_plays up
-        GROUP BY p.percentile
-        ORDER BY p.percentile
-        "#,
-    )
-    .bind(year_i32)
-    .fetch_all(pool)
-    .await?
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?
     .into_iter()
     .map(|row| {
         let percentile: i32 = row.get("percentile");
@@ -766,32 +1971,37 @@ pub async fn calculate_global_wrapped_stats(
     })
     .collect();
 
-    let artists_percentiles: Vec<(i32, u32)> = sqlx::query(
-        r#"
-        WITH user_artists AS (
-            SELECT
-                user_did,
-                COUNT(DISTINCT artist->>'artistName') as unique_artists
-            FROM user_plays, jsonb_array_elements(artists) as artist
-            WHERE EXTRACT(YEAR FROM played_at) = $1
-            GROUP BY user_did
-        ),
-        percentiles AS (
+    let artists_percentiles: Vec<(i32, u32)> = profiled!(
+        profiler,
+        "artists_percentiles",
+        QueryCategory::Percentile,
+        sqlx::query(
+            r#"
+            WITH user_artists AS (
+                SELECT
+                    user_did,
+                    COUNT(DISTINCT artist->>'artistName') as unique_artists
+                FROM user_plays, jsonb_array_elements(artists) as artist
+                WHERE played_at >= $1 AND played_at < $2
+                GROUP BY user_did
+            ),
+            percentiles AS (
+                SELECT
+                    UNNEST(ARRAY[0,5,10,15,20,25,30,35,40,45,50,55,60,65,70,75,80,85,90,95,100]) as percentile
+            )
             SELECT
-                UNNEST(ARRAY[0,5,10,15,20,25,30,35,40,45,50,55,60,65,70,75,80,85,90,95,100]) as percentile
+                p.percentile,
+                PERCENTILE_CONT(0.01 * p.percentile) WITHIN GROUP (ORDER BY ua.unique_artists)::INTEGER as unique_artists
+            FROM percentiles p
+            CROSS JOIN user_artists ua
+            GROUP BY p.percentile
+            ORDER BY p.percentile
+            "#,
         )
-        SELECT
-            p.percentile,
-            PERCENTILE_CONT(0.01 * p.percentile) WITHIN GROUP (ORDER BY ua.unique_artists)::INTEGER as unique_artists
-        FROM percentiles p
-        CROSS JOIN user_artists ua
-        GROUP BY p.percentile
-        ORDER BY p.percentile
-        "#,
-    )
-    .bind(year_i32)
-    .fetch_all(pool)
-    .await?
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?
     .into_iter()
     .map(|row| {
         let percentile: i32 = row.get("percentile");
@@ -800,30 +2010,35 @@ pub async fn calculate_global_wrapped_stats(
     })
     .collect();
 
-    let tracks_percentiles: Vec<(i32, u32)> = sqlx::query(
-        r#"
-        WITH user_tracks AS (
-            SELECT user_did, COUNT(DISTINCT track_name) as unique_tracks
-            FROM user_plays
-            WHERE EXTRACT(YEAR FROM played_at) = $1
-            GROUP BY user_did
-        ),
-        percentiles AS (
+    let tracks_percentiles: Vec<(i32, u32)> = profiled!(
+        profiler,
+        "tracks_percentiles",
+        QueryCategory::Percentile,
+        sqlx::query(
+            r#"
+            WITH user_tracks AS (
+                SELECT user_did, COUNT(DISTINCT track_name) as unique_tracks
+                FROM user_plays
+                WHERE played_at >= $1 AND played_at < $2
+                GROUP BY user_did
+            ),
+            percentiles AS (
+                SELECT
+                    UNNEST(ARRAY[0,5,10,15,20,25,30,35,40,45,50,55,60,65,70,75,80,85,90,95,100]) as percentile
+            )
             SELECT
-                UNNEST(ARRAY[0,5,10,15,20,25,30,35,40,45,50,55,60,65,70,75,80,85,90,95,100]) as percentile
+                p.percentile,
+                PERCENTILE_CONT(0.01 * p.percentile) WITHIN GROUP (ORDER BY ut.unique_tracks)::INTEGER as unique_tracks
+            FROM percentiles p
+            CROSS JOIN user_tracks ut
+            GROUP BY p.percentile
+            ORDER BY p.percentile
+            "#,
         )
-        SELECT
-            p.percentile,
-            PERCENTILE_CONT(0.01 * p.percentile) WITHIN GROUP (ORDER BY ut.unique_tracks)::INTEGER as unique_tracks
-        FROM percentiles p
-        CROSS JOIN user_tracks ut
-        GROUP BY p.percentile
-        ORDER BY p.percentile
-        "#,
-    )
-    .bind(year_i32)
-    .fetch_all(pool)
-    .await?
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+    )?
     .into_iter()
     .map(|row| {
         let percentile: i32 = row.get("percentile");
@@ -832,6 +2047,30 @@ pub async fn calculate_global_wrapped_stats(
     })
     .collect();
 
+    let rising_artist_rows = profiled!(
+        profiler,
+        "top_rising_artists_global",
+        QueryCategory::PerArtist,
+        sqlx::query(
+            r#"
+            SELECT
+                artist->>'artistName' as name,
+                COUNT(*) FILTER (WHERE played_at > now() - make_interval(days => $1)) as recent_plays,
+                EXTRACT(EPOCH FROM (now() - MIN(played_at))) / 86400.0 as days_since_first_play
+            FROM user_plays, jsonb_array_elements(artists) as artist
+            GROUP BY artist->>'artistName'
+            HAVING COUNT(*) FILTER (WHERE played_at > now() - make_interval(days => $1)) > 0
+            "#,
+        )
+        .bind(HOTNESS_WINDOW_DAYS as i32)
+        .fetch_all(pool)
+    )?;
+
+    let top_rising_artists = top_rising_from_rows(rising_artist_rows);
+
+    let engagement_cohorts =
+        get_active_user_sets(pool, start..end, &ENGAGEMENT_COHORT_THRESHOLDS_MINUTES).await?;
+
     Ok(GlobalWrappedStats {
         verified_minutes,
         total_users: total_users as u32,
@@ -847,17 +2086,19 @@ pub async fn calculate_global_wrapped_stats(
             artists_percentiles,
             tracks_percentiles,
         },
+        top_rising_artists,
+        engagement_cohorts,
     })
 }
 
 pub async fn get_cached_global_wrapped(
     pool: &PgPool,
-    year: u32,
+    window: ReportWindow,
 ) -> Result<Option<GlobalWrappedStats>> {
     let cached = sqlx::query(
-        "SELECT data FROM wrapped_cache WHERE user_did = 'global' AND year = $1",
+        "SELECT data FROM wrapped_cache WHERE user_did = 'global' AND window_key = $1",
     )
-    .bind(year as i32)
+    .bind(window.cache_key())
     .fetch_optional(pool)
     .await?;
 
@@ -868,20 +2109,20 @@ pub async fn get_cached_global_wrapped(
 
 pub async fn cache_global_wrapped(
     pool: &PgPool,
-    year: u32,
+    window: ReportWindow,
     stats: &GlobalWrappedStats,
 ) -> Result<()> {
     let json_data = serde_json::to_value(stats)?;
 
     sqlx::query(
         r#"
-        INSERT INTO wrapped_cache (user_did, year, data)
+        INSERT INTO wrapped_cache (user_did, window_key, data)
         VALUES ('global', $1, $2)
-        ON CONFLICT (user_did, year)
+        ON CONFLICT (user_did, window_key)
         DO UPDATE SET data = $2, created_at = NOW()
         "#,
     )
-    .bind(year as i32)
+    .bind(window.cache_key())
     .bind(json_data)
     .execute(pool)
     .await?;
@@ -889,26 +2130,80 @@ pub async fn cache_global_wrapped(
     Ok(())
 }
 
-fn calculate_longest_streak(daily_plays: &HashMap<NaiveDate, u32>) -> u32 {
+/// Normalized Shannon entropy over per-track play counts: 0.0 for a single track played
+/// over and over, approaching 1.0 as plays spread evenly across many distinct tracks.
+fn calculate_listening_entropy(track_play_counts: &[i64]) -> f64 {
+    let unique_tracks = track_play_counts.len();
+    if unique_tracks <= 1 {
+        return 0.0;
+    }
+
+    let total: i64 = track_play_counts.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+
+    let entropy: f64 = track_play_counts
+        .iter()
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum();
+
+    entropy / (unique_tracks as f64).log2()
+}
+
+fn calculate_streak_stats(daily_plays: &HashMap<NaiveDate, u32>) -> StreakStats {
     let mut dates: Vec<NaiveDate> = daily_plays.keys().copied().collect();
     dates.sort();
 
+    let Some(&first_date) = dates.first() else {
+        return StreakStats::default();
+    };
+
     let mut longest = 0;
+    let mut longest_start = first_date;
+    let mut longest_end = first_date;
+    let mut run_start = first_date;
     let mut current = 0;
 
     for i in 0..dates.len() {
         if i == 0 {
             current = 1;
+            run_start = dates[0];
         } else {
             let diff = dates[i].signed_duration_since(dates[i - 1]).num_days();
             if diff == 1 {
                 current += 1;
             } else {
-                longest = longest.max(current);
+                if current > longest {
+                    longest = current;
+                    longest_start = run_start;
+                    longest_end = dates[i - 1];
+                }
                 current = 1;
+                run_start = dates[i];
             }
         }
     }
+    if current > longest {
+        longest = current;
+        longest_start = run_start;
+        longest_end = dates[dates.len() - 1];
+    }
 
-    longest.max(current)
+    // The run ending at the most recent play date is only "current" if that date is today or
+    // yesterday - an old streak that already ended shouldn't be reported as ongoing.
+    let most_recent = *dates.last().unwrap();
+    let is_ongoing = (Utc::now().date_naive() - most_recent).num_days() <= 1;
+
+    StreakStats {
+        longest,
+        longest_start: Some(longest_start),
+        longest_end: Some(longest_end),
+        current: if is_ongoing { current } else { 0 },
+        current_start: is_ongoing.then_some(run_start),
+        current_end: is_ongoing.then_some(most_recent),
+    }
 }